@@ -4,11 +4,21 @@
 mod types;
 mod validation;
 
-use soroban_sdk::{contract, contractimpl, panic_with_error, token, Address, Env, Vec};
+use soroban_sdk::{
+    contract, contractimpl, panic_with_error, token, Address, BytesN, Env, IntoVal, InvokeError,
+    Map, String, Symbol, Val, Vec,
+};
 
 pub use crate::types::{
-    BatchBurnResult, BatchTransferResult, BurnRequest, BurnResult, DataKey, TransferEvents,
-    TransferRequest, TransferResult, MAX_BATCH_SIZE,
+    encode_batch_log, AddressInfo, BatchBurnResult, BatchRecord, BatchTransferResult, BurnRequest,
+    BurnResult, Claim, DataKey, EnsureWalletsResult, ErrorCode, EventMode, ExtDataKey, Governance,
+    MemoTransferRequest, OperatorUsage, OrderMode, PendingFeeCollector, PendingKind, PendingOp,
+    RateLimit, RateLimitWindow, RegistryWallet, RegistryWalletRequest, ReversalState,
+    ReversibleTransfer, RoundingMode, ScheduledBatch, ScheduledTransfer, StatSnapshot, Stream,
+    TokenStats, TransferEvents, TransferFromRequest, TransferRequest, TransferResult,
+    TransferSchedule, FEE_COLLECTOR_DELAY_LEDGERS, LEDGERS_PER_DAY, MAX_BATCH_SIZE, MAX_FEE_BPS,
+    MAX_OPERATORS, MAX_STAT_SNAPSHOTS, MAX_TOKEN_DECIMALS, RESULT_TTL_EXTEND, RESULT_TTL_THRESHOLD,
+    SAC_ACCOUNT_MISSING, SAC_BALANCE_DEAUTHORIZED, SAC_TRUSTLINE_MISSING, STORAGE_VERSION,
 };
 use crate::validation::{validate_address, validate_amount};
 
@@ -28,6 +38,56 @@ pub enum BatchTransferError {
     BatchTooLarge = 5,
     /// Invalid token contract
     InvalidToken = 6,
+    /// Fee rate exceeds 100%
+    InvalidFee = 7,
+    /// Fee collector can only be changed through the timelock
+    FeeCollectorLocked = 8,
+    /// No pending fee collector change
+    NoPendingFeeCollector = 9,
+    /// Timelock has not elapsed yet
+    TimelockNotElapsed = 10,
+    /// Token has been blocked by the admin
+    TokenBlocked = 11,
+    /// Token decimals out of range
+    InvalidDecimals = 12,
+    /// Contract is paused
+    ContractPaused = 13,
+    /// Sender is not on the sender allowlist
+    SenderNotAllowed = 14,
+    /// Operator list is full
+    TooManyOperators = 15,
+    /// No scheduled batch with the given id
+    ScheduleNotFound = 16,
+    /// Rate limit volume or window is not positive
+    InvalidRateLimit = 17,
+    /// No claim with the given id
+    ClaimNotFound = 18,
+    /// Claim expired and can only be reclaimed by the sender
+    ClaimExpired = 19,
+    /// Claim has not expired yet
+    ClaimNotExpired = 20,
+    /// Partial claim amount is not positive or exceeds what is left
+    ClaimAmountExceeded = 21,
+    /// Lifetime volume cap reached; raise it before unpausing
+    LifetimeCapReached = 22,
+    /// Scheduled transfer was already released
+    AlreadyReleased = 23,
+    /// No wallet registry has been configured
+    WalletRegistryNotSet = 24,
+    /// No pending admin transfer
+    NoPendingAdmin = 25,
+    /// Sender submitted a batch too recently
+    BatchCooldownActive = 26,
+    /// Reversible transfer does not exist
+    TransferNotFound = 27,
+    /// Dispute window of a reversible transfer is still open
+    DisputeWindowOpen = 28,
+    /// Dispute window of a reversible transfer has closed
+    DisputeWindowClosed = 29,
+    /// Reversible transfer was already settled or reversed
+    TransferFinalized = 30,
+    /// Stream does not exist or has been fully paid out
+    StreamNotFound = 31,
 }
 
 impl From<BatchTransferError> for soroban_sdk::Error {
@@ -55,18 +115,74 @@ impl BatchTransferContract {
         env.storage()
             .instance()
             .set(&DataKey::TotalVolumeTransferred, &0i128);
+        env.storage()
+            .instance()
+            .set(&DataKey::StorageVersion, &STORAGE_VERSION);
+    }
+
+    /// Initializes the contract like `initialize`, but returns `false`
+    /// instead of panicking when it is already initialized.
+    ///
+    /// Named `try_init` because the generated client already exposes
+    /// `try_initialize` as the non-panicking form of `initialize`.
+    pub fn try_init(env: Env, admin: Address) -> bool {
+        if env.storage().instance().has(&DataKey::Admin) {
+            return false;
+        }
+        Self::initialize(env, admin);
+        true
+    }
+
+    /// Brings stored data up to the layout this code expects.
+    ///
+    /// Mutating entry points refuse to run until the stored version matches
+    /// `STORAGE_VERSION`; contracts initialized before versioning start at 0.
+    pub fn migrate(env: Env, admin: Address) -> u32 {
+        admin.require_auth();
+        let stored_admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .expect("Contract not initialized");
+        if admin != stored_admin {
+            panic_with_error!(&env, BatchTransferError::Unauthorized);
+        }
+
+        if Self::get_storage_version(env.clone()) < STORAGE_VERSION {
+            env.storage()
+                .instance()
+                .set(&DataKey::StorageVersion, &STORAGE_VERSION);
+        }
+        Self::get_storage_version(env)
+    }
+
+    /// Returns the storage layout version recorded in this contract.
+    pub fn get_storage_version(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::StorageVersion)
+            .unwrap_or(0)
     }
 
     /// Executes batch transfers of XLM to multiple recipients.
+    ///
+    /// `caller` authorizes the arguments `(token, transfers)`, so a signed
+    /// authorization is only valid for this exact set of transfers.
     pub fn batch_transfer(
         env: Env,
         caller: Address,
         token: Address,
         transfers: Vec<TransferRequest>,
     ) -> BatchTransferResult {
-        // Verify authorization
-        caller.require_auth();
-        Self::require_admin(&env, &caller);
+        // Bind the authorization to this exact payload so a signature cannot
+        // be replayed with different recipients or amounts
+        caller.require_auth_for_args((token.clone(), transfers.clone()).into_val(&env));
+        Self::require_operator(&env, &caller);
+        Self::require_not_paused(&env);
+        Self::require_token_allowed(&env, &token);
+        Self::require_expected_decimals(&env, &token);
+        Self::require_sender_allowed(&env, &caller);
+        Self::enforce_batch_cooldown(&env, &caller);
 
         // Validate batch size
         let request_count = transfers.len();
@@ -77,177 +193,233 @@ impl BatchTransferContract {
             panic_with_error!(&env, BatchTransferError::BatchTooLarge);
         }
 
-        // Get batch ID and increment
-        let batch_id: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::TotalBatches)
-            .unwrap_or(0)
-            + 1;
+        Self::execute_batch(&env, &caller, &token, &transfers)
+    }
 
-        // Emit batch started event
-        TransferEvents::batch_started(&env, batch_id, request_count);
+    /// Executes a batch like `batch_transfer` after checking that the valid
+    /// request amounts sum to `expected_total`, panicking with "Total
+    /// mismatch" otherwise. An `expected_total` of -1 skips the check.
+    pub fn batch_transfer_checked(
+        env: Env,
+        caller: Address,
+        token: Address,
+        transfers: Vec<TransferRequest>,
+        expected_total: i128,
+    ) -> BatchTransferResult {
+        caller.require_auth();
+        Self::require_operator(&env, &caller);
+        Self::require_not_paused(&env);
+        Self::require_token_allowed(&env, &token);
+        Self::require_expected_decimals(&env, &token);
+        Self::require_sender_allowed(&env, &caller);
+        Self::enforce_batch_cooldown(&env, &caller);
 
-        // Initialize result vectors
-        let mut results: Vec<TransferResult> = Vec::new(&env);
-        let mut successful_count: u32 = 0;
-        let mut failed_count: u32 = 0;
-        let mut total_transferred: i128 = 0;
+        let request_count = transfers.len();
+        if request_count == 0 {
+            panic_with_error!(&env, BatchTransferError::EmptyBatch);
+        }
+        if request_count > MAX_BATCH_SIZE {
+            panic_with_error!(&env, BatchTransferError::BatchTooLarge);
+        }
 
-        // Create token client
-        let token_client = token::Client::new(&env, &token);
+        if expected_total != -1 {
+            let mut total: Option<i128> = Some(0);
+            for request in transfers.iter() {
+                if validate_address(&env, &request.recipient).is_ok()
+                    && validate_amount(request.amount).is_ok()
+                {
+                    total = total.and_then(|total| total.checked_add(request.amount));
+                }
+            }
+            if total != Some(expected_total) {
+                panic!("Total mismatch");
+            }
+        }
 
-        // Get initial balance
-        let mut available_balance = token_client.balance(&caller);
+        Self::execute_batch(&env, &caller, &token, &transfers)
+    }
 
-        // Calculate total needed for all valid transfers and validate upfront
-        let mut total_needed: i128 = 0;
-        let mut validated_requests: Vec<(TransferRequest, bool, u32)> = Vec::new(&env);
+    /// Executes a batch like `batch_transfer`, but in the given `order`, so an
+    /// underfunded sender pays the largest (or smallest) transfers first.
+    /// Results are still reported in input order.
+    pub fn batch_transfer_ordered(
+        env: Env,
+        caller: Address,
+        token: Address,
+        transfers: Vec<TransferRequest>,
+        order: OrderMode,
+    ) -> BatchTransferResult {
+        caller.require_auth();
+        Self::require_operator(&env, &caller);
+        Self::require_not_paused(&env);
+        Self::require_token_allowed(&env, &token);
+        Self::require_expected_decimals(&env, &token);
+        Self::require_sender_allowed(&env, &caller);
+        Self::enforce_batch_cooldown(&env, &caller);
 
-        // First pass: Validate all requests and calculate total needed
-        for request in transfers.iter() {
-            let mut is_valid = true;
-            let mut error_code = 0u32;
+        let request_count = transfers.len();
+        if request_count == 0 {
+            panic_with_error!(&env, BatchTransferError::EmptyBatch);
+        }
+        if request_count > MAX_BATCH_SIZE {
+            panic_with_error!(&env, BatchTransferError::BatchTooLarge);
+        }
 
-            // Validate recipient address
-            if validate_address(&env, &request.recipient).is_err() {
-                is_valid = false;
-                error_code = 0; // Invalid address
-            }
-            // Validate amount
-            else if validate_amount(request.amount).is_err() {
-                is_valid = false;
-                error_code = 1; // Invalid amount
-            }
+        Self::execute_batch_with_memos(&env, &caller, &token, &transfers, None, order)
+    }
 
-            if is_valid {
-                total_needed = total_needed
-                    .checked_add(request.amount)
-                    .unwrap_or(i128::MAX);
-            }
+    /// Executes a batch after creating a wallet in the wallet registry for
+    /// every recipient that lacks one, so onboarding and payment happen in one
+    /// call.
+    ///
+    /// Missing wallets are created in a single `batch_create_wallets` call
+    /// made with `admin`, which must therefore also administer the registry.
+    /// Transfers proceed whether or not creation succeeded; the result records
+    /// both outcomes.
+    pub fn batch_transfer_ensure_wallets(
+        env: Env,
+        admin: Address,
+        token: Address,
+        transfers: Vec<TransferRequest>,
+    ) -> EnsureWalletsResult {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_not_paused(&env);
+        Self::require_token_allowed(&env, &token);
+        Self::require_expected_decimals(&env, &token);
+        Self::require_sender_allowed(&env, &admin);
+        Self::enforce_batch_cooldown(&env, &admin);
 
-            validated_requests.push_back((request.clone(), is_valid, error_code));
+        let request_count = transfers.len();
+        if request_count == 0 {
+            panic_with_error!(&env, BatchTransferError::EmptyBatch);
         }
+        if request_count > MAX_BATCH_SIZE {
+            panic_with_error!(&env, BatchTransferError::BatchTooLarge);
+        }
+        let registry: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::WalletRegistry)
+            .unwrap_or_else(|| panic_with_error!(&env, BatchTransferError::WalletRegistryNotSet));
 
-        // Second pass: Process each request
-        for (request, is_valid, error_code) in validated_requests.iter() {
-            if !is_valid {
-                // Validation failed - record and continue
-                results.push_back(TransferResult::Failure(
-                    request.recipient.clone(),
-                    request.amount,
-                    error_code.clone(),
-                ));
-                failed_count += 1;
-                TransferEvents::transfer_failure(
-                    &env,
-                    batch_id,
-                    &request.recipient,
-                    request.amount,
-                    error_code.clone(),
-                );
-                continue;
-            }
-
-            // Check balance for this transfer
-            if available_balance < request.amount {
-                // Insufficient balance
-                results.push_back(TransferResult::Failure(
-                    request.recipient.clone(),
-                    request.amount,
-                    2, // Insufficient balance
-                ));
-                failed_count += 1;
-                TransferEvents::transfer_failure(
-                    &env,
-                    batch_id,
-                    &request.recipient,
-                    request.amount,
-                    2,
-                );
+        let mut missing: Map<Address, bool> = Map::new(&env);
+        let mut create_requests: Vec<RegistryWalletRequest> = Vec::new(&env);
+        for request in transfers.iter() {
+            if missing.contains_key(request.recipient.clone())
+                || Self::registry_wallet(&env, &request.recipient).is_some()
+            {
                 continue;
             }
+            missing.set(request.recipient.clone(), true);
+            create_requests.push_back(RegistryWalletRequest {
+                owner: request.recipient.clone(),
+                guardians: Vec::new(&env),
+                active: true,
+            });
+        }
 
-            // Execute transfer
-            // Note: After thorough validation, transfers should succeed.
-            // If a transfer fails due to contract-level issues (authorization, etc.),
-            // it will panic and revert the entire batch. This is acceptable as
-            // we've validated all inputs and balances.
-            token_client.transfer(&caller, &request.recipient, &request.amount);
+        if !create_requests.is_empty() {
+            // Per-wallet outcomes are read back below, so the summary is ignored
+            let _ = env.try_invoke_contract::<Val, InvokeError>(
+                &registry,
+                &Symbol::new(&env, "batch_create_wallets"),
+                (admin.clone(), create_requests).into_val(&env),
+            );
+        }
 
-            // Transfer succeeded
-            available_balance -= request.amount;
-            results.push_back(TransferResult::Success(
-                request.recipient.clone(),
-                request.amount,
-            ));
-            successful_count += 1;
-            total_transferred = total_transferred
-                .checked_add(request.amount)
-                .unwrap_or(total_transferred);
+        let mut wallets_created: Vec<bool> = Vec::new(&env);
+        for request in transfers.iter() {
+            let created = missing.get(request.recipient.clone()).unwrap_or(false)
+                && Self::registry_wallet(&env, &request.recipient).is_some();
+            wallets_created.push_back(created);
+        }
 
-            TransferEvents::transfer_success(&env, batch_id, &request.recipient, request.amount);
+        EnsureWalletsResult {
+            wallets_created,
+            transfers: Self::execute_batch(&env, &admin, &token, &transfers),
         }
+    }
 
-        // Update storage (batched at the end for efficiency)
-        let total_batches: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::TotalBatches)
-            .unwrap_or(0);
-        let total_processed: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::TotalTransfersProcessed)
-            .unwrap_or(0);
-        let total_volume: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::TotalVolumeTransferred)
-            .unwrap_or(0);
+    /// Distributes shares of `caller`'s current balance, given per recipient
+    /// in basis points summing to at most 10_000.
+    ///
+    /// The balance is read once; `rounding` decides where rounding dust goes
+    /// and defaults to `RoundingMode::RemainderToFirst`. Fees are charged on
+    /// top of each share, so a full distribution only succeeds entirely when
+    /// no fee is configured.
+    pub fn batch_transfer_balance_pct(
+        env: Env,
+        caller: Address,
+        token: Address,
+        shares: Vec<(Address, u32)>,
+        rounding: Option<RoundingMode>,
+    ) -> BatchTransferResult {
+        caller.require_auth();
+        Self::require_operator(&env, &caller);
+        Self::require_not_paused(&env);
+        Self::require_token_allowed(&env, &token);
+        Self::require_expected_decimals(&env, &token);
+        Self::require_sender_allowed(&env, &caller);
+        Self::enforce_batch_cooldown(&env, &caller);
 
-        env.storage()
-            .instance()
-            .set(&DataKey::TotalBatches, &(total_batches + 1));
-        env.storage().instance().set(
-            &DataKey::TotalTransfersProcessed,
-            &(total_processed + request_count as u64),
-        );
-        env.storage().instance().set(
-            &DataKey::TotalVolumeTransferred,
-            &total_transferred
-                .checked_add(total_volume)
-                .unwrap_or(i128::MAX),
-        );
+        let request_count = shares.len();
+        if request_count == 0 {
+            panic_with_error!(&env, BatchTransferError::EmptyBatch);
+        }
+        if request_count > MAX_BATCH_SIZE {
+            panic_with_error!(&env, BatchTransferError::BatchTooLarge);
+        }
+        let mut total_bps: u32 = 0;
+        for (_, bps) in shares.iter() {
+            total_bps = total_bps.saturating_add(bps);
+        }
+        if total_bps > 10_000 {
+            panic_with_error!(&env, BatchTransferError::InvalidBatch);
+        }
 
-        // Emit batch completed event
-        TransferEvents::batch_completed(
-            &env,
-            batch_id,
-            successful_count,
-            failed_count,
-            total_transferred,
+        let balance = token::Client::new(&env, &token).balance(&caller);
+        let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+        let mut fractions: Vec<i128> = Vec::new(&env);
+        let mut allocated: i128 = 0;
+        for (recipient, bps) in shares.iter() {
+            let amount = Self::bps_of(balance, bps);
+            allocated += amount;
+            transfers.push_back(TransferRequest { recipient, amount });
+            // Part of `balance * bps / 10_000` lost to rounding, in 1/10_000 units
+            fractions.push_back(balance % 10_000 * bps as i128 % 10_000);
+        }
+        let dust = Self::bps_of(balance, total_bps) - allocated;
+        Self::distribute_dust(
+            &mut transfers,
+            &fractions,
+            dust,
+            rounding.unwrap_or(RoundingMode::RemainderToFirst),
         );
 
-        BatchTransferResult {
-            total_requests: request_count,
-            successful: successful_count,
-            failed: failed_count,
-            total_transferred,
-            results,
-        }
+        Self::execute_batch(&env, &caller, &token, &transfers)
     }
 
-    pub fn batch_burn(
+    /// Executes batch transfers tagged with memos, e.g. invoice references.
+    ///
+    /// Each successful item emits a `memo` event next to its transfer event.
+    /// When `set_unique_memos` is enabled, an item repeating an earlier memo
+    /// in the batch fails with `ErrorCode::DUPLICATE_MEMO`.
+    pub fn batch_transfer_with_memos(
         env: Env,
         caller: Address,
         token: Address,
-        burns: Vec<BurnRequest>,
-    ) -> BatchBurnResult {
+        transfers: Vec<MemoTransferRequest>,
+    ) -> BatchTransferResult {
         caller.require_auth();
-        Self::require_admin(&env, &caller);
+        Self::require_operator(&env, &caller);
+        Self::require_not_paused(&env);
+        Self::require_token_allowed(&env, &token);
+        Self::require_expected_decimals(&env, &token);
+        Self::require_sender_allowed(&env, &caller);
+        Self::enforce_batch_cooldown(&env, &caller);
 
-        let request_count = burns.len();
+        let request_count = transfers.len();
         if request_count == 0 {
             panic_with_error!(&env, BatchTransferError::EmptyBatch);
         }
@@ -255,139 +427,3536 @@ impl BatchTransferContract {
             panic_with_error!(&env, BatchTransferError::BatchTooLarge);
         }
 
-        let batch_id: u64 = env
+        let mut requests: Vec<TransferRequest> = Vec::new(&env);
+        let mut memos: Vec<String> = Vec::new(&env);
+        for transfer in transfers.iter() {
+            requests.push_back(TransferRequest {
+                recipient: transfer.recipient,
+                amount: transfer.amount,
+            });
+            memos.push_back(transfer.memo);
+        }
+
+        Self::execute_batch_with_memos(
+            &env,
+            &caller,
+            &token,
+            &requests,
+            Some(&memos),
+            OrderMode::AsGiven,
+        )
+    }
+
+    /// Executes batch transfers at most once per `batch_key`.
+    ///
+    /// If a batch with the same key already executed, its stored result is
+    /// returned without moving any funds, so clients can safely retry after a
+    /// timeout.
+    pub fn batch_transfer_idempotent(
+        env: Env,
+        caller: Address,
+        token: Address,
+        transfers: Vec<TransferRequest>,
+        batch_key: BytesN<32>,
+    ) -> BatchTransferResult {
+        caller.require_auth();
+        Self::require_operator(&env, &caller);
+
+        let storage_key = DataKey::BatchResult(batch_key);
+        if let Some(stored) = env
             .storage()
-            .instance()
-            .get(&DataKey::TotalBatches)
-            .unwrap_or(0)
-            + 1;
+            .persistent()
+            .get::<_, BatchTransferResult>(&storage_key)
+        {
+            env.storage().persistent().extend_ttl(
+                &storage_key,
+                RESULT_TTL_THRESHOLD,
+                RESULT_TTL_EXTEND,
+            );
+            return stored;
+        }
 
-        TransferEvents::batch_started(&env, batch_id, request_count);
+        Self::require_not_paused(&env);
+        Self::require_token_allowed(&env, &token);
+        Self::require_expected_decimals(&env, &token);
+        Self::require_sender_allowed(&env, &caller);
+        Self::enforce_batch_cooldown(&env, &caller);
 
-        let token_client = token::Client::new(&env, &token);
+        let request_count = transfers.len();
+        if request_count == 0 {
+            panic_with_error!(&env, BatchTransferError::EmptyBatch);
+        }
+        if request_count > MAX_BATCH_SIZE {
+            panic_with_error!(&env, BatchTransferError::BatchTooLarge);
+        }
 
-        let mut results: Vec<BurnResult> = Vec::new(&env);
-        let mut successful_count: u32 = 0;
-        let mut failed_count: u32 = 0;
-        let mut total_burned: i128 = 0;
+        let result = Self::execute_batch(&env, &caller, &token, &transfers);
 
-        for request in burns.iter() {
-            let mut is_valid = true;
-            let mut error_code = 0u32;
+        env.storage().persistent().set(&storage_key, &result);
+        env.storage().persistent().extend_ttl(
+            &storage_key,
+            RESULT_TTL_THRESHOLD,
+            RESULT_TTL_EXTEND,
+        );
 
-            if validate_address(&env, &request.owner).is_err() {
-                is_valid = false;
-                error_code = 0;
-            } else if validate_amount(request.amount).is_err() {
-                is_valid = false;
-                error_code = 1;
-            }
+        result
+    }
 
-            if !is_valid {
-                results.push_back(BurnResult::Failure(
-                    request.owner.clone(),
-                    request.amount,
-                    error_code,
-                ));
-                failed_count += 1;
-                TransferEvents::burn_failure(
-                    &env,
-                    batch_id,
-                    &request.owner,
-                    request.amount,
-                    error_code,
-                );
-                continue;
-            }
+    /// Returns whether a batch with the given key has already executed.
+    pub fn is_batch_key_used(env: Env, batch_key: BytesN<32>) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::BatchResult(batch_key))
+    }
 
-            let balance = token_client.balance(&request.owner);
-            if balance < request.amount {
-                results.push_back(BurnResult::Failure(
-                    request.owner.clone(),
+    /// Returns whether the batch stored under `batch_key` had failed items,
+    /// or `None` if no batch executed with that key.
+    pub fn batch_key_had_failures(env: Env, batch_key: BytesN<32>) -> Option<bool> {
+        env.storage()
+            .persistent()
+            .get::<_, BatchTransferResult>(&DataKey::BatchResult(batch_key))
+            .map(|result| result.failed > 0)
+    }
+
+    /// Returns the full result of the most recently executed batch, if any.
+    pub fn get_last_batch_result(env: Env) -> Option<BatchTransferResult> {
+        let key = DataKey::LastBatchResult;
+        let result = env.storage().persistent().get(&key);
+        if result.is_some() {
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, RESULT_TTL_THRESHOLD, RESULT_TTL_EXTEND);
+        }
+        result
+    }
+
+    /// Executes batch transfers, processing the vector in chunks of `chunk_size`.
+    ///
+    /// Each chunk is recorded as its own batch with a `batch_started` /
+    /// `batch_completed` event pair, while the caller receives a single
+    /// aggregated result covering every request in input order.
+    pub fn batch_transfer_chunked(
+        env: Env,
+        caller: Address,
+        token: Address,
+        transfers: Vec<TransferRequest>,
+        chunk_size: u32,
+    ) -> BatchTransferResult {
+        caller.require_auth();
+        Self::require_operator(&env, &caller);
+        Self::require_not_paused(&env);
+        Self::require_token_allowed(&env, &token);
+        Self::require_expected_decimals(&env, &token);
+        Self::require_sender_allowed(&env, &caller);
+        Self::enforce_batch_cooldown(&env, &caller);
+
+        let request_count = transfers.len();
+        if request_count == 0 {
+            panic_with_error!(&env, BatchTransferError::EmptyBatch);
+        }
+        if chunk_size == 0 || chunk_size > MAX_BATCH_SIZE {
+            panic_with_error!(&env, BatchTransferError::InvalidBatch);
+        }
+
+        let mut aggregate = BatchTransferResult {
+            token: token.clone(),
+            total_requests: 0,
+            successful: 0,
+            failed: 0,
+            attempted: 0,
+            not_attempted: 0,
+            merged_count: 0,
+            total_transferred: 0,
+            results: Vec::new(&env),
+        };
+
+        let mut start: u32 = 0;
+        while start < request_count {
+            let end = core::cmp::min(start.saturating_add(chunk_size), request_count);
+            let chunk = transfers.slice(start..end);
+            let result = Self::execute_batch(&env, &caller, &token, &chunk);
+
+            aggregate.total_requests += result.total_requests;
+            aggregate.successful += result.successful;
+            aggregate.failed += result.failed;
+            aggregate.attempted += result.attempted;
+            aggregate.not_attempted += result.not_attempted;
+            aggregate.merged_count += result.merged_count;
+            aggregate.total_transferred = aggregate
+                .total_transferred
+                .checked_add(result.total_transferred)
+                .unwrap_or(i128::MAX);
+            aggregate.results.append(&result.results);
+
+            start = end;
+        }
+
+        aggregate
+    }
+
+    /// Pulls tokens from many accounts into `recipient` using allowances
+    /// granted to this contract.
+    ///
+    /// Each `from` account's allowance and balance are checked before the
+    /// token call, so underfunded entries fail fast with a specific code
+    /// instead of reverting the batch. Results are keyed by the `from` address.
+    pub fn batch_transfer_from(
+        env: Env,
+        caller: Address,
+        token: Address,
+        recipient: Address,
+        requests: Vec<TransferFromRequest>,
+    ) -> BatchTransferResult {
+        caller.require_auth();
+        Self::require_operator(&env, &caller);
+        Self::require_not_paused(&env);
+        Self::require_token_allowed(&env, &token);
+        Self::require_expected_decimals(&env, &token);
+
+        let request_count = requests.len();
+        if request_count == 0 {
+            panic_with_error!(&env, BatchTransferError::EmptyBatch);
+        }
+        if request_count > MAX_BATCH_SIZE {
+            panic_with_error!(&env, BatchTransferError::BatchTooLarge);
+        }
+
+        let batch_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalBatches)
+            .unwrap_or(0)
+            + 1;
+        TransferEvents::batch_started(&env, batch_id, request_count);
+
+        let token_client = token::Client::new(&env, &token);
+        let spender = env.current_contract_address();
+        let large_threshold = Self::get_large_transfer_alert(env.clone());
+        let mut results: Vec<TransferResult> = Vec::new(&env);
+        let mut successful_count: u32 = 0;
+        let mut failed_count: u32 = 0;
+        let mut total_transferred: i128 = 0;
+
+        for request in requests.iter() {
+            let wallet_budget = Self::wallet_budget(&env, &request.from, &token);
+            let error_code = if validate_address(&env, &request.from).is_err() {
+                Some(ErrorCode::INVALID_ADDRESS)
+            } else if validate_amount(request.amount).is_err() {
+                Some(ErrorCode::INVALID_AMOUNT)
+            } else if !Self::is_sender_allowed(env.clone(), request.from.clone())
+                || Self::is_sender_suspended(env.clone(), request.from.clone())
+            {
+                Some(ErrorCode::SENDER_NOT_ALLOWED)
+            } else if token_client.allowance(&request.from, &spender) < request.amount {
+                Some(ErrorCode::INSUFFICIENT_ALLOWANCE)
+            } else if token_client.balance(&request.from) < request.amount {
+                Some(ErrorCode::INSUFFICIENT_BALANCE)
+            } else if wallet_budget.as_ref().is_some_and(|(limit, usage)| {
+                usage
+                    .used
+                    .checked_add(request.amount)
+                    .is_none_or(|used| used > *limit)
+            }) {
+                Some(ErrorCode::WALLET_LIMIT_EXCEEDED)
+            } else if Self::recipient_cap_exceeded(&env, &recipient, request.amount) {
+                Some(ErrorCode::RECIPIENT_CAP_EXCEEDED)
+            } else {
+                None
+            };
+
+            if let Some(error_code) = error_code {
+                results.push_back(TransferResult::Failure(
+                    request.from.clone(),
                     request.amount,
-                    2,
+                    error_code,
                 ));
                 failed_count += 1;
-                TransferEvents::burn_failure(
+                Self::record_failure(&env, error_code);
+                TransferEvents::transfer_failure(
                     &env,
                     batch_id,
-                    &request.owner,
+                    &request.from,
                     request.amount,
-                    2,
+                    error_code,
                 );
                 continue;
             }
 
-            request.owner.require_auth();
-            token_client.burn(&request.owner, &request.amount);
+            token_client.transfer_from(&spender, &request.from, &recipient, &request.amount);
+            Self::record_received(&env, &recipient, request.amount);
+            if let Some((_, mut usage)) = wallet_budget {
+                usage.used += request.amount;
+                env.storage().persistent().set(
+                    &ExtDataKey::WalletSpend(request.from.clone(), token.clone()),
+                    &usage,
+                );
+            }
 
-            results.push_back(BurnResult::Success(
-                request.owner.clone(),
+            results.push_back(TransferResult::Success(
+                request.from.clone(),
                 request.amount,
             ));
             successful_count += 1;
-            total_burned = total_burned
+            total_transferred = total_transferred
                 .checked_add(request.amount)
-                .unwrap_or(total_burned);
-
-            TransferEvents::burn_success(&env, batch_id, &request.owner, request.amount);
+                .unwrap_or(total_transferred);
+            TransferEvents::transfer_success(&env, batch_id, &request.from, request.amount);
+            if large_threshold > 0 && request.amount >= large_threshold {
+                TransferEvents::large_transfer(
+                    &env,
+                    batch_id,
+                    &request.from,
+                    &recipient,
+                    request.amount,
+                );
+            }
         }
 
-        TransferEvents::burn_batch_completed(
+        Self::record_batch_stats(
+            &env,
+            &BatchRecord {
+                batch_id,
+                sender: caller.clone(),
+                token: token.clone(),
+                total_requests: request_count,
+                successful: successful_count,
+                failed: failed_count,
+                total_transferred,
+                ledger: env.ledger().sequence(),
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+        TransferEvents::batch_completed(
             &env,
             batch_id,
             successful_count,
             failed_count,
-            total_burned,
+            total_transferred,
         );
 
-        BatchBurnResult {
+        let attempted = successful_count + failed_count;
+        let result = BatchTransferResult {
+            token: token.clone(),
             total_requests: request_count,
             successful: successful_count,
             failed: failed_count,
-            total_burned,
+            attempted,
+            not_attempted: request_count - attempted,
+            merged_count: 0,
+            total_transferred,
             results,
-        }
+        };
+        Self::store_last_result(&env, &result);
+        result
     }
 
-    /// Returns the admin address.
-    pub fn get_admin(env: Env) -> Address {
+    /// Stores a batch to be executed from `caller`'s balance once
+    /// `execute_after_ledger` is reached, returning its schedule id.
+    ///
+    /// The submitter is recorded so it can cancel its own schedule.
+    pub fn schedule_batch(
+        env: Env,
+        caller: Address,
+        token: Address,
+        transfers: Vec<TransferRequest>,
+        execute_after_ledger: u32,
+    ) -> u64 {
+        caller.require_auth();
+        Self::require_operator(&env, &caller);
+        Self::require_not_paused(&env);
+        Self::require_token_allowed(&env, &token);
+
+        let request_count = transfers.len();
+        if request_count == 0 {
+            panic_with_error!(&env, BatchTransferError::EmptyBatch);
+        }
+        if request_count > MAX_BATCH_SIZE {
+            panic_with_error!(&env, BatchTransferError::BatchTooLarge);
+        }
+
+        let schedule_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::NextScheduleId)
+            .unwrap_or(1);
         env.storage()
             .instance()
-            .get(&DataKey::Admin)
-            .expect("Contract not initialized")
+            .set(&DataKey::NextScheduleId, &(schedule_id + 1));
+
+        let storage_key = DataKey::ScheduledBatch(schedule_id);
+        env.storage().persistent().set(
+            &storage_key,
+            &ScheduledBatch {
+                submitter: caller.clone(),
+                token,
+                transfers,
+                execute_after_ledger,
+            },
+        );
+        env.storage().persistent().extend_ttl(
+            &storage_key,
+            RESULT_TTL_THRESHOLD,
+            RESULT_TTL_EXTEND,
+        );
+
+        TransferEvents::batch_scheduled(&env, schedule_id, &caller, execute_after_ledger);
+        schedule_id
     }
 
-    /// Updates the admin address.
-    pub fn set_admin(env: Env, current_admin: Address, new_admin: Address) {
-        current_admin.require_auth();
-        Self::require_admin(&env, &current_admin);
+    /// Executes a scheduled batch once its ledger has been reached.
+    ///
+    /// Funds move from the submitter, who must authorize the execution and
+    /// still hold operator rights.
+    pub fn execute_scheduled(env: Env, schedule_id: u64) -> BatchTransferResult {
+        let scheduled = Self::load_scheduled(&env, schedule_id);
+        scheduled.submitter.require_auth();
+        Self::require_operator(&env, &scheduled.submitter);
+        Self::require_not_paused(&env);
+        Self::require_token_allowed(&env, &scheduled.token);
+        Self::require_expected_decimals(&env, &scheduled.token);
+        Self::require_sender_allowed(&env, &scheduled.submitter);
+        if env.ledger().sequence() < scheduled.execute_after_ledger {
+            panic_with_error!(&env, BatchTransferError::TimelockNotElapsed);
+        }
 
-        env.storage().instance().set(&DataKey::Admin, &new_admin);
+        env.storage()
+            .persistent()
+            .remove(&DataKey::ScheduledBatch(schedule_id));
+        let result = Self::execute_batch(
+            &env,
+            &scheduled.submitter,
+            &scheduled.token,
+            &scheduled.transfers,
+        );
+        TransferEvents::schedule_executed(&env, schedule_id, Self::get_total_batches(env.clone()));
+        result
     }
 
-    /// Returns the total number of batches processed.
-    pub fn get_total_batches(env: Env) -> u64 {
+    /// Cancels a scheduled batch.
+    ///
+    /// Callable by the admin or by the operator that submitted the schedule.
+    pub fn cancel_scheduled(env: Env, caller: Address, schedule_id: u64) {
+        caller.require_auth();
+        Self::require_storage_current(&env);
+        let scheduled = Self::load_scheduled(&env, schedule_id);
+        if caller != scheduled.submitter {
+            Self::require_admin(&env, &caller);
+        }
+
         env.storage()
-            .instance()
-            .get(&DataKey::TotalBatches)
-            .unwrap_or(0)
+            .persistent()
+            .remove(&DataKey::ScheduledBatch(schedule_id));
+        TransferEvents::schedule_cancelled(&env, schedule_id, &caller);
     }
 
-    /// Returns the total number of transfers processed (successful + failed).
-    pub fn get_total_transfers_processed(env: Env) -> u64 {
+    /// Returns a scheduled batch, if it is still pending.
+    pub fn get_scheduled(env: Env, schedule_id: u64) -> Option<ScheduledBatch> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ScheduledBatch(schedule_id))
+    }
+
+    /// Locks the total of `transfers` from `caller` in custody, returning a
+    /// schedule id. Each item is paid out by `release_scheduled` once its
+    /// own `release_ledger` is reached.
+    pub fn batch_schedule_transfers(
+        env: Env,
+        caller: Address,
+        token: Address,
+        transfers: Vec<ScheduledTransfer>,
+    ) -> u64 {
+        caller.require_auth();
+        Self::require_operator(&env, &caller);
+        Self::require_not_paused(&env);
+        Self::require_token_allowed(&env, &token);
+        Self::require_expected_decimals(&env, &token);
+        Self::require_sender_allowed(&env, &caller);
+
+        let request_count = transfers.len();
+        if request_count == 0 {
+            panic_with_error!(&env, BatchTransferError::EmptyBatch);
+        }
+        if request_count > MAX_BATCH_SIZE {
+            panic_with_error!(&env, BatchTransferError::BatchTooLarge);
+        }
+
+        let mut total: i128 = 0;
+        let mut released: Vec<bool> = Vec::new(&env);
+        for transfer in transfers.iter() {
+            if validate_address(&env, &transfer.recipient).is_err()
+                || validate_amount(transfer.amount).is_err()
+            {
+                panic_with_error!(&env, BatchTransferError::InvalidBatch);
+            }
+            total = match total.checked_add(transfer.amount) {
+                Some(total) => total,
+                None => panic_with_error!(&env, BatchTransferError::InvalidBatch),
+            };
+            released.push_back(false);
+        }
+        token::Client::new(&env, &token).transfer(&caller, &env.current_contract_address(), &total);
+        Self::adjust_locked(&env, &token, total);
+
+        let schedule_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::NextScheduleId)
+            .unwrap_or(1);
         env.storage()
             .instance()
-            .get(&DataKey::TotalTransfersProcessed)
-            .unwrap_or(0)
+            .set(&DataKey::NextScheduleId, &(schedule_id + 1));
+
+        let storage_key = DataKey::TransferSchedule(schedule_id);
+        env.storage().persistent().set(
+            &storage_key,
+            &TransferSchedule {
+                sender: caller.clone(),
+                token,
+                transfers,
+                released,
+            },
+        );
+        env.storage().persistent().extend_ttl(
+            &storage_key,
+            RESULT_TTL_THRESHOLD,
+            RESULT_TTL_EXTEND,
+        );
+
+        TransferEvents::transfers_scheduled(&env, schedule_id, &caller, total);
+        schedule_id
     }
 
-    /// Returns the total volume transferred (in stroops).
-    pub fn get_total_volume_transferred(env: Env) -> i128 {
+    /// Pays out item `index` of a transfer schedule once its release ledger
+    /// has been reached. Anyone may trigger the release.
+    pub fn release_scheduled(env: Env, schedule_id: u64, index: u32) -> i128 {
+        Self::require_storage_current(&env);
+        Self::require_not_paused(&env);
+        let mut schedule = Self::load_transfer_schedule(&env, schedule_id);
+        let transfer = match schedule.transfers.get(index) {
+            Some(transfer) => transfer,
+            None => panic_with_error!(&env, BatchTransferError::InvalidBatch),
+        };
+        if schedule.released.get_unchecked(index) {
+            panic_with_error!(&env, BatchTransferError::AlreadyReleased);
+        }
+        if env.ledger().sequence() < transfer.release_ledger {
+            panic_with_error!(&env, BatchTransferError::TimelockNotElapsed);
+        }
+
+        schedule.released.set(index, true);
+        let storage_key = DataKey::TransferSchedule(schedule_id);
+        if schedule.released.iter().all(|released| released) {
+            env.storage().persistent().remove(&storage_key);
+        } else {
+            env.storage().persistent().set(&storage_key, &schedule);
+        }
+        Self::adjust_locked(&env, &schedule.token, -transfer.amount);
+        token::Client::new(&env, &schedule.token).transfer(
+            &env.current_contract_address(),
+            &transfer.recipient,
+            &transfer.amount,
+        );
+        TransferEvents::scheduled_released(
+            &env,
+            schedule_id,
+            index,
+            &transfer.recipient,
+            transfer.amount,
+        );
+        transfer.amount
+    }
+
+    /// Cancels a transfer schedule, refunding every unreleased item to the
+    /// sender. Returns the refunded amount.
+    pub fn cancel_scheduled_transfer(env: Env, caller: Address, schedule_id: u64) -> i128 {
+        caller.require_auth();
+        Self::require_storage_current(&env);
+        let schedule = Self::load_transfer_schedule(&env, schedule_id);
+        if schedule.sender != caller {
+            panic_with_error!(&env, BatchTransferError::Unauthorized);
+        }
+
+        let mut refund: i128 = 0;
+        for (transfer, released) in schedule.transfers.iter().zip(schedule.released.iter()) {
+            if !released {
+                refund += transfer.amount;
+            }
+        }
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::TransferSchedule(schedule_id));
+        Self::adjust_locked(&env, &schedule.token, -refund);
+        token::Client::new(&env, &schedule.token).transfer(
+            &env.current_contract_address(),
+            &caller,
+            &refund,
+        );
+        TransferEvents::schedule_cancelled(&env, schedule_id, &caller);
+        refund
+    }
+
+    /// Returns a transfer schedule, if it still has unreleased items.
+    pub fn get_transfer_schedule(env: Env, schedule_id: u64) -> Option<TransferSchedule> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::TransferSchedule(schedule_id))
+    }
+
+    /// Locks funds from `caller` in custody as one claim per request,
+    /// returning the claim ids in input order.
+    ///
+    /// Recipients collect with `claim` until `expires_ledger`; after that the
+    /// sender can take the funds back with `reclaim_expired`.
+    pub fn batch_create_claims(
+        env: Env,
+        caller: Address,
+        token: Address,
+        transfers: Vec<TransferRequest>,
+        expires_ledger: u32,
+    ) -> Vec<u64> {
+        caller.require_auth();
+        Self::require_operator(&env, &caller);
+        Self::require_not_paused(&env);
+        Self::require_token_allowed(&env, &token);
+        Self::require_expected_decimals(&env, &token);
+        Self::require_sender_allowed(&env, &caller);
+
+        let request_count = transfers.len();
+        if request_count == 0 {
+            panic_with_error!(&env, BatchTransferError::EmptyBatch);
+        }
+        if request_count > MAX_BATCH_SIZE {
+            panic_with_error!(&env, BatchTransferError::BatchTooLarge);
+        }
+        if expires_ledger <= env.ledger().sequence() {
+            panic_with_error!(&env, BatchTransferError::InvalidBatch);
+        }
+
+        let mut total: i128 = 0;
+        for request in transfers.iter() {
+            if validate_address(&env, &request.recipient).is_err()
+                || validate_amount(request.amount).is_err()
+            {
+                panic_with_error!(&env, BatchTransferError::InvalidBatch);
+            }
+            total = match total.checked_add(request.amount) {
+                Some(total) => total,
+                None => panic_with_error!(&env, BatchTransferError::InvalidBatch),
+            };
+        }
+        token::Client::new(&env, &token).transfer(&caller, &env.current_contract_address(), &total);
+        Self::adjust_locked(&env, &token, total);
+
+        let mut claim_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::NextClaimId)
+            .unwrap_or(1);
+        let mut claim_ids: Vec<u64> = Vec::new(&env);
+        for request in transfers.iter() {
+            let storage_key = DataKey::Claim(claim_id);
+            env.storage().persistent().set(
+                &storage_key,
+                &Claim {
+                    sender: caller.clone(),
+                    recipient: request.recipient.clone(),
+                    token: token.clone(),
+                    amount: request.amount,
+                    expires_ledger,
+                },
+            );
+            env.storage().persistent().extend_ttl(
+                &storage_key,
+                RESULT_TTL_THRESHOLD,
+                RESULT_TTL_EXTEND,
+            );
+            Self::index_claim(&env, &request.recipient, claim_id);
+            TransferEvents::claim_created(&env, claim_id, &request.recipient, request.amount);
+            claim_ids.push_back(claim_id);
+            claim_id += 1;
+        }
         env.storage()
             .instance()
-            .get(&DataKey::TotalVolumeTransferred)
-            .unwrap_or(0)
+            .set(&DataKey::NextClaimId, &claim_id);
+
+        claim_ids
+    }
+
+    /// Releases a claim to its recipient.
+    pub fn claim(env: Env, recipient: Address, claim_id: u64) -> i128 {
+        recipient.require_auth();
+        Self::require_storage_current(&env);
+        let claim = Self::load_claim(&env, claim_id);
+        Self::release_claim(&env, &recipient, claim_id, claim, None)
+    }
+
+    /// Releases `amount` of a claim to its recipient, keeping the remainder
+    /// claimable. The claim is removed once fully drained.
+    pub fn claim_partial(env: Env, recipient: Address, claim_id: u64, amount: i128) -> i128 {
+        recipient.require_auth();
+        Self::require_storage_current(&env);
+        let claim = Self::load_claim(&env, claim_id);
+        if amount <= 0 || amount > claim.amount {
+            panic_with_error!(&env, BatchTransferError::ClaimAmountExceeded);
+        }
+        Self::release_claim(&env, &recipient, claim_id, claim, Some(amount))
+    }
+
+    /// Returns an expired, unclaimed claim to its sender.
+    pub fn reclaim_expired(env: Env, sender: Address, claim_id: u64) -> i128 {
+        sender.require_auth();
+        Self::require_storage_current(&env);
+        let claim = Self::load_claim(&env, claim_id);
+        if claim.sender != sender {
+            panic_with_error!(&env, BatchTransferError::Unauthorized);
+        }
+        if env.ledger().sequence() < claim.expires_ledger {
+            panic_with_error!(&env, BatchTransferError::ClaimNotExpired);
+        }
+
+        env.storage().persistent().remove(&DataKey::Claim(claim_id));
+        Self::unindex_claim(&env, &claim.recipient, claim_id);
+        Self::adjust_locked(&env, &claim.token, -claim.amount);
+        token::Client::new(&env, &claim.token).transfer(
+            &env.current_contract_address(),
+            &sender,
+            &claim.amount,
+        );
+        TransferEvents::claim_reclaimed(&env, claim_id, &sender, claim.amount);
+        claim.amount
+    }
+
+    /// Returns a claim, if it is still outstanding.
+    pub fn get_claim(env: Env, claim_id: u64) -> Option<Claim> {
+        env.storage().persistent().get(&DataKey::Claim(claim_id))
+    }
+
+    /// Returns the total of `token` that `recipient` can collect right now,
+    /// summed over its unexpired outstanding claims.
+    pub fn get_claimable(env: Env, recipient: Address, token: Address) -> i128 {
+        let ledger = env.ledger().sequence();
+        let mut claimable: i128 = 0;
+        for claim_id in Self::recipient_claims(&env, &recipient).iter() {
+            if let Some(claim) = Self::get_claim(env.clone(), claim_id) {
+                if claim.token == token && ledger < claim.expires_ledger {
+                    claimable = claimable.saturating_add(claim.amount);
+                }
+            }
+        }
+        claimable
+    }
+
+    /// Locks `transfers` in custody for `dispute_ledgers` ledgers, returning
+    /// one reversible transfer id per request.
+    ///
+    /// Until the window closes `from` can `reverse` a transfer to reclaim
+    /// it; afterwards anyone can `settle` it to pay the recipient.
+    pub fn batch_transfer_reversible(
+        env: Env,
+        from: Address,
+        token: Address,
+        transfers: Vec<TransferRequest>,
+        dispute_ledgers: u32,
+    ) -> Vec<u64> {
+        from.require_auth();
+        Self::require_operator(&env, &from);
+        Self::require_not_paused(&env);
+        Self::require_token_allowed(&env, &token);
+        Self::require_expected_decimals(&env, &token);
+        Self::require_sender_allowed(&env, &from);
+
+        let request_count = transfers.len();
+        if request_count == 0 {
+            panic_with_error!(&env, BatchTransferError::EmptyBatch);
+        }
+        if request_count > MAX_BATCH_SIZE {
+            panic_with_error!(&env, BatchTransferError::BatchTooLarge);
+        }
+        let settle_ledger = match env.ledger().sequence().checked_add(dispute_ledgers) {
+            Some(ledger) if dispute_ledgers > 0 => ledger,
+            _ => panic_with_error!(&env, BatchTransferError::InvalidBatch),
+        };
+
+        let mut total: i128 = 0;
+        for request in transfers.iter() {
+            if validate_address(&env, &request.recipient).is_err()
+                || validate_amount(request.amount).is_err()
+            {
+                panic_with_error!(&env, BatchTransferError::InvalidBatch);
+            }
+            total = match total.checked_add(request.amount) {
+                Some(total) => total,
+                None => panic_with_error!(&env, BatchTransferError::InvalidBatch),
+            };
+        }
+        token::Client::new(&env, &token).transfer(&from, &env.current_contract_address(), &total);
+        Self::adjust_locked(&env, &token, total);
+
+        let mut transfer_id: u64 = env
+            .storage()
+            .instance()
+            .get(&ExtDataKey::NextReversibleId)
+            .unwrap_or(1);
+        let mut transfer_ids: Vec<u64> = Vec::new(&env);
+        for request in transfers.iter() {
+            Self::store_reversible(
+                &env,
+                transfer_id,
+                &ReversibleTransfer {
+                    sender: from.clone(),
+                    recipient: request.recipient.clone(),
+                    token: token.clone(),
+                    amount: request.amount,
+                    settle_ledger,
+                    state: ReversalState::Pending,
+                },
+            );
+            TransferEvents::reversible_created(
+                &env,
+                transfer_id,
+                &request.recipient,
+                request.amount,
+                settle_ledger,
+            );
+            transfer_ids.push_back(transfer_id);
+            transfer_id += 1;
+        }
+        env.storage()
+            .instance()
+            .set(&ExtDataKey::NextReversibleId, &transfer_id);
+
+        transfer_ids
+    }
+
+    /// Pays a reversible transfer out to its recipient once its dispute
+    /// window has closed. Callable by anyone.
+    pub fn settle(env: Env, transfer_id: u64) -> i128 {
+        Self::require_storage_current(&env);
+        let mut transfer = Self::load_pending_reversible(&env, transfer_id);
+        if env.ledger().sequence() < transfer.settle_ledger {
+            panic_with_error!(&env, BatchTransferError::DisputeWindowOpen);
+        }
+
+        transfer.state = ReversalState::Settled;
+        Self::store_reversible(&env, transfer_id, &transfer);
+        Self::adjust_locked(&env, &transfer.token, -transfer.amount);
+        token::Client::new(&env, &transfer.token).transfer(
+            &env.current_contract_address(),
+            &transfer.recipient,
+            &transfer.amount,
+        );
+        TransferEvents::reversible_settled(&env, transfer_id, &transfer.recipient, transfer.amount);
+        transfer.amount
+    }
+
+    /// Returns a reversible transfer to its sender while its dispute window
+    /// is still open.
+    pub fn reverse(env: Env, transfer_id: u64) -> i128 {
+        Self::require_storage_current(&env);
+        let mut transfer = Self::load_pending_reversible(&env, transfer_id);
+        transfer.sender.require_auth();
+        if env.ledger().sequence() >= transfer.settle_ledger {
+            panic_with_error!(&env, BatchTransferError::DisputeWindowClosed);
+        }
+
+        transfer.state = ReversalState::Reversed;
+        Self::store_reversible(&env, transfer_id, &transfer);
+        Self::adjust_locked(&env, &transfer.token, -transfer.amount);
+        token::Client::new(&env, &transfer.token).transfer(
+            &env.current_contract_address(),
+            &transfer.sender,
+            &transfer.amount,
+        );
+        TransferEvents::reversible_reversed(&env, transfer_id, &transfer.sender, transfer.amount);
+        transfer.amount
+    }
+
+    /// Returns a reversible transfer with its current state, if it exists.
+    pub fn get_reversible_transfer(env: Env, transfer_id: u64) -> Option<ReversibleTransfer> {
+        env.storage()
+            .persistent()
+            .get(&ExtDataKey::Reversible(transfer_id))
+    }
+
+    /// Streams `token` from `from` to `recipient` at `rate_per_ledger` for
+    /// every ledger between `start_ledger` and `stop_ledger`, returning the
+    /// stream id.
+    ///
+    /// The full amount is locked in custody up front. The recipient collects
+    /// what has accrued with `withdraw_stream`; `from` can `close_stream` to
+    /// take back the rest.
+    pub fn open_stream(
+        env: Env,
+        from: Address,
+        token: Address,
+        recipient: Address,
+        rate_per_ledger: i128,
+        start_ledger: u32,
+        stop_ledger: u32,
+    ) -> u64 {
+        from.require_auth();
+        Self::require_operator(&env, &from);
+        Self::require_not_paused(&env);
+        Self::require_token_allowed(&env, &token);
+        Self::require_expected_decimals(&env, &token);
+        Self::require_sender_allowed(&env, &from);
+
+        if validate_address(&env, &recipient).is_err()
+            || validate_amount(rate_per_ledger).is_err()
+            || start_ledger < env.ledger().sequence()
+            || stop_ledger <= start_ledger
+        {
+            panic_with_error!(&env, BatchTransferError::InvalidBatch);
+        }
+        let total = match rate_per_ledger.checked_mul((stop_ledger - start_ledger) as i128) {
+            Some(total) => total,
+            None => panic_with_error!(&env, BatchTransferError::InvalidBatch),
+        };
+        token::Client::new(&env, &token).transfer(&from, &env.current_contract_address(), &total);
+        Self::adjust_locked(&env, &token, total);
+
+        let stream_id: u64 = env
+            .storage()
+            .instance()
+            .get(&ExtDataKey::NextStreamId)
+            .unwrap_or(1);
+        env.storage()
+            .instance()
+            .set(&ExtDataKey::NextStreamId, &(stream_id + 1));
+        let stream = Stream {
+            sender: from,
+            recipient,
+            token,
+            rate_per_ledger,
+            start_ledger,
+            stop_ledger,
+            withdrawn_ledger: start_ledger,
+        };
+        Self::store_stream(&env, stream_id, &stream);
+        TransferEvents::stream_opened(&env, stream_id, &stream);
+        stream_id
+    }
+
+    /// Pays `recipient` everything its stream has accrued since the last
+    /// withdrawal, returning the amount paid.
+    pub fn withdraw_stream(env: Env, recipient: Address, stream_id: u64) -> i128 {
+        recipient.require_auth();
+        Self::require_storage_current(&env);
+        let mut stream = Self::load_stream(&env, stream_id);
+        if stream.recipient != recipient {
+            panic_with_error!(&env, BatchTransferError::Unauthorized);
+        }
+
+        let now = env.ledger().sequence().min(stream.stop_ledger);
+        let amount = Self::stream_accrued(&stream, now);
+        if amount > 0 {
+            stream.withdrawn_ledger = now;
+            Self::adjust_locked(&env, &stream.token, -amount);
+            token::Client::new(&env, &stream.token).transfer(
+                &env.current_contract_address(),
+                &recipient,
+                &amount,
+            );
+            TransferEvents::stream_withdrawn(&env, stream_id, &recipient, amount);
+        }
+
+        if stream.withdrawn_ledger == stream.stop_ledger {
+            env.storage()
+                .persistent()
+                .remove(&ExtDataKey::Stream(stream_id));
+        } else {
+            Self::store_stream(&env, stream_id, &stream);
+        }
+        amount
+    }
+
+    /// Ends a stream early: the recipient is paid what has accrued so far and
+    /// the unaccrued remainder is refunded to `from`, which is returned.
+    pub fn close_stream(env: Env, from: Address, stream_id: u64) -> i128 {
+        from.require_auth();
+        Self::require_storage_current(&env);
+        let stream = Self::load_stream(&env, stream_id);
+        if stream.sender != from {
+            panic_with_error!(&env, BatchTransferError::Unauthorized);
+        }
+
+        let now = env.ledger().sequence().min(stream.stop_ledger);
+        let paid = Self::stream_accrued(&stream, now);
+        let refunded = Self::stream_accrued(&stream, stream.stop_ledger) - paid;
+        env.storage()
+            .persistent()
+            .remove(&ExtDataKey::Stream(stream_id));
+        Self::adjust_locked(&env, &stream.token, -(paid + refunded));
+
+        let token_client = token::Client::new(&env, &stream.token);
+        let contract = env.current_contract_address();
+        if paid > 0 {
+            token_client.transfer(&contract, &stream.recipient, &paid);
+        }
+        if refunded > 0 {
+            token_client.transfer(&contract, &from, &refunded);
+        }
+        TransferEvents::stream_closed(&env, stream_id, paid, refunded);
+        refunded
+    }
+
+    /// Returns a stream that has not been fully paid out or closed.
+    pub fn get_stream(env: Env, stream_id: u64) -> Option<Stream> {
+        env.storage()
+            .persistent()
+            .get(&ExtDataKey::Stream(stream_id))
+    }
+
+    /// Sets the allowance `from` granted each of `spenders` on `token` to
+    /// zero, returning one result per spender with the allowance it held.
+    ///
+    /// Works while the contract is paused, so allowances can be pulled
+    /// during an incident. Invalid spenders fail with
+    /// `ErrorCode::INVALID_ADDRESS`.
+    pub fn batch_revoke(
+        env: Env,
+        from: Address,
+        token: Address,
+        spenders: Vec<Address>,
+    ) -> Vec<TransferResult> {
+        from.require_auth();
+
+        let request_count = spenders.len();
+        if request_count == 0 {
+            panic_with_error!(&env, BatchTransferError::EmptyBatch);
+        }
+        if request_count > MAX_BATCH_SIZE {
+            panic_with_error!(&env, BatchTransferError::BatchTooLarge);
+        }
+
+        let token_client = token::Client::new(&env, &token);
+        let ledger = env.ledger().sequence();
+        let mut results: Vec<TransferResult> = Vec::new(&env);
+        for spender in spenders.iter() {
+            if validate_address(&env, &spender).is_err() {
+                results.push_back(TransferResult::Failure(
+                    spender,
+                    0,
+                    ErrorCode::INVALID_ADDRESS,
+                ));
+                continue;
+            }
+
+            let revoked = token_client.allowance(&from, &spender);
+            token_client.approve(&from, &spender, &0, &ledger);
+            results.push_back(TransferResult::Success(spender, revoked));
+        }
+        results
+    }
+
+    /// Returns the amount of `token` held in custody for outstanding claims,
+    /// unreleased scheduled transfers, pending reversible transfers and open
+    /// streams.
+    ///
+    /// Scheduled batches are paid from the submitter's balance at execution
+    /// time and are therefore not included.
+    pub fn get_total_locked(env: Env, token: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::TotalLocked(token))
+            .unwrap_or(0)
+    }
+
+    pub fn batch_burn(
+        env: Env,
+        caller: Address,
+        token: Address,
+        burns: Vec<BurnRequest>,
+    ) -> BatchBurnResult {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+        Self::require_not_paused(&env);
+        Self::require_token_allowed(&env, &token);
+
+        let request_count = burns.len();
+        if request_count == 0 {
+            panic_with_error!(&env, BatchTransferError::EmptyBatch);
+        }
+        if request_count > MAX_BATCH_SIZE {
+            panic_with_error!(&env, BatchTransferError::BatchTooLarge);
+        }
+
+        let batch_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalBatches)
+            .unwrap_or(0)
+            + 1;
+
+        TransferEvents::batch_started(&env, batch_id, request_count);
+
+        let token_client = token::Client::new(&env, &token);
+
+        let mut results: Vec<BurnResult> = Vec::new(&env);
+        let mut successful_count: u32 = 0;
+        let mut failed_count: u32 = 0;
+        let mut total_burned: i128 = 0;
+
+        // Owners already authorized in this call; `require_auth` may only run
+        // once per address
+        let mut authorized: Map<Address, bool> = Map::new(&env);
+        for request in burns.iter() {
+            let mut is_valid = true;
+            let mut error_code = 0u32;
+
+            if validate_address(&env, &request.owner).is_err() {
+                is_valid = false;
+                error_code = ErrorCode::INVALID_ADDRESS;
+            } else if validate_amount(request.amount).is_err() {
+                is_valid = false;
+                error_code = ErrorCode::INVALID_AMOUNT;
+            }
+
+            if !is_valid {
+                results.push_back(BurnResult::Failure(
+                    request.owner.clone(),
+                    request.amount,
+                    error_code,
+                ));
+                failed_count += 1;
+                Self::record_failure(&env, error_code);
+                TransferEvents::burn_failure(
+                    &env,
+                    batch_id,
+                    &request.owner,
+                    request.amount,
+                    error_code,
+                );
+                continue;
+            }
+
+            let balance = token_client.balance(&request.owner);
+            if balance < request.amount {
+                results.push_back(BurnResult::Failure(
+                    request.owner.clone(),
+                    request.amount,
+                    ErrorCode::INSUFFICIENT_BALANCE,
+                ));
+                failed_count += 1;
+                Self::record_failure(&env, ErrorCode::INSUFFICIENT_BALANCE);
+                TransferEvents::burn_failure(
+                    &env,
+                    batch_id,
+                    &request.owner,
+                    request.amount,
+                    ErrorCode::INSUFFICIENT_BALANCE,
+                );
+                continue;
+            }
+
+            if !authorized.contains_key(request.owner.clone()) {
+                request.owner.require_auth();
+                authorized.set(request.owner.clone(), true);
+            }
+            token_client.burn(&request.owner, &request.amount);
+
+            results.push_back(BurnResult::Success(request.owner.clone(), request.amount));
+            successful_count += 1;
+            total_burned = total_burned
+                .checked_add(request.amount)
+                .unwrap_or(total_burned);
+
+            TransferEvents::burn_success(&env, batch_id, &request.owner, request.amount);
+        }
+
+        Self::add_token_stat(&env, DataKey::TokenBurned(token.clone()), total_burned);
+        TransferEvents::burn_batch_completed(
+            &env,
+            batch_id,
+            successful_count,
+            failed_count,
+            total_burned,
+        );
+
+        BatchBurnResult {
+            total_requests: request_count,
+            successful: successful_count,
+            failed: failed_count,
+            total_burned,
+            results,
+        }
+    }
+
+    /// Returns the admin address.
+    pub fn get_admin(env: Env) -> Address {
+        env.storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .expect("Contract not initialized")
+    }
+
+    /// Updates the admin address.
+    pub fn set_admin(env: Env, current_admin: Address, new_admin: Address) {
+        current_admin.require_auth();
+        Self::require_admin(&env, &current_admin);
+
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
+        env.storage().instance().remove(&DataKey::PendingAdmin);
+    }
+
+    /// Starts a two-step admin transfer; `new_admin` takes over once it calls
+    /// `accept_admin`. Proposing again replaces the pending address.
+    pub fn propose_admin(env: Env, admin: Address, new_admin: Address) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::PendingAdmin, &new_admin);
+    }
+
+    /// Completes a pending admin transfer; only the proposed address can
+    /// accept.
+    pub fn accept_admin(env: Env, new_admin: Address) {
+        new_admin.require_auth();
+        let pending: Address = match env.storage().instance().get(&DataKey::PendingAdmin) {
+            Some(pending) => pending,
+            None => panic_with_error!(&env, BatchTransferError::NoPendingAdmin),
+        };
+        if pending != new_admin {
+            panic_with_error!(&env, BatchTransferError::Unauthorized);
+        }
+
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
+        env.storage().instance().remove(&DataKey::PendingAdmin);
+    }
+
+    /// Withdraws a pending admin transfer before it is accepted.
+    pub fn cancel_admin_transfer(env: Env, admin: Address) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+
+        if !env.storage().instance().has(&DataKey::PendingAdmin) {
+            panic_with_error!(&env, BatchTransferError::NoPendingAdmin);
+        }
+        env.storage().instance().remove(&DataKey::PendingAdmin);
+    }
+
+    /// Returns the address a pending admin transfer is waiting on, if any.
+    pub fn get_pending_admin(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::PendingAdmin)
+    }
+
+    /// Grants `operator` the right to submit transfer batches.
+    ///
+    /// Adding an existing operator is a no-op. At most `MAX_OPERATORS`
+    /// operators can be registered.
+    pub fn add_operator(env: Env, admin: Address, operator: Address) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+
+        let mut operators = Self::get_operators(env.clone());
+        if operators.contains(&operator) {
+            return;
+        }
+        if operators.len() >= MAX_OPERATORS {
+            panic_with_error!(&env, BatchTransferError::TooManyOperators);
+        }
+        operators.push_back(operator.clone());
+        env.storage()
+            .persistent()
+            .set(&DataKey::Operators, &operators);
+        TransferEvents::operator_added(&env, &operator);
+    }
+
+    /// Revokes the operator rights of `operator`.
+    pub fn remove_operator(env: Env, admin: Address, operator: Address) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+
+        let mut operators = Self::get_operators(env.clone());
+        if let Some(index) = operators.first_index_of(&operator) {
+            operators.remove(index);
+            env.storage()
+                .persistent()
+                .set(&DataKey::Operators, &operators);
+            TransferEvents::operator_removed(&env, &operator);
+        }
+    }
+
+    /// Returns all registered operators in the order they were added.
+    pub fn get_operators(env: Env) -> Vec<Address> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Operators)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Returns whether `address` is a registered operator.
+    pub fn is_operator(env: Env, address: Address) -> bool {
+        Self::get_operators(env).contains(&address)
+    }
+
+    /// Returns every governance-related address in a single snapshot.
+    ///
+    /// Unset optional roles are returned as `None` or an empty list.
+    pub fn get_governance(env: Env) -> Governance {
+        Governance {
+            owner: Self::get_admin(env.clone()),
+            pending_admin: env.storage().instance().get(&DataKey::PendingAdmin),
+            operators: Self::get_operators(env.clone()),
+            fee_collector: env.storage().instance().get(&DataKey::FeeCollector),
+            price_oracle: None,
+            transfer_contract: None,
+        }
+    }
+
+    /// Reports every role and flag held by `address` in one call, for
+    /// support tooling.
+    ///
+    /// `wallet_id` is looked up in the wallet registry when one is configured
+    /// and is `None` otherwise, or if the registry call fails.
+    pub fn lookup_address(env: Env, address: Address) -> AddressInfo {
+        let fee_collector: Option<Address> = env.storage().instance().get(&DataKey::FeeCollector);
+        let allowlisted = env
+            .storage()
+            .persistent()
+            .get(&DataKey::AllowedSender(address.clone()))
+            .unwrap_or(false);
+
+        AddressInfo {
+            is_admin: Self::get_admin(env.clone()) == address,
+            is_operator: Self::is_operator(env.clone(), address.clone()),
+            is_fee_collector: fee_collector.as_ref() == Some(&address),
+            is_allowlisted: allowlisted,
+            is_blocked_token: Self::is_token_blocked(env.clone(), address.clone()),
+            is_suspended: Self::is_sender_suspended(env.clone(), address.clone()),
+            wallet_id: Self::registry_wallet(&env, &address).map(|wallet| wallet.id),
+        }
+    }
+
+    /// Sets the wallet contract consulted for wallet ownership.
+    pub fn set_wallet_registry(env: Env, admin: Address, registry: Address) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::WalletRegistry, &registry);
+    }
+
+    /// Returns the configured wallet registry, if any.
+    pub fn get_wallet_registry(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::WalletRegistry)
+    }
+
+    /// Configures the fee charged on successful transfers.
+    ///
+    /// The fee is `amount * fee_bps / 10_000` per transfer, paid by the sender
+    /// to `fee_collector` once per batch. A collector equal to the sender nets
+    /// out, so no fee is charged or recorded for that sender.
+    ///
+    /// Once a collector is set it can only be changed through
+    /// `propose_fee_collector` / `apply_fee_collector`.
+    pub fn set_fee(env: Env, admin: Address, fee_bps: u32, fee_collector: Address) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+
+        if fee_bps > MAX_FEE_BPS {
+            panic_with_error!(&env, BatchTransferError::InvalidFee);
+        }
+        let current: Option<Address> = env.storage().instance().get(&DataKey::FeeCollector);
+        if current.is_some_and(|c| c != fee_collector) {
+            panic_with_error!(&env, BatchTransferError::FeeCollectorLocked);
+        }
+
+        env.storage().instance().set(&DataKey::FeeBps, &fee_bps);
+        env.storage()
+            .instance()
+            .set(&DataKey::FeeCollector, &fee_collector);
+    }
+
+    /// Returns the configured fee rate in basis points.
+    pub fn get_fee_bps(env: Env) -> u32 {
+        env.storage().instance().get(&DataKey::FeeBps).unwrap_or(0)
+    }
+
+    /// Overrides the fee rate for batches in `token`, taking precedence over
+    /// the global rate. Fees still go to the configured fee collector.
+    pub fn set_token_fee(env: Env, admin: Address, token: Address, fee_bps: u32) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+        if fee_bps > MAX_FEE_BPS {
+            panic_with_error!(&env, BatchTransferError::InvalidFee);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&ExtDataKey::TokenFee(token), &fee_bps);
+    }
+
+    /// Removes the fee override of `token`, reverting it to the global rate.
+    pub fn clear_token_fee(env: Env, admin: Address, token: Address) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+
+        env.storage()
+            .persistent()
+            .remove(&ExtDataKey::TokenFee(token));
+    }
+
+    /// Returns the fee override of `token`, if any.
+    pub fn get_token_fee(env: Env, token: Address) -> Option<u32> {
+        env.storage().persistent().get(&ExtDataKey::TokenFee(token))
+    }
+
+    /// Returns the configured fee collector, if any.
+    pub fn get_fee_collector(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::FeeCollector)
+    }
+
+    /// Proposes a new fee collector that can be applied after
+    /// `FEE_COLLECTOR_DELAY_LEDGERS` ledgers. Replaces any pending proposal.
+    pub fn propose_fee_collector(env: Env, admin: Address, new_collector: Address) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+
+        let pending = PendingFeeCollector {
+            collector: new_collector.clone(),
+            unlock_ledger: env
+                .ledger()
+                .sequence()
+                .saturating_add(FEE_COLLECTOR_DELAY_LEDGERS),
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::PendingFeeCollector, &pending);
+
+        TransferEvents::fee_collector_proposed(&env, &new_collector, pending.unlock_ledger);
+    }
+
+    /// Applies the pending fee collector once its timelock has elapsed.
+    pub fn apply_fee_collector(env: Env, admin: Address) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+
+        let pending: PendingFeeCollector = env
+            .storage()
+            .instance()
+            .get(&DataKey::PendingFeeCollector)
+            .unwrap_or_else(|| panic_with_error!(&env, BatchTransferError::NoPendingFeeCollector));
+        if env.ledger().sequence() < pending.unlock_ledger {
+            panic_with_error!(&env, BatchTransferError::TimelockNotElapsed);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::FeeCollector, &pending.collector);
+        env.storage()
+            .instance()
+            .remove(&DataKey::PendingFeeCollector);
+
+        TransferEvents::fee_collector_applied(&env, &pending.collector);
+    }
+
+    /// Cancels the pending fee collector change, keeping the current collector.
+    pub fn cancel_fee_collector(env: Env, admin: Address) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+
+        if !env.storage().instance().has(&DataKey::PendingFeeCollector) {
+            panic_with_error!(&env, BatchTransferError::NoPendingFeeCollector);
+        }
+        env.storage()
+            .instance()
+            .remove(&DataKey::PendingFeeCollector);
+    }
+
+    /// Returns the pending fee collector change, if any.
+    pub fn get_pending_fee_collector(env: Env) -> Option<PendingFeeCollector> {
+        env.storage().instance().get(&DataKey::PendingFeeCollector)
+    }
+
+    /// Blocks a token so that transfers and burns of it are rejected.
+    pub fn block_token(env: Env, admin: Address, token: Address) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::BlockedToken(token.clone()), &true);
+        TransferEvents::token_blocked(&env, &token);
+    }
+
+    /// Removes a token from the block list.
+    pub fn unblock_token(env: Env, admin: Address, token: Address) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::BlockedToken(token.clone()));
+        TransferEvents::token_unblocked(&env, &token);
+    }
+
+    /// Returns whether a token is blocked.
+    pub fn is_token_blocked(env: Env, token: Address) -> bool {
+        env.storage()
+            .persistent()
+            .get(&DataKey::BlockedToken(token))
+            .unwrap_or(false)
+    }
+
+    /// Pauses all transfer and burn operations.
+    ///
+    /// Pausing an already paused contract is a no-op and emits no event.
+    pub fn pause(env: Env, admin: Address) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+
+        if Self::is_paused(env.clone()) {
+            return;
+        }
+        env.storage().instance().set(&DataKey::Paused, &true);
+        TransferEvents::paused(&env, &admin);
+    }
+
+    /// Resumes transfer and burn operations.
+    ///
+    /// Unpausing a contract that is not paused is a no-op and emits no event.
+    /// Fails while the lifetime volume cap is reached.
+    pub fn unpause(env: Env, admin: Address) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+
+        if !Self::is_paused(env.clone()) {
+            return;
+        }
+        if Self::lifetime_cap_reached(&env) {
+            panic_with_error!(&env, BatchTransferError::LifetimeCapReached);
+        }
+        env.storage().instance().remove(&DataKey::Paused);
+        TransferEvents::unpaused(&env, &admin);
+    }
+
+    /// Emits a `large_transfer` event for every successful transfer of at
+    /// least `threshold`; 0 disables the alert.
+    pub fn set_large_transfer_alert(env: Env, admin: Address, threshold: i128) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+        if threshold < 0 {
+            panic_with_error!(&env, BatchTransferError::InvalidBatch);
+        }
+
+        if threshold == 0 {
+            env.storage()
+                .instance()
+                .remove(&DataKey::LargeTransferThreshold);
+        } else {
+            env.storage()
+                .instance()
+                .set(&DataKey::LargeTransferThreshold, &threshold);
+        }
+    }
+
+    /// Returns the large transfer alert threshold, 0 meaning disabled.
+    pub fn get_large_transfer_alert(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::LargeTransferThreshold)
+            .unwrap_or(0)
+    }
+
+    /// Caps the cumulative volume transferred over the contract's life; 0
+    /// removes the cap.
+    ///
+    /// The batch that reaches the cap completes, then the contract pauses
+    /// itself and stays paused until the cap is raised and `unpause` called.
+    pub fn set_lifetime_cap(env: Env, admin: Address, amount: i128) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+        if amount < 0 {
+            panic_with_error!(&env, BatchTransferError::InvalidBatch);
+        }
+
+        if amount == 0 {
+            env.storage().instance().remove(&DataKey::LifetimeCap);
+        } else {
+            env.storage().instance().set(&DataKey::LifetimeCap, &amount);
+        }
+    }
+
+    /// Returns the lifetime volume cap, 0 meaning unlimited.
+    pub fn get_lifetime_cap(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::LifetimeCap)
+            .unwrap_or(0)
+    }
+
+    /// Permanently locks the contract's configuration.
+    ///
+    /// Afterwards every config setter (fees, limits, caps, flags, callback,
+    /// decimals) panics with "Config frozen"; transfers and operational
+    /// controls such as pausing, operators and sender lists keep working.
+    /// There is no unfreeze short of an upgrade.
+    pub fn freeze_config(env: Env, admin: Address) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+
+        if Self::is_config_frozen(env.clone()) {
+            return;
+        }
+        env.storage().instance().set(&DataKey::ConfigFrozen, &true);
+        TransferEvents::config_frozen(&env, &admin);
+    }
+
+    /// Returns whether the configuration has been frozen.
+    pub fn is_config_frozen(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::ConfigFrozen)
+            .unwrap_or(false)
+    }
+
+    /// Returns whether the contract is paused.
+    pub fn is_paused(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::Paused)
+            .unwrap_or(false)
+    }
+
+    /// Turns enforcement of the sender allowlist on or off.
+    ///
+    /// While enabled, only allowlisted addresses may be the source of funds
+    /// in a batch, independently of who is authorized to submit it.
+    pub fn set_sender_allowlist_enabled(env: Env, admin: Address, enabled: bool) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::SenderAllowlistEnabled, &enabled);
+    }
+
+    /// Returns whether the sender allowlist is enforced.
+    pub fn is_sender_allowlist_enabled(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::SenderAllowlistEnabled)
+            .unwrap_or(false)
+    }
+
+    /// Adds an address to the sender allowlist.
+    pub fn add_allowed_sender(env: Env, admin: Address, sender: Address) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::AllowedSender(sender), &true);
+    }
+
+    /// Removes an address from the sender allowlist.
+    pub fn remove_allowed_sender(env: Env, admin: Address, sender: Address) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::AllowedSender(sender));
+    }
+
+    /// Returns whether `sender` may spend funds in a batch.
+    ///
+    /// Always true while the allowlist is disabled.
+    pub fn is_sender_allowed(env: Env, sender: Address) -> bool {
+        !Self::is_sender_allowlist_enabled(env.clone())
+            || env
+                .storage()
+                .persistent()
+                .get(&DataKey::AllowedSender(sender))
+                .unwrap_or(false)
+    }
+
+    /// Blocks `sender` from spending in any batch until unsuspended.
+    pub fn suspend_sender(env: Env, admin: Address, sender: Address) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::SuspendedSender(sender), &true);
+    }
+
+    /// Lifts a suspension placed with `suspend_sender`.
+    pub fn unsuspend_sender(env: Env, admin: Address, sender: Address) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::SuspendedSender(sender));
+    }
+
+    /// Returns whether `sender` is suspended.
+    pub fn is_sender_suspended(env: Env, sender: Address) -> bool {
+        env.storage()
+            .persistent()
+            .get(&DataKey::SuspendedSender(sender))
+            .unwrap_or(false)
+    }
+
+    /// Enables or disables merging of duplicate recipients within a batch.
+    ///
+    /// When enabled, valid requests to a recipient already present in the
+    /// batch are added to its first request and reported in `merged_count`.
+    pub fn set_merge_duplicates(env: Env, admin: Address, enabled: bool) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::MergeDuplicates, &enabled);
+    }
+
+    /// Returns whether duplicate recipients are merged.
+    pub fn get_merge_duplicates(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::MergeDuplicates)
+            .unwrap_or(false)
+    }
+
+    /// Requires memos to be unique within a `batch_transfer_with_memos` batch.
+    ///
+    /// The first occurrence of a memo still succeeds.
+    pub fn set_unique_memos(env: Env, admin: Address, enabled: bool) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::UniqueMemos, &enabled);
+    }
+
+    /// Returns whether memos must be unique within a batch.
+    pub fn get_unique_memos(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::UniqueMemos)
+            .unwrap_or(false)
+    }
+
+    /// Limits the volume transferred per window of `window_ledgers` ledgers.
+    ///
+    /// Transfers that would push the current window past `max_volume` fail
+    /// with `ErrorCode::RATE_LIMITED`; the window restarts once it has elapsed.
+    pub fn set_rate_limit(env: Env, admin: Address, max_volume: i128, window_ledgers: u32) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+        if max_volume <= 0 || window_ledgers == 0 {
+            panic_with_error!(&env, BatchTransferError::InvalidRateLimit);
+        }
+
+        env.storage().instance().set(
+            &DataKey::RateLimit,
+            &RateLimit {
+                max_volume,
+                window_ledgers,
+            },
+        );
+    }
+
+    /// Removes the rate limit.
+    pub fn clear_rate_limit(env: Env, admin: Address) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+
+        env.storage().instance().remove(&DataKey::RateLimit);
+        env.storage().instance().remove(&DataKey::RateLimitWindow);
+    }
+
+    /// Returns the configured rate limit, if any.
+    pub fn get_rate_limit(env: Env) -> Option<RateLimit> {
+        env.storage().instance().get(&DataKey::RateLimit)
+    }
+
+    /// Returns the volume that can still be transferred in the current window.
+    ///
+    /// Accounts for window rollover at the current ledger; returns `i128::MAX`
+    /// when no rate limit is configured.
+    pub fn get_rate_limit_remaining(env: Env) -> i128 {
+        match env
+            .storage()
+            .instance()
+            .get::<_, RateLimit>(&DataKey::RateLimit)
+        {
+            Some(limit) => limit.max_volume - Self::current_rate_window(&env, &limit).used,
+            None => i128::MAX,
+        }
+    }
+
+    /// Requires `ledgers` ledgers between consecutive batches from the same
+    /// sender; 0 disables the cooldown.
+    ///
+    /// Applies to batches executed immediately from the sender's balance. A
+    /// batch submitted too soon fails with `BatchCooldownActive`.
+    pub fn set_batch_cooldown(env: Env, admin: Address, ledgers: u32) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+
+        if ledgers == 0 {
+            env.storage().instance().remove(&ExtDataKey::BatchCooldown);
+        } else {
+            env.storage()
+                .instance()
+                .set(&ExtDataKey::BatchCooldown, &ledgers);
+        }
+    }
+
+    /// Returns the batch cooldown in ledgers, 0 meaning disabled.
+    pub fn get_batch_cooldown(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&ExtDataKey::BatchCooldown)
+            .unwrap_or(0)
+    }
+
+    /// Caps the volume `operator` may submit per day; 0 removes the limit.
+    ///
+    /// Each operator has its own budget, reset every `LEDGERS_PER_DAY`
+    /// ledgers. Transfers that would push the operator past `daily_amount`
+    /// fail with `ErrorCode::OPERATOR_LIMIT_EXCEEDED`.
+    pub fn set_operator_limit(env: Env, admin: Address, operator: Address, daily_amount: i128) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+        if daily_amount < 0 {
+            panic_with_error!(&env, BatchTransferError::InvalidRateLimit);
+        }
+
+        let key = DataKey::OperatorLimit(operator);
+        if daily_amount == 0 {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &daily_amount);
+        }
+    }
+
+    /// Caps how much of `token` the wallet `owner` may spend through this
+    /// contract per day; 0 removes the limit.
+    ///
+    /// The limit only applies while `owner` has a wallet in the wallet
+    /// registry. Transfers from it that would exceed the limit fail with
+    /// `ErrorCode::WALLET_LIMIT_EXCEEDED`.
+    pub fn set_wallet_spend_limit(env: Env, owner: Address, token: Address, per_day: i128) {
+        owner.require_auth();
+        if per_day < 0 {
+            panic_with_error!(&env, BatchTransferError::InvalidRateLimit);
+        }
+
+        let key = ExtDataKey::WalletSpendLimit(owner, token);
+        if per_day == 0 {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &per_day);
+        }
+    }
+
+    /// Returns the daily spend limit of `owner` for `token`, 0 meaning
+    /// unlimited.
+    pub fn get_wallet_spend_limit(env: Env, owner: Address, token: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&ExtDataKey::WalletSpendLimit(owner, token))
+            .unwrap_or(0)
+    }
+
+    /// Returns the daily limit of `operator`, 0 meaning unlimited.
+    pub fn get_operator_limit(env: Env, operator: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::OperatorLimit(operator))
+            .unwrap_or(0)
+    }
+
+    /// Returns the volume `operator` can still submit today; `i128::MAX` when
+    /// no limit is set.
+    pub fn get_operator_remaining(env: Env, operator: Address) -> i128 {
+        match Self::operator_budget(&env, &operator) {
+            Some((limit, usage)) => limit - usage.used,
+            None => i128::MAX,
+        }
+    }
+
+    /// Caps the cumulative volume `recipient` may receive; 0 removes the cap.
+    ///
+    /// Received volume is tracked only while a cap is set, and transfers
+    /// that would push the recipient past `max_total` fail with
+    /// `ErrorCode::RECIPIENT_CAP_EXCEEDED`.
+    pub fn set_recipient_cap(env: Env, admin: Address, recipient: Address, max_total: i128) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+        if max_total < 0 {
+            panic_with_error!(&env, BatchTransferError::InvalidBatch);
+        }
+
+        let key = DataKey::RecipientCap(recipient);
+        if max_total == 0 {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &max_total);
+        }
+    }
+
+    /// Returns the cumulative cap for `recipient`, 0 meaning unlimited.
+    pub fn get_recipient_cap(env: Env, recipient: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::RecipientCap(recipient))
+            .unwrap_or(0)
+    }
+
+    /// Returns the volume counted against `recipient`'s cap so far.
+    pub fn get_recipient_received(env: Env, recipient: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::RecipientReceived(recipient))
+            .unwrap_or(0)
+    }
+
+    /// Returns whether `recipient` has ever received a successful transfer.
+    pub fn has_received(env: Env, recipient: Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::HasReceived(recipient))
+    }
+
+    /// Returns how many distinct addresses have received a successful
+    /// transfer.
+    pub fn get_unique_recipients(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::UniqueRecipients)
+            .unwrap_or(0)
+    }
+
+    /// Returns lifetime failure counts per `ErrorCode` as `(code, count)`,
+    /// in code order. Codes that never occurred are omitted.
+    pub fn get_failure_breakdown(env: Env) -> Vec<(u32, u64)> {
+        let mut breakdown = Vec::new(&env);
+        for code in ErrorCode::INVALID_ADDRESS..=ErrorCode::TRANSFER_REJECTED {
+            let count: u64 = env
+                .storage()
+                .instance()
+                .get(&ExtDataKey::FailureCount(code))
+                .unwrap_or(0);
+            if count > 0 {
+                breakdown.push_back((code, count));
+            }
+        }
+        breakdown
+    }
+
+    /// Registers a contract notified after every batch through
+    /// `on_batch_complete(batch_id: u64, summary: BatchRecord)`.
+    ///
+    /// A failing callback does not revert the batch; a `callback_failed`
+    /// event is emitted instead.
+    pub fn set_callback(env: Env, admin: Address, callback: Address) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+
+        env.storage().instance().set(&DataKey::Callback, &callback);
+    }
+
+    /// Removes the batch completion callback.
+    pub fn clear_callback(env: Env, admin: Address) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+
+        env.storage().instance().remove(&DataKey::Callback);
+    }
+
+    /// Returns the registered batch completion callback, if any.
+    pub fn get_callback(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Callback)
+    }
+
+    /// Sets how per-item outcomes are reported.
+    ///
+    /// `Compressed` replaces the per-item `transfer` events with a single
+    /// `batch_log` event per batch, trading indexer convenience for budget.
+    pub fn set_event_mode(env: Env, admin: Address, mode: EventMode) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+
+        env.storage().instance().set(&DataKey::EventMode, &mode);
+    }
+
+    /// Returns the configured event mode.
+    pub fn get_event_mode(env: Env) -> EventMode {
+        env.storage()
+            .instance()
+            .get(&DataKey::EventMode)
+            .unwrap_or(EventMode::Full)
+    }
+
+    /// Enables or disables balance-delta verification.
+    ///
+    /// When enabled, each transfer reads the recipient's balance before and
+    /// after and records the delta actually received, so fee-on-transfer or
+    /// rebasing tokens surface as `PartialDelivery` instead of `Success`.
+    pub fn set_verify_deltas(env: Env, admin: Address, enabled: bool) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::VerifyDeltas, &enabled);
+    }
+
+    /// Returns whether balance-delta verification is enabled.
+    pub fn get_verify_deltas(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::VerifyDeltas)
+            .unwrap_or(false)
+    }
+
+    /// Enables or disables the `sender_drained` event, emitted when a batch
+    /// leaves its sender with a zero balance. It only reports; the batch
+    /// still runs.
+    pub fn set_warn_on_drain(env: Env, admin: Address, enabled: bool) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+
+        env.storage()
+            .instance()
+            .set(&ExtDataKey::WarnOnDrain, &enabled);
+    }
+
+    /// Returns whether drained senders are reported.
+    pub fn get_warn_on_drain(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&ExtDataKey::WarnOnDrain)
+            .unwrap_or(false)
+    }
+
+    /// Enables or disables trimming of trailing zero-amount requests.
+    ///
+    /// When enabled, zero amounts at the end of a batch are dropped before
+    /// execution and excluded from `total_requests`; zero amounts elsewhere
+    /// still fail as invalid.
+    pub fn set_trim_trailing_zeros(env: Env, admin: Address, enabled: bool) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+
+        env.storage()
+            .instance()
+            .set(&ExtDataKey::TrimTrailingZeros, &enabled);
+    }
+
+    /// Returns whether trailing zero-amount requests are trimmed.
+    pub fn get_trim_trailing_zeros(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&ExtDataKey::TrimTrailingZeros)
+            .unwrap_or(false)
+    }
+
+    /// Returns the total fees collected (in stroops).
+    pub fn get_total_fees_collected(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TotalFeesCollected)
+            .unwrap_or(0)
+    }
+
+    /// Returns the total volume transferred for a token (in stroops).
+    pub fn get_token_volume(env: Env, token: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::TokenVolume(token))
+            .unwrap_or(0)
+    }
+
+    /// Returns the lifetime stats of `token` in one read.
+    ///
+    /// `transfers` counts successful transfers, `burns` the burned volume and
+    /// `custody_locked` matches `get_total_locked`.
+    pub fn get_token_stats(env: Env, token: Address) -> TokenStats {
+        let storage = env.storage().persistent();
+        TokenStats {
+            volume: storage
+                .get(&DataKey::TokenVolume(token.clone()))
+                .unwrap_or(0),
+            transfers: storage
+                .get(&DataKey::TokenTransfers(token.clone()))
+                .unwrap_or(0),
+            burns: storage
+                .get(&DataKey::TokenBurned(token.clone()))
+                .unwrap_or(0),
+            mints: 0,
+            fees_accrued: storage.get(&DataKey::TokenFees(token.clone())).unwrap_or(0),
+            custody_locked: storage.get(&DataKey::TotalLocked(token)).unwrap_or(0),
+        }
+    }
+
+    /// Registers the number of decimals of a token for human-readable stats.
+    pub fn set_token_decimals(env: Env, admin: Address, token: Address, decimals: u32) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+
+        if decimals > MAX_TOKEN_DECIMALS {
+            panic_with_error!(&env, BatchTransferError::InvalidDecimals);
+        }
+        env.storage()
+            .persistent()
+            .set(&DataKey::TokenDecimals(token), &decimals);
+    }
+
+    /// Returns the registered decimals of a token, if any.
+    pub fn get_token_decimals(env: Env, token: Address) -> Option<u32> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::TokenDecimals(token))
+    }
+
+    /// Sets the decimals every transferred token must report, or clears the check.
+    ///
+    /// Guards against pointing a batch at the wrong asset: while set, transfer
+    /// entry points panic with "Unexpected token decimals" on a mismatch.
+    pub fn set_expected_decimals(env: Env, admin: Address, decimals: Option<u32>) {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+        Self::require_config_unfrozen(&env);
+
+        match decimals {
+            Some(decimals) => env
+                .storage()
+                .instance()
+                .set(&DataKey::ExpectedDecimals, &decimals),
+            None => env.storage().instance().remove(&DataKey::ExpectedDecimals),
+        }
+    }
+
+    /// Returns the decimals transferred tokens are expected to report, if set.
+    pub fn get_expected_decimals(env: Env) -> Option<u32> {
+        env.storage().instance().get(&DataKey::ExpectedDecimals)
+    }
+
+    /// Returns the total volume transferred for a token in whole units.
+    ///
+    /// The stroop volume is divided by `10^decimals` and truncated, so any
+    /// fractional remainder is dropped. Tokens without registered decimals
+    /// are reported in stroops.
+    pub fn get_total_volume_human(env: Env, token: Address) -> i128 {
+        let decimals: u32 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::TokenDecimals(token.clone()))
+            .unwrap_or(0);
+        Self::get_token_volume(env, token) / 10i128.pow(decimals)
+    }
+
+    /// Returns a short human-readable description of a per-item failure code.
+    ///
+    /// Unknown codes are described as `"unknown"`.
+    pub fn error_description(env: Env, code: u32) -> String {
+        let description = match code {
+            ErrorCode::INVALID_ADDRESS => "invalid address",
+            ErrorCode::INVALID_AMOUNT => "invalid amount",
+            ErrorCode::INSUFFICIENT_BALANCE => "insufficient balance",
+            ErrorCode::PARTIAL_DELIVERY => "partial delivery",
+            ErrorCode::INSUFFICIENT_ALLOWANCE => "insufficient allowance",
+            ErrorCode::SENDER_NOT_ALLOWED => "sender not allowed",
+            ErrorCode::RATE_LIMITED => "rate limited",
+            ErrorCode::RECIPIENT_CAP_EXCEEDED => "recipient cap exceeded",
+            ErrorCode::RECIPIENT_NOT_READY => "recipient not ready",
+            ErrorCode::DUPLICATE_MEMO => "duplicate memo",
+            ErrorCode::FEE_OVERFLOW => "fee overflow",
+            ErrorCode::OPERATOR_LIMIT_EXCEEDED => "operator limit exceeded",
+            ErrorCode::WALLET_LIMIT_EXCEEDED => "wallet limit exceeded",
+            ErrorCode::TRANSFER_REJECTED => "transfer rejected",
+            _ => "unknown",
+        };
+        String::from_str(&env, description)
+    }
+
+    /// Returns the ledger sequence of the last completed batch (0 if none).
+    pub fn get_last_batch_ledger(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::LastBatchLedger)
+            .unwrap_or(0)
+    }
+
+    /// Returns the ledger timestamp of the last completed batch (0 if none).
+    pub fn get_last_batch_timestamp(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::LastBatchTimestamp)
+            .unwrap_or(0)
+    }
+
+    /// Returns the total number of batches processed.
+    pub fn get_total_batches(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TotalBatches)
+            .unwrap_or(0)
+    }
+
+    /// Returns this contract's address and its current balance of `token`,
+    /// for integrations funding the contract.
+    pub fn get_contract_info(env: Env, token: Address) -> (Address, i128) {
+        let contract = env.current_contract_address();
+        let balance = token::Client::new(&env, &token).balance(&contract);
+        (contract, balance)
+    }
+
+    /// Returns the record of a completed batch, if it is still stored.
+    pub fn get_batch(env: Env, batch_id: u64) -> Option<BatchRecord> {
+        env.storage().persistent().get(&DataKey::Batch(batch_id))
+    }
+
+    /// Returns the records for `batch_ids` in input order, with `None` for
+    /// unknown ids. At most `MAX_BATCH_SIZE` ids can be requested at once.
+    pub fn get_batches_by_ids(env: Env, batch_ids: Vec<u64>) -> Vec<Option<BatchRecord>> {
+        if batch_ids.len() > MAX_BATCH_SIZE {
+            panic_with_error!(&env, BatchTransferError::BatchTooLarge);
+        }
+
+        let mut records = Vec::new(&env);
+        for batch_id in batch_ids.iter() {
+            records.push_back(env.storage().persistent().get(&DataKey::Batch(batch_id)));
+        }
+        records
+    }
+
+    /// Lists outstanding scheduled batches, transfer schedules, claims,
+    /// reversible transfers and streams with ids in
+    /// `start_id..start_id + limit`.
+    ///
+    /// Scheduled batches and transfer schedules share one id sequence;
+    /// claims, reversible transfers and streams each have their own, so one
+    /// id can appear once per sequence. At most `MAX_BATCH_SIZE` ids can be
+    /// scanned at once.
+    pub fn list_pending(env: Env, start_id: u64, limit: u32) -> Vec<PendingOp> {
+        if limit > MAX_BATCH_SIZE {
+            panic_with_error!(&env, BatchTransferError::BatchTooLarge);
+        }
+
+        let storage = env.storage().persistent();
+        let mut pending: Vec<PendingOp> = Vec::new(&env);
+        for offset in 0..limit as u64 {
+            let id = match start_id.checked_add(offset) {
+                Some(id) => id,
+                None => break,
+            };
+
+            if let Some(scheduled) = storage.get::<_, ScheduledBatch>(&DataKey::ScheduledBatch(id))
+            {
+                let mut amount: i128 = 0;
+                for transfer in scheduled.transfers.iter() {
+                    amount = amount.saturating_add(transfer.amount.max(0));
+                }
+                pending.push_back(PendingOp {
+                    kind: PendingKind::ScheduledBatch,
+                    id,
+                    amount,
+                    ledger: scheduled.execute_after_ledger,
+                });
+            }
+
+            if let Some(schedule) =
+                storage.get::<_, TransferSchedule>(&DataKey::TransferSchedule(id))
+            {
+                let mut amount: i128 = 0;
+                let mut ledger = u32::MAX;
+                for (transfer, released) in schedule.transfers.iter().zip(schedule.released.iter())
+                {
+                    if !released {
+                        amount += transfer.amount;
+                        ledger = ledger.min(transfer.release_ledger);
+                    }
+                }
+                pending.push_back(PendingOp {
+                    kind: PendingKind::TransferSchedule,
+                    id,
+                    amount,
+                    ledger,
+                });
+            }
+
+            if let Some(claim) = storage.get::<_, Claim>(&DataKey::Claim(id)) {
+                pending.push_back(PendingOp {
+                    kind: PendingKind::Claim,
+                    id,
+                    amount: claim.amount,
+                    ledger: claim.expires_ledger,
+                });
+            }
+
+            if let Some(transfer) =
+                storage.get::<_, ReversibleTransfer>(&ExtDataKey::Reversible(id))
+            {
+                if transfer.state == ReversalState::Pending {
+                    pending.push_back(PendingOp {
+                        kind: PendingKind::Reversible,
+                        id,
+                        amount: transfer.amount,
+                        ledger: transfer.settle_ledger,
+                    });
+                }
+            }
+
+            if let Some(stream) = storage.get::<_, Stream>(&ExtDataKey::Stream(id)) {
+                pending.push_back(PendingOp {
+                    kind: PendingKind::Stream,
+                    id,
+                    amount: Self::stream_accrued(&stream, stream.stop_ledger),
+                    ledger: stream.stop_ledger,
+                });
+            }
+        }
+        pending
+    }
+
+    /// Returns the percentage of successful transfers across the last `n`
+    /// batches, clamped to the stored history; 0 when there is none.
+    ///
+    /// At most `MAX_BATCH_SIZE` batches can be read at once.
+    pub fn get_recent_success_rate(env: Env, n: u32) -> u32 {
+        if n > MAX_BATCH_SIZE {
+            panic_with_error!(&env, BatchTransferError::BatchTooLarge);
+        }
+        let total_batches = Self::get_total_batches(env.clone());
+        let count = (n as u64).min(total_batches);
+
+        let mut successful: u64 = 0;
+        let mut attempted: u64 = 0;
+        for batch_id in (total_batches - count + 1)..=total_batches {
+            if let Some(record) = env
+                .storage()
+                .persistent()
+                .get::<_, BatchRecord>(&DataKey::Batch(batch_id))
+            {
+                successful += record.successful as u64;
+                attempted += (record.successful + record.failed) as u64;
+            }
+        }
+
+        if attempted == 0 {
+            return 0;
+        }
+        (successful * 100 / attempted) as u32
+    }
+
+    /// Returns the total number of transfers processed (successful + failed).
+    pub fn get_total_transfers_processed(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TotalTransfersProcessed)
+            .unwrap_or(0)
+    }
+
+    /// Returns the total volume transferred (in stroops).
+    pub fn get_total_volume_transferred(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TotalVolumeTransferred)
+            .unwrap_or(0)
+    }
+
+    /// Closes the current stats period and starts a new one.
+    ///
+    /// The period's totals are kept as a snapshot (the latest
+    /// `MAX_STAT_SNAPSHOTS` are retained) and emitted as an event. Lifetime
+    /// totals are left untouched, since batch ids and the lifetime cap rely
+    /// on them.
+    pub fn reset_stats(env: Env, admin: Address) -> StatSnapshot {
+        admin.require_auth();
+        Self::require_admin(&env, &admin);
+
+        let lifetime = StatSnapshot {
+            reset_ledger: env.ledger().sequence(),
+            batches: Self::get_total_batches(env.clone()),
+            transfers_processed: Self::get_total_transfers_processed(env.clone()),
+            volume: Self::get_total_volume_transferred(env.clone()),
+            fees: Self::get_total_fees_collected(env.clone()),
+        };
+        let snapshot = match env
+            .storage()
+            .instance()
+            .get::<_, StatSnapshot>(&ExtDataKey::StatsBaseline)
+        {
+            Some(baseline) => StatSnapshot {
+                reset_ledger: lifetime.reset_ledger,
+                batches: lifetime.batches - baseline.batches,
+                transfers_processed: lifetime.transfers_processed - baseline.transfers_processed,
+                volume: lifetime.volume.saturating_sub(baseline.volume),
+                fees: lifetime.fees.saturating_sub(baseline.fees),
+            },
+            None => lifetime.clone(),
+        };
+
+        let mut snapshots = Self::get_stat_snapshots(env.clone(), MAX_STAT_SNAPSHOTS);
+        snapshots.push_front(snapshot.clone());
+        if snapshots.len() > MAX_STAT_SNAPSHOTS {
+            snapshots.pop_back();
+        }
+        env.storage()
+            .instance()
+            .set(&ExtDataKey::StatSnapshots, &snapshots);
+        env.storage()
+            .instance()
+            .set(&ExtDataKey::StatsBaseline, &lifetime);
+        TransferEvents::stats_reset(&env, &snapshot);
+        snapshot
+    }
+
+    /// Returns up to `limit` of the most recent stats snapshots, newest first.
+    pub fn get_stat_snapshots(env: Env, limit: u32) -> Vec<StatSnapshot> {
+        let snapshots: Vec<StatSnapshot> = env
+            .storage()
+            .instance()
+            .get(&ExtDataKey::StatSnapshots)
+            .unwrap_or(Vec::new(&env));
+        snapshots.slice(0..limit.min(snapshots.len()))
+    }
+
+    // Internal helper that processes one validated batch, emits its events and
+    // updates the lifetime stats.
+    fn execute_batch(
+        env: &Env,
+        caller: &Address,
+        token: &Address,
+        transfers: &Vec<TransferRequest>,
+    ) -> BatchTransferResult {
+        Self::execute_batch_with_memos(env, caller, token, transfers, None, OrderMode::AsGiven)
+    }
+
+    // Internal helper running a batch whose items may carry memos, aligned
+    // with `transfers`, executed in `order`. Batches with memos are never
+    // merged.
+    fn execute_batch_with_memos(
+        env: &Env,
+        caller: &Address,
+        token: &Address,
+        transfers: &Vec<TransferRequest>,
+        memos: Option<&Vec<String>>,
+        order: OrderMode,
+    ) -> BatchTransferResult {
+        // Drop zero-amount padding from the end of fixed-size client buffers
+        let mut transfers = transfers.clone();
+        if Self::get_trim_trailing_zeros(env.clone()) {
+            while transfers.last().is_some_and(|request| request.amount == 0) {
+                transfers.pop_back();
+            }
+            if transfers.is_empty() {
+                panic_with_error!(env, BatchTransferError::EmptyBatch);
+            }
+        }
+        let request_count = transfers.len();
+
+        // Get batch ID and increment
+        let batch_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalBatches)
+            .unwrap_or(0)
+            + 1;
+
+        // Emit batch started event
+        TransferEvents::batch_started(env, batch_id, request_count);
+
+        // Initialize result vectors
+        let mut results: Vec<TransferResult> = Vec::new(env);
+        let mut successful_count: u32 = 0;
+        let mut failed_count: u32 = 0;
+        let mut total_transferred: i128 = 0;
+
+        // Create token client
+        let token_client = token::Client::new(env, token);
+
+        // Get initial balance
+        let mut available_balance = token_client.balance(caller);
+
+        // Load fee configuration, preferring the token's own rate. A fee paid
+        // to the sender itself is a no-op, so it is neither reserved from the
+        // balance nor recorded.
+        let fee_collector: Option<Address> = env.storage().instance().get(&DataKey::FeeCollector);
+        let fee_bps: u32 = match &fee_collector {
+            Some(collector) if collector != caller => {
+                Self::get_token_fee(env.clone(), token.clone())
+                    .unwrap_or_else(|| Self::get_fee_bps(env.clone()))
+            }
+            _ => 0,
+        };
+        let mut total_fees: i128 = 0;
+
+        // In compressed mode per-item events are replaced by one batch log
+        let compressed = env
+            .storage()
+            .instance()
+            .get(&DataKey::EventMode)
+            .unwrap_or(EventMode::Full)
+            == EventMode::Compressed;
+
+        let large_threshold = Self::get_large_transfer_alert(env.clone());
+        let verify_deltas: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::VerifyDeltas)
+            .unwrap_or(false);
+
+        // Volume already used in the current rate-limit window, if limited
+        let mut rate_window: Option<(RateLimit, RateLimitWindow)> = env
+            .storage()
+            .instance()
+            .get(&DataKey::RateLimit)
+            .map(|limit| {
+                let window = Self::current_rate_window(env, &limit);
+                (limit, window)
+            });
+
+        // Volume the submitting operator already used today, if limited
+        let mut operator_budget = Self::operator_budget(env, caller);
+        // Amount a sender with a registered wallet already spent today, if limited
+        let mut wallet_budget = Self::wallet_budget(env, caller, token);
+
+        // Fold duplicate recipients into their first occurrence when enabled
+        let (transfers, merged_count) = if memos.is_none()
+            && env
+                .storage()
+                .instance()
+                .get(&DataKey::MergeDuplicates)
+                .unwrap_or(false)
+        {
+            Self::merge_duplicates(env, &transfers)
+        } else {
+            (transfers, 0)
+        };
+
+        // Calculate total needed for all valid transfers and validate upfront.
+        // Saturates at i128::MAX, which keeps the invariant check below sound.
+        let mut total_needed: i128 = 0;
+        let mut validated_requests: Vec<(TransferRequest, bool, u32)> = Vec::new(env);
+
+        // Memos already used in this batch, when they must be unique
+        let mut seen_memos: Option<Map<String, bool>> = memos
+            .filter(|_| Self::get_unique_memos(env.clone()))
+            .map(|_| Map::new(env));
+
+        // First pass: Validate all requests and calculate total needed
+        for (index, request) in transfers.iter().enumerate() {
+            let mut is_valid = true;
+            let mut error_code = 0u32;
+
+            // Validate recipient address
+            if validate_address(env, &request.recipient).is_err() {
+                is_valid = false;
+                error_code = ErrorCode::INVALID_ADDRESS;
+            }
+            // Validate amount
+            else if validate_amount(request.amount).is_err() {
+                is_valid = false;
+                error_code = ErrorCode::INVALID_AMOUNT;
+            }
+            // Reject a memo repeated within the batch
+            else if let (Some(seen), Some(memos)) = (seen_memos.as_mut(), memos) {
+                let memo = memos.get_unchecked(index as u32);
+                if seen.contains_key(memo.clone()) {
+                    is_valid = false;
+                    error_code = ErrorCode::DUPLICATE_MEMO;
+                } else {
+                    seen.set(memo, true);
+                }
+            }
+
+            if is_valid {
+                total_needed = total_needed
+                    .checked_add(request.amount)
+                    .unwrap_or(i128::MAX);
+            }
+
+            validated_requests.push_back((request.clone(), is_valid, error_code));
+        }
+
+        // Second pass: Process each request in execution order
+        let execution_order = Self::execution_order(env, &validated_requests, order);
+        for index in execution_order.iter() {
+            let (request, is_valid, error_code) = validated_requests.get_unchecked(index);
+            if !is_valid {
+                // Validation failed - record and continue
+                results.push_back(TransferResult::Failure(
+                    request.recipient.clone(),
+                    request.amount,
+                    error_code,
+                ));
+                failed_count += 1;
+                Self::record_failure(env, error_code);
+                if !compressed {
+                    TransferEvents::transfer_failure(
+                        env,
+                        batch_id,
+                        &request.recipient,
+                        request.amount,
+                        error_code,
+                    );
+                }
+                continue;
+            }
+
+            // Check balance for this transfer, including its fee
+            let fee_and_required = Self::compute_fee(request.amount, fee_bps)
+                .and_then(|fee| Some((fee, request.amount.checked_add(fee)?)));
+            let failure_code = if fee_and_required.is_none() {
+                Some(ErrorCode::FEE_OVERFLOW)
+            } else if fee_and_required.is_some_and(|(_, required)| available_balance < required) {
+                Some(ErrorCode::INSUFFICIENT_BALANCE)
+            } else if rate_window.as_ref().is_some_and(|(limit, window)| {
+                window
+                    .used
+                    .checked_add(request.amount)
+                    .is_none_or(|used| used > limit.max_volume)
+            }) {
+                Some(ErrorCode::RATE_LIMITED)
+            } else if operator_budget.as_ref().is_some_and(|(limit, usage)| {
+                usage
+                    .used
+                    .checked_add(request.amount)
+                    .is_none_or(|used| used > *limit)
+            }) {
+                Some(ErrorCode::OPERATOR_LIMIT_EXCEEDED)
+            } else if wallet_budget.as_ref().is_some_and(|(limit, usage)| {
+                usage
+                    .used
+                    .checked_add(request.amount)
+                    .is_none_or(|used| used > *limit)
+            }) {
+                Some(ErrorCode::WALLET_LIMIT_EXCEEDED)
+            } else if Self::recipient_cap_exceeded(env, &request.recipient, request.amount) {
+                Some(ErrorCode::RECIPIENT_CAP_EXCEEDED)
+            } else {
+                None
+            };
+            if let Some(error_code) = failure_code {
+                results.push_back(TransferResult::Failure(
+                    request.recipient.clone(),
+                    request.amount,
+                    error_code,
+                ));
+                failed_count += 1;
+                Self::record_failure(env, error_code);
+                if !compressed {
+                    TransferEvents::transfer_failure(
+                        env,
+                        batch_id,
+                        &request.recipient,
+                        request.amount,
+                        error_code,
+                    );
+                }
+                continue;
+            }
+            let (fee, required) = fee_and_required.unwrap_or_default();
+
+            // Execute transfer
+            // Note: A transfer the token rejects is recorded without reverting
+            // the batch, as `RECIPIENT_NOT_READY` when the token blames the
+            // recipient (e.g. no trustline) and `TRANSFER_REJECTED` otherwise.
+            let balance_before = if verify_deltas {
+                token_client.balance(&request.recipient)
+            } else {
+                0
+            };
+            let outcome = token_client.try_transfer(caller, &request.recipient, &request.amount);
+            if !matches!(outcome, Ok(Ok(()))) {
+                let error_code = match outcome {
+                    Err(Ok(error)) if Self::rejected_by_recipient(env, token, caller, error) => {
+                        ErrorCode::RECIPIENT_NOT_READY
+                    }
+                    _ => ErrorCode::TRANSFER_REJECTED,
+                };
+                results.push_back(TransferResult::Failure(
+                    request.recipient.clone(),
+                    request.amount,
+                    error_code,
+                ));
+                failed_count += 1;
+                Self::record_failure(env, error_code);
+                if !compressed {
+                    TransferEvents::transfer_failure(
+                        env,
+                        batch_id,
+                        &request.recipient,
+                        request.amount,
+                        error_code,
+                    );
+                }
+                continue;
+            }
+
+            // Record what the recipient actually received when verifying. A
+            // token crediting extra (rebasing, bonuses) is capped at the
+            // requested amount so it cannot trip the volume invariant below.
+            let delivered = if verify_deltas {
+                (token_client.balance(&request.recipient) - balance_before).min(request.amount)
+            } else {
+                request.amount
+            };
+
+            // Transfer succeeded
+            available_balance -= required;
+            if let Some((_, window)) = rate_window.as_mut() {
+                window.used += request.amount;
+            }
+            if let Some((_, usage)) = operator_budget.as_mut() {
+                usage.used += request.amount;
+            }
+            if let Some((_, usage)) = wallet_budget.as_mut() {
+                usage.used += request.amount;
+            }
+            Self::record_received(env, &request.recipient, request.amount);
+            total_fees += fee;
+            successful_count += 1;
+            total_transferred = total_transferred
+                .checked_add(delivered)
+                .unwrap_or(total_transferred);
+
+            if delivered < request.amount {
+                results.push_back(TransferResult::PartialDelivery(
+                    request.recipient.clone(),
+                    request.amount,
+                    delivered,
+                ));
+                if !compressed {
+                    TransferEvents::transfer_partial(
+                        env,
+                        batch_id,
+                        &request.recipient,
+                        request.amount,
+                        delivered,
+                    );
+                }
+            } else {
+                results.push_back(TransferResult::Success(
+                    request.recipient.clone(),
+                    request.amount,
+                ));
+                if !compressed {
+                    TransferEvents::transfer_success(
+                        env,
+                        batch_id,
+                        &request.recipient,
+                        request.amount,
+                    );
+                }
+            }
+            if large_threshold > 0 && request.amount >= large_threshold {
+                TransferEvents::large_transfer(
+                    env,
+                    batch_id,
+                    caller,
+                    &request.recipient,
+                    request.amount,
+                );
+            }
+            if let Some(memos) = memos {
+                let memo = memos.get_unchecked(index);
+                TransferEvents::transfer_memo(env, batch_id, &request.recipient, &memo);
+            }
+        }
+
+        // Report results in input order regardless of execution order
+        if order != OrderMode::AsGiven {
+            let mut by_index: Map<u32, TransferResult> = Map::new(env);
+            for (index, result) in execution_order.iter().zip(results.iter()) {
+                by_index.set(index, result);
+            }
+            results = by_index.values();
+        }
+
+        if let Some((_, window)) = &rate_window {
+            env.storage()
+                .instance()
+                .set(&DataKey::RateLimitWindow, window);
+        }
+        if let Some((_, usage)) = &operator_budget {
+            env.storage()
+                .persistent()
+                .set(&DataKey::OperatorUsage(caller.clone()), usage);
+        }
+        if let Some((_, usage)) = &wallet_budget {
+            env.storage().persistent().set(
+                &ExtDataKey::WalletSpend(caller.clone(), token.clone()),
+                usage,
+            );
+        }
+
+        // Delivered volume can never exceed the valid requested volume; more
+        // means an item was counted twice
+        if total_transferred > total_needed {
+            panic!("Invariant violated");
+        }
+
+        // Collect the accumulated fee in a single transfer. A collector the
+        // token rejects must not undo the committed transfers, so the fee is
+        // skipped and flagged instead.
+        if total_fees > 0 {
+            if let Some(collector) = &fee_collector {
+                if matches!(
+                    token_client.try_transfer(caller, collector, &total_fees),
+                    Ok(Ok(()))
+                ) {
+                    TransferEvents::fee_collected(env, batch_id, collector, total_fees);
+                } else {
+                    TransferEvents::fee_transfer_failed(env, batch_id, collector, total_fees);
+                    total_fees = 0;
+                }
+            }
+        }
+
+        if successful_count > 0
+            && Self::get_warn_on_drain(env.clone())
+            && token_client.balance(caller) == 0
+        {
+            TransferEvents::sender_drained(env, batch_id, caller, token);
+        }
+
+        // Update storage (batched at the end for efficiency)
+        let record = BatchRecord {
+            batch_id,
+            sender: caller.clone(),
+            token: token.clone(),
+            total_requests: request_count,
+            successful: successful_count,
+            failed: failed_count,
+            total_transferred,
+            ledger: env.ledger().sequence(),
+            timestamp: env.ledger().timestamp(),
+        };
+        Self::record_batch_stats(env, &record);
+        if total_fees > 0 {
+            let fees_collected: i128 = env
+                .storage()
+                .instance()
+                .get(&DataKey::TotalFeesCollected)
+                .unwrap_or(0);
+            env.storage().instance().set(
+                &DataKey::TotalFeesCollected,
+                &fees_collected.checked_add(total_fees).unwrap_or(i128::MAX),
+            );
+            Self::add_token_stat(env, DataKey::TokenFees(token.clone()), total_fees);
+        }
+
+        if compressed {
+            TransferEvents::batch_log(env, batch_id, &encode_batch_log(env, &results));
+        }
+
+        // Emit batch completed event
+        TransferEvents::batch_completed(
+            env,
+            batch_id,
+            successful_count,
+            failed_count,
+            total_transferred,
+        );
+        Self::notify_callback(env, &record);
+
+        let attempted = successful_count + failed_count;
+        let result = BatchTransferResult {
+            token: token.clone(),
+            total_requests: request_count,
+            successful: successful_count,
+            failed: failed_count,
+            attempted,
+            not_attempted: request_count - attempted - merged_count,
+            merged_count,
+            total_transferred,
+            results,
+        };
+        Self::store_last_result(env, &result);
+        result
+    }
+
+    // Internal helper keeping the most recent batch result for
+    // `get_last_batch_result`
+    fn store_last_result(env: &Env, result: &BatchTransferResult) {
+        let key = DataKey::LastBatchResult;
+        env.storage().persistent().set(&key, result);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, RESULT_TTL_THRESHOLD, RESULT_TTL_EXTEND);
+    }
+
+    // Internal helper invoking the registered callback without letting its
+    // failure revert the batch
+    fn notify_callback(env: &Env, record: &BatchRecord) {
+        let callback: Address = match env.storage().instance().get(&DataKey::Callback) {
+            Some(callback) => callback,
+            None => return,
+        };
+        let outcome = env.try_invoke_contract::<(), InvokeError>(
+            &callback,
+            &Symbol::new(env, "on_batch_complete"),
+            (record.batch_id, record.clone()).into_val(env),
+        );
+        if !matches!(outcome, Ok(Ok(()))) {
+            TransferEvents::callback_failed(env, record.batch_id, &callback);
+        }
+    }
+
+    // Internal helper fetching the wallet of `owner` from the wallet registry,
+    // treating a missing registry or a failed call as no wallet
+    fn registry_wallet(env: &Env, owner: &Address) -> Option<RegistryWallet> {
+        let registry: Address = env.storage().instance().get(&DataKey::WalletRegistry)?;
+        match env.try_invoke_contract::<Option<RegistryWallet>, InvokeError>(
+            &registry,
+            &Symbol::new(env, "get_wallet"),
+            (owner.clone(),).into_val(env),
+        ) {
+            Ok(Ok(wallet)) => wallet,
+            _ => None,
+        }
+    }
+
+    // Internal helper summing valid requests to the same recipient into one,
+    // returning the merged list and how many requests were folded away
+    fn merge_duplicates(
+        env: &Env,
+        transfers: &Vec<TransferRequest>,
+    ) -> (Vec<TransferRequest>, u32) {
+        let mut merged: Vec<TransferRequest> = Vec::new(env);
+        let mut first_index: Map<Address, u32> = Map::new(env);
+        let mut merged_count: u32 = 0;
+
+        for request in transfers.iter() {
+            if validate_amount(request.amount).is_ok() {
+                if let Some(index) = first_index.get(request.recipient.clone()) {
+                    let mut existing = merged.get_unchecked(index);
+                    if let Some(amount) = existing.amount.checked_add(request.amount) {
+                        existing.amount = amount;
+                        merged.set(index, existing);
+                        merged_count += 1;
+                        continue;
+                    }
+                } else {
+                    first_index.set(request.recipient.clone(), merged.len());
+                }
+            }
+            merged.push_back(request);
+        }
+
+        (merged, merged_count)
+    }
+
+    // Internal helper checking whether `amount` would exceed the recipient's cap
+    fn recipient_cap_exceeded(env: &Env, recipient: &Address, amount: i128) -> bool {
+        let cap: i128 = match env
+            .storage()
+            .persistent()
+            .get(&DataKey::RecipientCap(recipient.clone()))
+        {
+            Some(cap) => cap,
+            None => return false,
+        };
+        let received: i128 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::RecipientReceived(recipient.clone()))
+            .unwrap_or(0);
+        received
+            .checked_add(amount)
+            .is_none_or(|received| received > cap)
+    }
+
+    // Internal helper returning the indexes of `requests` in the order they
+    // should execute. Ties keep their input order.
+    fn execution_order(
+        env: &Env,
+        requests: &Vec<(TransferRequest, bool, u32)>,
+        order: OrderMode,
+    ) -> Vec<u32> {
+        if order == OrderMode::AsGiven {
+            let mut indexes = Vec::new(env);
+            for index in 0..requests.len() {
+                indexes.push_back(index);
+            }
+            return indexes;
+        }
+
+        // Map keys iterate sorted, so keying by amount sorts the batch. The
+        // descending order is built back to front, so its tie-breaker is
+        // flipped to keep ties in input order.
+        let mut sorted: Map<(i128, u32), u32> = Map::new(env);
+        for (index, (request, _, _)) in requests.iter().enumerate() {
+            let index = index as u32;
+            let tie_break = match order {
+                OrderMode::DescendingAmount => u32::MAX - index,
+                _ => index,
+            };
+            sorted.set((request.amount, tie_break), index);
+        }
+        let mut indexes = Vec::new(env);
+        for index in sorted.values().iter() {
+            if order == OrderMode::DescendingAmount {
+                indexes.push_front(index);
+            } else {
+                indexes.push_back(index);
+            }
+        }
+        indexes
+    }
+
+    // Internal helper counting a failed item under its error code
+    fn record_failure(env: &Env, error_code: u32) {
+        let key = ExtDataKey::FailureCount(error_code);
+        let count: u64 = env.storage().instance().get(&key).unwrap_or(0);
+        env.storage().instance().set(&key, &(count + 1));
+    }
+
+    // Internal helper deciding whether a Stellar Asset Contract error blames
+    // the recipient. The sender passed the balance check, so a missing
+    // account or trustline is the recipient's; a deauthorized balance is only
+    // the recipient's while the sender itself is still authorized.
+    fn rejected_by_recipient(
+        env: &Env,
+        token: &Address,
+        sender: &Address,
+        error: soroban_sdk::Error,
+    ) -> bool {
+        let blames = |code: u32| error == soroban_sdk::Error::from_contract_error(code);
+        if blames(SAC_ACCOUNT_MISSING) || blames(SAC_TRUSTLINE_MISSING) {
+            return true;
+        }
+        blames(SAC_BALANCE_DEAUTHORIZED)
+            && matches!(
+                token::StellarAssetClient::new(env, token).try_authorized(sender),
+                Ok(Ok(true))
+            )
+    }
+
+    // Internal helper counting `amount` against a capped recipient
+    fn record_received(env: &Env, recipient: &Address, amount: i128) {
+        let seen_key = DataKey::HasReceived(recipient.clone());
+        if !env.storage().persistent().has(&seen_key) {
+            env.storage().persistent().set(&seen_key, &true);
+            let unique: u64 = Self::get_unique_recipients(env.clone());
+            env.storage()
+                .instance()
+                .set(&DataKey::UniqueRecipients, &(unique + 1));
+        }
+
+        if !env
+            .storage()
+            .persistent()
+            .has(&DataKey::RecipientCap(recipient.clone()))
+        {
+            return;
+        }
+        let key = DataKey::RecipientReceived(recipient.clone());
+        let received: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        env.storage().persistent().set(&key, &(received + amount));
+    }
+
+    // Internal helper rejecting a batch from `sender` inside the cooldown
+    // and recording the current ledger as its latest batch
+    fn enforce_batch_cooldown(env: &Env, sender: &Address) {
+        let cooldown = Self::get_batch_cooldown(env.clone());
+        if cooldown == 0 {
+            return;
+        }
+        let key = ExtDataKey::LastBatchLedger(sender.clone());
+        let sequence = env.ledger().sequence();
+        if let Some(last) = env.storage().persistent().get::<_, u32>(&key) {
+            if sequence < last.saturating_add(cooldown) {
+                panic_with_error!(env, BatchTransferError::BatchCooldownActive);
+            }
+        }
+        env.storage().persistent().set(&key, &sequence);
+    }
+
+    // Internal helper returning the daily limit of `operator` with its usage
+    // for the current day, or `None` if the operator is unlimited
+    fn operator_budget(env: &Env, operator: &Address) -> Option<(i128, OperatorUsage)> {
+        let limit: i128 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::OperatorLimit(operator.clone()))?;
+        let day = env.ledger().sequence() / LEDGERS_PER_DAY;
+        let usage = match env
+            .storage()
+            .persistent()
+            .get::<_, OperatorUsage>(&DataKey::OperatorUsage(operator.clone()))
+        {
+            Some(usage) if usage.day == day => usage,
+            _ => OperatorUsage { day, used: 0 },
+        };
+        Some((limit, usage))
+    }
+
+    // Internal helper returning the daily spend limit of wallet `owner` for
+    // `token` with its spending today, or `None` if the owner is unlimited or
+    // has no registered wallet
+    fn wallet_budget(env: &Env, owner: &Address, token: &Address) -> Option<(i128, OperatorUsage)> {
+        let limit: i128 = env
+            .storage()
+            .persistent()
+            .get(&ExtDataKey::WalletSpendLimit(owner.clone(), token.clone()))?;
+        Self::registry_wallet(env, owner)?;
+        let day = env.ledger().sequence() / LEDGERS_PER_DAY;
+        let usage = match env
+            .storage()
+            .persistent()
+            .get::<_, OperatorUsage>(&ExtDataKey::WalletSpend(owner.clone(), token.clone()))
+        {
+            Some(usage) if usage.day == day => usage,
+            _ => OperatorUsage { day, used: 0 },
+        };
+        Some((limit, usage))
+    }
+
+    // Internal helper returning the rate-limit window covering the current ledger
+    fn current_rate_window(env: &Env, limit: &RateLimit) -> RateLimitWindow {
+        let sequence = env.ledger().sequence();
+        match env
+            .storage()
+            .instance()
+            .get::<_, RateLimitWindow>(&DataKey::RateLimitWindow)
+        {
+            Some(window) if sequence < window.start_ledger.saturating_add(limit.window_ledgers) => {
+                window
+            }
+            _ => RateLimitWindow {
+                start_ledger: sequence,
+                used: 0,
+            },
+        }
+    }
+
+    // Internal helper updating the counters and storing the record of a batch
+    fn record_batch_stats(env: &Env, record: &BatchRecord) {
+        let token = &record.token;
+        let request_count = record.total_requests;
+        let total_transferred = record.total_transferred;
+        let total_batches: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalBatches)
+            .unwrap_or(0);
+        let total_processed: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalTransfersProcessed)
+            .unwrap_or(0);
+        let total_volume: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalVolumeTransferred)
+            .unwrap_or(0);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalBatches, &(total_batches + 1));
+        env.storage()
+            .instance()
+            .set(&DataKey::LastBatchLedger, &env.ledger().sequence());
+        env.storage()
+            .instance()
+            .set(&DataKey::LastBatchTimestamp, &env.ledger().timestamp());
+        env.storage().instance().set(
+            &DataKey::TotalTransfersProcessed,
+            &(total_processed + request_count as u64),
+        );
+        env.storage().instance().set(
+            &DataKey::TotalVolumeTransferred,
+            &total_transferred
+                .checked_add(total_volume)
+                .unwrap_or(i128::MAX),
+        );
+        if !Self::is_paused(env.clone()) && Self::lifetime_cap_reached(env) {
+            env.storage().instance().set(&DataKey::Paused, &true);
+            TransferEvents::auto_paused(
+                env,
+                Self::get_total_volume_transferred(env.clone()),
+                Self::get_lifetime_cap(env.clone()),
+            );
+        }
+        let record_key = DataKey::Batch(record.batch_id);
+        env.storage().persistent().set(&record_key, record);
+        env.storage()
+            .persistent()
+            .extend_ttl(&record_key, RESULT_TTL_THRESHOLD, RESULT_TTL_EXTEND);
+        if record.successful > 0 {
+            let transfers_key = DataKey::TokenTransfers(token.clone());
+            let token_transfers: u64 = env.storage().persistent().get(&transfers_key).unwrap_or(0);
+            env.storage().persistent().set(
+                &transfers_key,
+                &(token_transfers + record.successful as u64),
+            );
+        }
+        if total_transferred > 0 {
+            let volume_key = DataKey::TokenVolume(token.clone());
+            let token_volume: i128 = env.storage().persistent().get(&volume_key).unwrap_or(0);
+            env.storage().persistent().set(
+                &volume_key,
+                &token_volume
+                    .checked_add(total_transferred)
+                    .unwrap_or(i128::MAX),
+            );
+        }
+    }
+
+    // Internal helper adding the rounding dust of a split to its shares
+    // according to `mode`
+    fn distribute_dust(
+        transfers: &mut Vec<TransferRequest>,
+        fractions: &Vec<i128>,
+        dust: i128,
+        mode: RoundingMode,
+    ) {
+        if dust <= 0 {
+            return;
+        }
+        let add = |transfers: &mut Vec<TransferRequest>, index: u32, amount: i128| {
+            let mut transfer = transfers.get_unchecked(index);
+            transfer.amount += amount;
+            transfers.set(index, transfer);
+        };
+
+        match mode {
+            RoundingMode::RemainderToFirst => add(transfers, 0, dust),
+            RoundingMode::RemainderToLast => add(transfers, transfers.len() - 1, dust),
+            RoundingMode::RemainderToLargest => {
+                let mut largest = 0;
+                let mut largest_amount = i128::MIN;
+                for (index, transfer) in transfers.iter().enumerate() {
+                    if transfer.amount > largest_amount {
+                        largest = index as u32;
+                        largest_amount = transfer.amount;
+                    }
+                }
+                add(transfers, largest, dust);
+            }
+            RoundingMode::BankersApprox => {
+                // Dust is below the number of shares, so each gets at most one unit
+                let mut fractions = fractions.clone();
+                for _ in 0..dust {
+                    let mut best = 0;
+                    for (index, fraction) in fractions.iter().enumerate() {
+                        if fraction > fractions.get_unchecked(best) {
+                            best = index as u32;
+                        }
+                    }
+                    fractions.set(best, -1);
+                    add(transfers, best, 1);
+                }
+            }
+        }
+    }
+
+    // Internal helper adding `amount` to a per-token running total
+    fn add_token_stat(env: &Env, key: DataKey, amount: i128) {
+        if amount <= 0 {
+            return;
+        }
+        let total: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        env.storage()
+            .persistent()
+            .set(&key, &total.checked_add(amount).unwrap_or(i128::MAX));
+    }
+
+    // Internal helper computing `bps` basis points of `amount`, rounded down,
+    // without overflowing for large amounts
+    fn bps_of(amount: i128, bps: u32) -> i128 {
+        let bps = bps as i128;
+        amount / 10_000 * bps + amount % 10_000 * bps / 10_000
+    }
+
+    // Internal helper computing the fee owed on a single transfer, or `None`
+    // if `amount * fee_bps` overflows.
+    // Amounts are validated positive, so the division truncates (rounds down)
+    // and the per-item fees of a batch never sum to more than
+    // `total * fee_bps / 10_000`.
+    fn compute_fee(amount: i128, fee_bps: u32) -> Option<i128> {
+        Some(amount.checked_mul(fee_bps as i128)? / 10_000)
+    }
+
+    // Internal helper rejecting operations while the contract is paused
+    fn require_not_paused(env: &Env) {
+        if env
+            .storage()
+            .instance()
+            .get(&DataKey::Paused)
+            .unwrap_or(false)
+        {
+            panic_with_error!(env, BatchTransferError::ContractPaused);
+        }
+    }
+
+    // Internal helper checking whether the lifetime volume cap has been reached
+    fn lifetime_cap_reached(env: &Env) -> bool {
+        let cap = Self::get_lifetime_cap(env.clone());
+        cap > 0 && Self::get_total_volume_transferred(env.clone()) >= cap
+    }
+
+    // Internal helper rejecting batches funded by a suspended sender or one
+    // missing from the allowlist
+    fn require_sender_allowed(env: &Env, sender: &Address) {
+        if Self::is_sender_suspended(env.clone(), sender.clone()) {
+            panic!("Sender suspended");
+        }
+        if !Self::is_sender_allowed(env.clone(), sender.clone()) {
+            panic_with_error!(env, BatchTransferError::SenderNotAllowed);
+        }
+    }
+
+    // Internal helper rejecting tokens whose decimals differ from the expected value
+    fn require_expected_decimals(env: &Env, token: &Address) {
+        if let Some(expected) = env
+            .storage()
+            .instance()
+            .get::<_, u32>(&DataKey::ExpectedDecimals)
+        {
+            if token::Client::new(env, token).decimals() != expected {
+                panic!("Unexpected token decimals");
+            }
+        }
+    }
+
+    // Internal helper rejecting operations on blocked tokens
+    fn require_token_allowed(env: &Env, token: &Address) {
+        if env
+            .storage()
+            .persistent()
+            .get(&DataKey::BlockedToken(token.clone()))
+            .unwrap_or(false)
+        {
+            panic_with_error!(env, BatchTransferError::TokenBlocked);
+        }
+    }
+
+    // Internal helper loading a transfer schedule with unreleased items
+    fn load_transfer_schedule(env: &Env, schedule_id: u64) -> TransferSchedule {
+        match env
+            .storage()
+            .persistent()
+            .get(&DataKey::TransferSchedule(schedule_id))
+        {
+            Some(schedule) => schedule,
+            None => panic_with_error!(env, BatchTransferError::ScheduleNotFound),
+        }
+    }
+
+    // Internal helper returning the ids of a recipient's outstanding claims
+    fn recipient_claims(env: &Env, recipient: &Address) -> Vec<u64> {
+        env.storage()
+            .persistent()
+            .get(&ExtDataKey::RecipientClaims(recipient.clone()))
+            .unwrap_or(Vec::new(env))
+    }
+
+    // Internal helper storing a recipient's claim index with a fresh TTL
+    fn store_recipient_claims(env: &Env, recipient: &Address, claim_ids: &Vec<u64>) {
+        let storage_key = ExtDataKey::RecipientClaims(recipient.clone());
+        if claim_ids.is_empty() {
+            env.storage().persistent().remove(&storage_key);
+            return;
+        }
+        env.storage().persistent().set(&storage_key, claim_ids);
+        env.storage().persistent().extend_ttl(
+            &storage_key,
+            RESULT_TTL_THRESHOLD,
+            RESULT_TTL_EXTEND,
+        );
+    }
+
+    // Internal helper adding a claim to its recipient's index
+    fn index_claim(env: &Env, recipient: &Address, claim_id: u64) {
+        let mut claim_ids = Self::recipient_claims(env, recipient);
+        claim_ids.push_back(claim_id);
+        Self::store_recipient_claims(env, recipient, &claim_ids);
+    }
+
+    // Internal helper dropping a settled or reclaimed claim from the index
+    fn unindex_claim(env: &Env, recipient: &Address, claim_id: u64) {
+        let mut claim_ids = Self::recipient_claims(env, recipient);
+        if let Some(index) = claim_ids.first_index_of(claim_id) {
+            claim_ids.remove(index);
+            Self::store_recipient_claims(env, recipient, &claim_ids);
+        }
+    }
+
+    // Internal helper loading an outstanding claim
+    fn load_claim(env: &Env, claim_id: u64) -> Claim {
+        match env.storage().persistent().get(&DataKey::Claim(claim_id)) {
+            Some(claim) => claim,
+            None => panic_with_error!(env, BatchTransferError::ClaimNotFound),
+        }
+    }
+
+    // Internal helper paying out `amount` (or all) of a claim to its recipient
+    fn release_claim(
+        env: &Env,
+        recipient: &Address,
+        claim_id: u64,
+        mut claim: Claim,
+        amount: Option<i128>,
+    ) -> i128 {
+        if claim.recipient != *recipient {
+            panic_with_error!(env, BatchTransferError::Unauthorized);
+        }
+        if env.ledger().sequence() >= claim.expires_ledger {
+            panic_with_error!(env, BatchTransferError::ClaimExpired);
+        }
+
+        let amount = amount.unwrap_or(claim.amount);
+        let storage_key = DataKey::Claim(claim_id);
+        claim.amount -= amount;
+        if claim.amount == 0 {
+            env.storage().persistent().remove(&storage_key);
+            Self::unindex_claim(env, recipient, claim_id);
+        } else {
+            env.storage().persistent().set(&storage_key, &claim);
+        }
+        Self::adjust_locked(env, &claim.token, -amount);
+        token::Client::new(env, &claim.token).transfer(
+            &env.current_contract_address(),
+            recipient,
+            &amount,
+        );
+        TransferEvents::claim_settled(env, claim_id, recipient, amount);
+        amount
+    }
+
+    // Internal helper persisting a reversible transfer with a fresh TTL
+    fn store_reversible(env: &Env, transfer_id: u64, transfer: &ReversibleTransfer) {
+        let storage_key = ExtDataKey::Reversible(transfer_id);
+        env.storage().persistent().set(&storage_key, transfer);
+        env.storage().persistent().extend_ttl(
+            &storage_key,
+            RESULT_TTL_THRESHOLD,
+            RESULT_TTL_EXTEND,
+        );
+    }
+
+    // Internal helper loading a reversible transfer that is still in custody
+    fn load_pending_reversible(env: &Env, transfer_id: u64) -> ReversibleTransfer {
+        let transfer = match Self::get_reversible_transfer(env.clone(), transfer_id) {
+            Some(transfer) => transfer,
+            None => panic_with_error!(env, BatchTransferError::TransferNotFound),
+        };
+        if transfer.state != ReversalState::Pending {
+            panic_with_error!(env, BatchTransferError::TransferFinalized);
+        }
+        transfer
+    }
+
+    // Internal helper persisting a stream and keeping it alive
+    fn store_stream(env: &Env, stream_id: u64, stream: &Stream) {
+        let storage_key = ExtDataKey::Stream(stream_id);
+        env.storage().persistent().set(&storage_key, stream);
+        env.storage().persistent().extend_ttl(
+            &storage_key,
+            RESULT_TTL_THRESHOLD,
+            RESULT_TTL_EXTEND,
+        );
+    }
+
+    // Internal helper loading a stream that still holds funds
+    fn load_stream(env: &Env, stream_id: u64) -> Stream {
+        match Self::get_stream(env.clone(), stream_id) {
+            Some(stream) => stream,
+            None => panic_with_error!(env, BatchTransferError::StreamNotFound),
+        }
+    }
+
+    // Internal helper returning what a stream has accrued since its last
+    // withdrawal up to `ledger`, which must not exceed its stop ledger. Cannot
+    // overflow since the full amount was checked when the stream was opened.
+    fn stream_accrued(stream: &Stream, ledger: u32) -> i128 {
+        let from = stream.withdrawn_ledger.max(stream.start_ledger);
+        if ledger <= from {
+            return 0;
+        }
+        stream.rate_per_ledger * (ledger - from) as i128
+    }
+
+    // Internal helper adding `delta` to the amount of `token` held in custody.
+    // Callers only pass validated amounts or their negation.
+    fn adjust_locked(env: &Env, token: &Address, delta: i128) {
+        let key = DataKey::TotalLocked(token.clone());
+        let locked: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        let locked = locked
+            .checked_add(delta)
+            .expect("Locked total out of range");
+        env.storage().persistent().set(&key, &locked);
+    }
+
+    // Internal helper loading a pending scheduled batch
+    fn load_scheduled(env: &Env, schedule_id: u64) -> ScheduledBatch {
+        match env
+            .storage()
+            .persistent()
+            .get(&DataKey::ScheduledBatch(schedule_id))
+        {
+            Some(scheduled) => scheduled,
+            None => panic_with_error!(env, BatchTransferError::ScheduleNotFound),
+        }
+    }
+
+    // Internal helper rejecting config changes once the config is frozen
+    fn require_config_unfrozen(env: &Env) {
+        if Self::is_config_frozen(env.clone()) {
+            panic!("Config frozen");
+        }
+    }
+
+    // Internal helper refusing to mutate storage written in an older layout.
+    // Also enforced by `require_admin` and `require_operator`.
+    fn require_storage_current(env: &Env) {
+        if Self::get_storage_version(env.clone()) != STORAGE_VERSION {
+            panic!("Storage migration required");
+        }
+    }
+
+    // Internal helper accepting the admin or a registered operator
+    fn require_operator(env: &Env, caller: &Address) {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .expect("Contract not initialized");
+        Self::require_storage_current(env);
+
+        if *caller != admin && !Self::is_operator(env.clone(), caller.clone()) {
+            panic_with_error!(env, BatchTransferError::Unauthorized);
+        }
     }
 
     // Internal helper to verify admin
@@ -397,6 +3966,7 @@ impl BatchTransferContract {
             .instance()
             .get(&DataKey::Admin)
             .expect("Contract not initialized");
+        Self::require_storage_current(env);
 
         if *caller != admin {
             panic_with_error!(env, BatchTransferError::Unauthorized);
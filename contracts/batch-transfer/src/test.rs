@@ -3,14 +3,24 @@
 #![cfg(test)]
 
 use crate::{
-    BatchBurnResult, BatchTransferContract, BatchTransferContractClient, BurnRequest,
-    TransferRequest, TransferResult,
+    encode_batch_log, BatchBurnResult, BatchTransferContract, BatchTransferContractClient,
+    BurnRequest, DataKey, EventMode, MemoTransferRequest, OrderMode, PendingKind, PendingOp,
+    ReversalState, RoundingMode, ScheduledTransfer, TransferFromRequest, TransferRequest,
+    TransferResult, FEE_COLLECTOR_DELAY_LEDGERS, STORAGE_VERSION,
 };
 use soroban_sdk::{
-    testutils::{Address as _, Events as _, Ledger},
-    token, Address, Env, Vec,
+    symbol_short,
+    testutils::{Address as _, Events as _, IssuerFlags, Ledger, MockAuth, MockAuthInvoke},
+    token,
+    xdr::{
+        InvokeContractArgs, ScAddress, ScVal, SorobanAddressCredentials, SorobanAuthorizationEntry,
+        SorobanAuthorizedFunction, SorobanAuthorizedInvocation, SorobanCredentials,
+    },
+    Address, Bytes, BytesN, Env, IntoVal, String, Symbol, TryFromVal, Val, Vec,
 };
 
+extern crate std;
+
 /// Creates a test environment with the contract deployed and initialized.
 fn setup_test_env() -> (
     Env,
@@ -23,6 +33,9 @@ fn setup_test_env() -> (
     env.mock_all_auths();
     env.ledger().with_mut(|li| {
         li.sequence_number = 12345;
+        // Keep instance storage alive across timelock delays
+        li.min_persistent_entry_ttl = 100_000;
+        li.max_entry_ttl = 1_000_000;
     });
 
     // Deploy token contract (simulating XLM StellarAssetContract)
@@ -52,6 +65,14 @@ fn create_burn_request(_env: &Env, owner: Address, amount: i128) -> BurnRequest
     BurnRequest { owner, amount }
 }
 
+/// Reads the topic at `index` as a symbol, or an empty symbol if it is not one.
+fn symbol_topic(env: &Env, topics: &Vec<Val>, index: u32) -> Symbol {
+    topics
+        .get(index)
+        .and_then(|v| Symbol::try_from_val(env, &v).ok())
+        .unwrap_or(Symbol::new(env, ""))
+}
+
 // Initialization Tests
 
 #[test]
@@ -67,12 +88,24 @@ fn test_initialize_contract() {
 #[test]
 #[should_panic(expected = "Contract already initialized")]
 fn test_cannot_initialize_twice() {
-    let (env, admin, _token, _token_client, client) = setup_test_env();
+    let (env, _admin, _token, _token_client, client) = setup_test_env();
 
     let new_admin = Address::generate(&env);
     client.initialize(&new_admin);
 }
 
+#[test]
+fn test_try_init_returns_false_once_initialized() {
+    let env = Env::default();
+    let contract_id = env.register(BatchTransferContract, ());
+    let client = BatchTransferContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    assert!(client.try_init(&admin));
+    assert!(!client.try_init(&Address::generate(&env)));
+    assert_eq!(client.get_admin(), admin);
+}
+
 // Batch Transfer Tests
 
 #[test]
@@ -101,6 +134,25 @@ fn test_batch_transfer_single_recipient() {
     // In production, these would verify actual token balances
 }
 
+#[test]
+fn test_batch_transfer_result_echoes_token() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        1_000,
+    ));
+
+    let result = client.batch_transfer(&admin, &token, &transfers);
+    assert_eq!(result.token, token);
+
+    let chunked = client.batch_transfer_chunked(&admin, &token, &transfers, &1);
+    assert_eq!(chunked.token, token);
+}
+
 #[test]
 fn test_batch_transfer_multiple_recipients() {
     let (env, admin, token, _token_client, client) = setup_test_env();
@@ -240,6 +292,9 @@ fn test_batch_transfer_partial_failures() {
     assert_eq!(result.successful, 2);
     assert_eq!(result.failed, 2);
     assert_eq!(result.total_transferred, 30_000_000);
+    // Failed requests still count as attempted; nothing aborts a batch early
+    assert_eq!(result.attempted, 4);
+    assert_eq!(result.not_attempted, 0);
 
     // Successful transfers would update balances, failed ones would not
     // Balance verification would be done in integration tests
@@ -323,7 +378,7 @@ fn test_batch_transfer_empty_batch() {
 #[test]
 #[should_panic]
 fn test_batch_transfer_unauthorized() {
-    let (env, admin, token, _token_client, client) = setup_test_env();
+    let (env, _admin, token, _token_client, client) = setup_test_env();
 
     let unauthorized = Address::generate(&env);
     let recipient = Address::generate(&env);
@@ -352,7 +407,11 @@ fn test_batch_transfer_large_batch() {
     for _i in 0..50 {
         let recipient = Address::generate(&env);
         recipients.push_back(recipient.clone());
-        transfers.push_back(create_transfer_request(&env, recipient, amount_per_recipient));
+        transfers.push_back(create_transfer_request(
+            &env,
+            recipient,
+            amount_per_recipient,
+        ));
         // 0.1 XLM each
     }
 
@@ -500,6 +559,30 @@ fn test_batch_burn_partial_failures() {
     assert_eq!(result.total_burned, 10_000_000);
 }
 
+#[test]
+fn test_batch_burn_duplicate_owner_exceeding_balance() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+
+    let owner = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &15_000_000);
+
+    let mut burns: Vec<BurnRequest> = Vec::new(&env);
+    burns.push_back(create_burn_request(&env, owner.clone(), 10_000_000));
+    burns.push_back(create_burn_request(&env, owner.clone(), 10_000_000));
+    burns.push_back(create_burn_request(&env, owner.clone(), 5_000_000));
+
+    let result = client.batch_burn(&admin, &token, &burns);
+
+    assert_eq!(result.successful, 2);
+    assert_eq!(result.failed, 1);
+    assert_eq!(result.total_burned, 15_000_000);
+    match result.results.get(1).unwrap() {
+        crate::BurnResult::Failure(_, _, error_code) => assert_eq!(error_code, 2),
+        _ => panic!("expected the second burn to fail"),
+    }
+    assert_eq!(token_client.balance(&owner), 0);
+}
+
 #[test]
 fn test_batch_burn_events_emitted() {
     let (env, admin, token, _token_client, client) = setup_test_env();
@@ -542,3 +625,2850 @@ fn test_batch_burn_unauthorized() {
     let unauthorized = Address::generate(&env);
     client.batch_burn(&unauthorized, &token, &burns);
 }
+
+#[test]
+fn test_batch_transfer_chunked_aggregates_results() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+
+    let token_admin_client = token::StellarAssetClient::new(&env, &token);
+    let amount: i128 = 1_000_000;
+    token_admin_client.mint(&admin, &(amount * 10));
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    for _i in 0..10 {
+        transfers.push_back(create_transfer_request(
+            &env,
+            Address::generate(&env),
+            amount,
+        ));
+    }
+
+    let result = client.batch_transfer_chunked(&admin, &token, &transfers, &4);
+
+    assert_eq!(result.total_requests, 10);
+    assert_eq!(result.successful, 10);
+    assert_eq!(result.failed, 0);
+    assert_eq!(result.total_transferred, amount * 10);
+    assert_eq!(result.results.len(), 10);
+    assert_eq!(
+        result.attempted + result.not_attempted,
+        result.total_requests
+    );
+
+    // One started/completed pair per chunk: 4 + 4 + 2
+    let contract_events = env
+        .events()
+        .all()
+        .iter()
+        .filter(|e| e.0 == client.address)
+        .collect::<std::vec::Vec<_>>();
+    let started = contract_events
+        .iter()
+        .filter(|e| {
+            symbol_topic(&env, &e.1, 0) == symbol_short!("batch")
+                && symbol_topic(&env, &e.1, 1) == symbol_short!("started")
+        })
+        .count();
+    let completed = contract_events
+        .iter()
+        .filter(|e| {
+            symbol_topic(&env, &e.1, 0) == symbol_short!("batch")
+                && symbol_topic(&env, &e.1, 1) == symbol_short!("completed")
+        })
+        .count();
+    assert_eq!(started, 3);
+    assert_eq!(completed, 3);
+    assert_eq!(token_client.balance(&admin), 0);
+    assert_eq!(client.get_total_batches(), 3);
+    assert_eq!(client.get_total_transfers_processed(), 10);
+}
+
+#[test]
+#[should_panic]
+fn test_batch_transfer_chunked_zero_chunk_size() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        1_000_000,
+    ));
+
+    client.batch_transfer_chunked(&admin, &token, &transfers, &0);
+}
+
+// Fee Tests
+
+#[test]
+fn test_batch_transfer_charges_fee_to_collector() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+
+    let collector = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    client.set_fee(&admin, &100, &collector); // 1%
+
+    let token_admin_client = token::StellarAssetClient::new(&env, &token);
+    token_admin_client.mint(&admin, &10_100_000);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 10_000_000));
+
+    let result = client.batch_transfer(&admin, &token, &transfers);
+
+    assert_eq!(result.successful, 1);
+    assert_eq!(token_client.balance(&recipient), 10_000_000);
+    assert_eq!(token_client.balance(&collector), 100_000);
+    assert_eq!(token_client.balance(&admin), 0);
+    assert_eq!(client.get_total_fees_collected(), 100_000);
+}
+
+#[test]
+fn test_batch_transfer_self_fee_is_not_recorded() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+
+    let recipient = Address::generate(&env);
+    client.set_fee(&admin, &100, &admin);
+
+    let token_admin_client = token::StellarAssetClient::new(&env, &token);
+    token_admin_client.mint(&admin, &10_000_000);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 10_000_000));
+
+    let result = client.batch_transfer(&admin, &token, &transfers);
+
+    // The whole balance can be sent since no fee is reserved for a self-fee
+    assert_eq!(result.successful, 1);
+    assert_eq!(token_client.balance(&recipient), 10_000_000);
+    assert_eq!(token_client.balance(&admin), 0);
+    assert_eq!(client.get_total_fees_collected(), 0);
+}
+
+#[test]
+#[should_panic]
+fn test_set_fee_above_max_rejected() {
+    let (env, admin, _token, _token_client, client) = setup_test_env();
+
+    client.set_fee(&admin, &10_001, &Address::generate(&env));
+}
+
+#[test]
+#[should_panic]
+fn test_apply_fee_collector_before_delay_rejected() {
+    let (env, admin, _token, _token_client, client) = setup_test_env();
+
+    client.set_fee(&admin, &100, &Address::generate(&env));
+    client.propose_fee_collector(&admin, &Address::generate(&env));
+
+    env.ledger().with_mut(|li| {
+        li.sequence_number += FEE_COLLECTOR_DELAY_LEDGERS - 1;
+    });
+    client.apply_fee_collector(&admin);
+}
+
+#[test]
+fn test_apply_fee_collector_after_delay() {
+    let (env, admin, _token, _token_client, client) = setup_test_env();
+
+    let old_collector = Address::generate(&env);
+    let new_collector = Address::generate(&env);
+    client.set_fee(&admin, &100, &old_collector);
+    client.propose_fee_collector(&admin, &new_collector);
+
+    let pending = client.get_pending_fee_collector().unwrap();
+    assert_eq!(pending.collector, new_collector);
+    assert_eq!(pending.unlock_ledger, 12345 + FEE_COLLECTOR_DELAY_LEDGERS);
+    assert_eq!(client.get_fee_collector(), Some(old_collector));
+
+    env.ledger().with_mut(|li| {
+        li.sequence_number += FEE_COLLECTOR_DELAY_LEDGERS;
+    });
+    client.apply_fee_collector(&admin);
+
+    assert_eq!(client.get_fee_collector(), Some(new_collector));
+    assert!(client.get_pending_fee_collector().is_none());
+}
+
+#[test]
+fn test_cancel_fee_collector_keeps_old_collector() {
+    let (env, admin, _token, _token_client, client) = setup_test_env();
+
+    let old_collector = Address::generate(&env);
+    client.set_fee(&admin, &100, &old_collector);
+    client.propose_fee_collector(&admin, &Address::generate(&env));
+    client.cancel_fee_collector(&admin);
+
+    env.ledger().with_mut(|li| {
+        li.sequence_number += FEE_COLLECTOR_DELAY_LEDGERS;
+    });
+
+    assert!(client.get_pending_fee_collector().is_none());
+    assert_eq!(client.get_fee_collector(), Some(old_collector));
+    assert!(client.try_apply_fee_collector(&admin).is_err());
+}
+
+#[test]
+#[should_panic]
+fn test_set_fee_cannot_bypass_timelock() {
+    let (env, admin, _token, _token_client, client) = setup_test_env();
+
+    client.set_fee(&admin, &100, &Address::generate(&env));
+    client.set_fee(&admin, &100, &Address::generate(&env));
+}
+
+// Event Mode Tests
+
+/// Decodes a `batch_log` blob into `(succeeded, error_code)` per item.
+fn decode_batch_log(log: &Bytes) -> std::vec::Vec<(bool, u32)> {
+    let read_u32 = |at: u32| {
+        let mut buf = [0u8; 4];
+        log.slice(at..at + 4).copy_into_slice(&mut buf);
+        u32::from_be_bytes(buf)
+    };
+
+    let count = read_u32(0);
+    let bitset_len = count.div_ceil(8);
+    let mut outcomes = std::vec::Vec::new();
+    for i in 0..count {
+        let byte = log.get(4 + i / 8).unwrap();
+        outcomes.push((byte & (1 << (i % 8)) != 0, 0));
+    }
+
+    let mut at = 4 + bitset_len;
+    while at < log.len() {
+        let index = read_u32(at);
+        outcomes[index as usize].1 = read_u32(at + 4);
+        at += 8;
+    }
+    outcomes
+}
+
+#[test]
+fn test_compressed_event_mode_round_trip() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+
+    client.set_event_mode(&admin, &EventMode::Compressed);
+    assert_eq!(client.get_event_mode(), EventMode::Compressed);
+
+    let token_admin_client = token::StellarAssetClient::new(&env, &token);
+    token_admin_client.mint(&admin, &30_000_000);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    for i in 0..10 {
+        let amount: i128 = match i {
+            2 => 0,                 // Invalid amount
+            7 => 1_000_000_000_000, // Insufficient balance
+            _ => 1_000_000,
+        };
+        transfers.push_back(create_transfer_request(
+            &env,
+            Address::generate(&env),
+            amount,
+        ));
+    }
+
+    let result = client.batch_transfer(&admin, &token, &transfers);
+    assert_eq!(result.successful, 8);
+    assert_eq!(result.failed, 2);
+
+    let contract_events = env
+        .events()
+        .all()
+        .iter()
+        .filter(|e| e.0 == client.address)
+        .collect::<std::vec::Vec<_>>();
+    assert!(!contract_events
+        .iter()
+        .any(|e| symbol_topic(&env, &e.1, 0) == symbol_short!("transfer")));
+
+    let log_event = contract_events
+        .iter()
+        .find(|e| symbol_topic(&env, &e.1, 1) == symbol_short!("log"))
+        .expect("batch_log event");
+    let log = Bytes::try_from_val(&env, &log_event.2).unwrap();
+    assert_eq!(log, encode_batch_log(&env, &result.results));
+
+    let decoded = decode_batch_log(&log);
+    assert_eq!(decoded.len(), 10);
+    for (i, outcome) in result.results.iter().enumerate() {
+        match outcome {
+            TransferResult::Success(_, _) => assert_eq!(decoded[i], (true, 0)),
+            TransferResult::Failure(_, _, code) => assert_eq!(decoded[i], (false, code)),
+            TransferResult::PartialDelivery(_, _, _) => assert_eq!(decoded[i], (false, 3)),
+        }
+    }
+    assert_eq!(decoded[2], (false, 1));
+    assert_eq!(decoded[7], (false, 2));
+}
+
+// Token Block Tests
+
+#[test]
+fn test_blocked_token_rejected_by_transfer_and_burn() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+
+    let other_token = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let owner = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000_000);
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &10_000_000);
+    token::StellarAssetClient::new(&env, &other_token).mint(&admin, &10_000_000);
+    token::StellarAssetClient::new(&env, &other_token).mint(&owner, &10_000_000);
+
+    client.block_token(&admin, &token);
+    assert!(client.is_token_blocked(&token));
+    assert!(!client.is_token_blocked(&other_token));
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        1_000_000,
+    ));
+    let mut burns: Vec<BurnRequest> = Vec::new(&env);
+    burns.push_back(create_burn_request(&env, owner.clone(), 1_000_000));
+
+    assert!(client
+        .try_batch_transfer(&admin, &token, &transfers)
+        .is_err());
+    assert!(client
+        .try_batch_transfer_chunked(&admin, &token, &transfers, &1)
+        .is_err());
+    assert!(client.try_batch_burn(&admin, &token, &burns).is_err());
+
+    assert_eq!(
+        client
+            .batch_transfer(&admin, &other_token, &transfers)
+            .successful,
+        1
+    );
+    assert_eq!(
+        client.batch_burn(&admin, &other_token, &burns).successful,
+        1
+    );
+
+    client.unblock_token(&admin, &token);
+    assert_eq!(
+        client.batch_transfer(&admin, &token, &transfers).successful,
+        1
+    );
+    assert_eq!(client.batch_burn(&admin, &token, &burns).successful, 1);
+}
+
+// Idempotency Tests
+
+#[test]
+fn test_batch_transfer_idempotent_executes_once() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+
+    let recipient = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &20_000_000);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 10_000_000));
+    let key = BytesN::from_array(&env, &[1u8; 32]);
+    let unused_key = BytesN::from_array(&env, &[2u8; 32]);
+
+    assert!(!client.is_batch_key_used(&key));
+
+    let first = client.batch_transfer_idempotent(&admin, &token, &transfers, &key);
+    let replay = client.batch_transfer_idempotent(&admin, &token, &transfers, &key);
+
+    assert_eq!(first.successful, 1);
+    assert_eq!(replay.successful, 1);
+    assert_eq!(replay.total_transferred, first.total_transferred);
+    assert_eq!(token_client.balance(&recipient), 10_000_000);
+    assert_eq!(client.get_total_batches(), 1);
+
+    assert!(client.is_batch_key_used(&key));
+    assert!(!client.is_batch_key_used(&unused_key));
+}
+
+#[test]
+fn test_batch_key_had_failures() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+
+    let clean_key = BytesN::from_array(&env, &[1u8; 32]);
+    let failed_key = BytesN::from_array(&env, &[2u8; 32]);
+    let unknown_key = BytesN::from_array(&env, &[3u8; 32]);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        1_000,
+    ));
+    client.batch_transfer_idempotent(&admin, &token, &transfers, &clean_key);
+
+    transfers.push_back(create_transfer_request(&env, Address::generate(&env), 0));
+    client.batch_transfer_idempotent(&admin, &token, &transfers, &failed_key);
+
+    assert_eq!(client.batch_key_had_failures(&unknown_key), None);
+    assert_eq!(client.batch_key_had_failures(&clean_key), Some(false));
+    assert_eq!(client.batch_key_had_failures(&failed_key), Some(true));
+}
+
+// Per-Token Stats Tests
+
+#[test]
+fn test_total_volume_human_uses_registered_decimals() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &125_000_000);
+    client.set_token_decimals(&admin, &token, &7);
+    assert_eq!(client.get_token_decimals(&token), Some(7));
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        100_000_000,
+    ));
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        25_000_000,
+    ));
+    client.batch_transfer(&admin, &token, &transfers);
+
+    assert_eq!(client.get_token_volume(&token), 125_000_000);
+    // 12.5 units truncated to whole units
+    assert_eq!(client.get_total_volume_human(&token), 12);
+}
+
+// Delta Verification Tests
+
+mod fee_on_transfer_token {
+    use soroban_sdk::{contract, contractimpl, Address, Env};
+
+    /// Minimal token that burns 1% of every transfer.
+    #[contract]
+    pub struct FeeOnTransferToken;
+
+    #[contractimpl]
+    impl FeeOnTransferToken {
+        pub fn mint(env: Env, to: Address, amount: i128) {
+            let balance = Self::balance(env.clone(), to.clone());
+            env.storage().persistent().set(&to, &(balance + amount));
+        }
+
+        pub fn balance(env: Env, id: Address) -> i128 {
+            env.storage().persistent().get(&id).unwrap_or(0)
+        }
+
+        pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+            from.require_auth();
+            let from_balance = Self::balance(env.clone(), from.clone());
+            let to_balance = Self::balance(env.clone(), to.clone());
+            env.storage()
+                .persistent()
+                .set(&from, &(from_balance - amount));
+            env.storage()
+                .persistent()
+                .set(&to, &(to_balance + amount - amount / 100));
+        }
+    }
+}
+
+mod bonus_token {
+    use soroban_sdk::{contract, contractimpl, Address, Env};
+
+    /// Minimal token that credits a 1% bonus on every transfer.
+    #[contract]
+    pub struct BonusToken;
+
+    #[contractimpl]
+    impl BonusToken {
+        pub fn mint(env: Env, to: Address, amount: i128) {
+            let balance = Self::balance(env.clone(), to.clone());
+            env.storage().persistent().set(&to, &(balance + amount));
+        }
+
+        pub fn balance(env: Env, id: Address) -> i128 {
+            env.storage().persistent().get(&id).unwrap_or(0)
+        }
+
+        pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+            from.require_auth();
+            let from_balance = Self::balance(env.clone(), from.clone());
+            let to_balance = Self::balance(env.clone(), to.clone());
+            env.storage()
+                .persistent()
+                .set(&from, &(from_balance - amount));
+            env.storage()
+                .persistent()
+                .set(&to, &(to_balance + amount + amount / 100));
+        }
+    }
+}
+
+#[test]
+fn test_verify_deltas_caps_over_credited_delivery() {
+    let (env, admin, _token, _token_client, client) = setup_test_env();
+    let bonus_token = env.register(bonus_token::BonusToken, ());
+    let bonus_token_client = bonus_token::BonusTokenClient::new(&env, &bonus_token);
+    bonus_token_client.mint(&admin, &10_000);
+    client.set_verify_deltas(&admin, &true);
+
+    let recipient = Address::generate(&env);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 1_000));
+
+    let result = client.batch_transfer(&admin, &bonus_token, &transfers);
+
+    assert_eq!(result.successful, 1);
+    assert_eq!(result.total_transferred, 1_000);
+    match result.results.get(0).unwrap() {
+        TransferResult::Success(_, amount) => assert_eq!(amount, 1_000),
+        _ => panic!("Expected success"),
+    }
+    assert_eq!(bonus_token_client.balance(&recipient), 1_010);
+}
+
+#[test]
+fn test_verify_deltas_records_net_received() {
+    let (env, admin, _token, _token_client, client) = setup_test_env();
+    let fee_token = env.register(fee_on_transfer_token::FeeOnTransferToken, ());
+    let fee_token_client = fee_on_transfer_token::FeeOnTransferTokenClient::new(&env, &fee_token);
+    fee_token_client.mint(&admin, &10_000);
+
+    assert!(!client.get_verify_deltas());
+    client.set_verify_deltas(&admin, &true);
+    assert!(client.get_verify_deltas());
+
+    let recipient = Address::generate(&env);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 1_000));
+
+    let result = client.batch_transfer(&admin, &fee_token, &transfers);
+
+    assert_eq!(result.successful, 1);
+    assert_eq!(result.total_transferred, 990);
+    match result.results.get(0).unwrap() {
+        TransferResult::PartialDelivery(recv, requested, delivered) => {
+            assert_eq!(recv, recipient);
+            assert_eq!(requested, 1_000);
+            assert_eq!(delivered, 990);
+        }
+        _ => panic!("Expected partial delivery"),
+    }
+    assert_eq!(client.get_token_volume(&fee_token), 990);
+    assert_eq!(fee_token_client.balance(&recipient), 990);
+}
+
+#[test]
+fn test_verify_deltas_reports_success_for_standard_token() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+    client.set_verify_deltas(&admin, &true);
+
+    let recipient = Address::generate(&env);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 1_000));
+
+    let result = client.batch_transfer(&admin, &token, &transfers);
+
+    assert_eq!(result.total_transferred, 1_000);
+    match result.results.get(0).unwrap() {
+        TransferResult::Success(recv, amount) => {
+            assert_eq!(recv, recipient);
+            assert_eq!(amount, 1_000);
+        }
+        _ => panic!("Expected success"),
+    }
+}
+
+// Pause Tests
+
+#[test]
+fn test_pause_emits_event_with_admin_and_ledger() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+
+    client.pause(&admin);
+    let pause_events: std::vec::Vec<_> = env
+        .events()
+        .all()
+        .iter()
+        .filter(|e| symbol_topic(&env, &e.1, 1) == symbol_short!("paused"))
+        .collect();
+    assert_eq!(pause_events.len(), 1);
+    let (actor, ledger) = <(Address, u32)>::try_from_val(&env, &pause_events[0].2).unwrap();
+    assert_eq!(actor, admin);
+    assert_eq!(ledger, 12345);
+    assert!(client.is_paused());
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        1_000_000,
+    ));
+    assert!(client
+        .try_batch_transfer(&admin, &token, &transfers)
+        .is_err());
+
+    client.unpause(&admin);
+    let unpause_events = env
+        .events()
+        .all()
+        .iter()
+        .filter(|e| symbol_topic(&env, &e.1, 1) == symbol_short!("unpaused"))
+        .count();
+    assert_eq!(unpause_events, 1);
+    assert!(!client.is_paused());
+}
+
+#[test]
+fn test_redundant_pause_emits_no_event() {
+    let (env, admin, _token, _token_client, client) = setup_test_env();
+
+    client.pause(&admin);
+    client.pause(&admin);
+
+    let pause_events = env
+        .events()
+        .all()
+        .iter()
+        .filter(|e| symbol_topic(&env, &e.1, 1) == symbol_short!("paused"))
+        .count();
+    assert_eq!(pause_events, 0);
+    assert!(client.is_paused());
+}
+
+// Governance Tests
+
+#[test]
+fn test_governance_snapshot_reflects_setters() {
+    let (env, admin, _token, _token_client, client) = setup_test_env();
+
+    let governance = client.get_governance();
+    assert_eq!(governance.owner, admin);
+    assert_eq!(governance.pending_admin, None);
+    assert_eq!(governance.operators.len(), 0);
+    assert_eq!(governance.fee_collector, None);
+    assert_eq!(governance.price_oracle, None);
+    assert_eq!(governance.transfer_contract, None);
+
+    let collector = Address::generate(&env);
+    let new_admin = Address::generate(&env);
+    client.set_fee(&admin, &100, &collector);
+    client.set_admin(&admin, &new_admin);
+
+    let governance = client.get_governance();
+    assert_eq!(governance.owner, new_admin);
+    assert_eq!(governance.fee_collector, Some(collector));
+}
+
+#[test]
+fn test_cancel_admin_transfer_blocks_acceptance() {
+    let (env, admin, _token, _token_client, client) = setup_test_env();
+    let candidate = Address::generate(&env);
+
+    client.propose_admin(&admin, &candidate);
+    assert_eq!(
+        client.get_governance().pending_admin,
+        Some(candidate.clone())
+    );
+
+    client.cancel_admin_transfer(&admin);
+    assert_eq!(client.get_pending_admin(), None);
+    assert!(client.try_accept_admin(&candidate).is_err());
+    assert_eq!(client.get_admin(), admin);
+
+    client.propose_admin(&admin, &candidate);
+    client.accept_admin(&candidate);
+    assert_eq!(client.get_admin(), candidate);
+    assert_eq!(client.get_pending_admin(), None);
+}
+
+// Transfer From Tests
+
+#[test]
+fn test_batch_transfer_from_flags_insufficient_allowance() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    let asset_client = token::StellarAssetClient::new(&env, &token);
+
+    let recipient = Address::generate(&env);
+    let funded = Address::generate(&env);
+    let underapproved = Address::generate(&env);
+    asset_client.mint(&funded, &5_000);
+    asset_client.mint(&underapproved, &5_000);
+    token_client.approve(&funded, &client.address, &1_000, &20_000);
+    token_client.approve(&underapproved, &client.address, &500, &20_000);
+
+    let mut requests: Vec<TransferFromRequest> = Vec::new(&env);
+    requests.push_back(TransferFromRequest {
+        from: funded.clone(),
+        amount: 1_000,
+    });
+    requests.push_back(TransferFromRequest {
+        from: underapproved.clone(),
+        amount: 1_000,
+    });
+
+    let result = client.batch_transfer_from(&admin, &token, &recipient, &requests);
+
+    assert_eq!(result.successful, 1);
+    assert_eq!(result.failed, 1);
+    assert_eq!(result.total_transferred, 1_000);
+    match result.results.get(1).unwrap() {
+        TransferResult::Failure(from, amount, error_code) => {
+            assert_eq!(from, underapproved);
+            assert_eq!(amount, 1_000);
+            assert_eq!(error_code, 4); // Insufficient allowance
+        }
+        _ => panic!("Expected insufficient allowance failure"),
+    }
+    assert_eq!(token_client.balance(&recipient), 1_000);
+    assert_eq!(token_client.balance(&underapproved), 5_000);
+}
+
+// Sender Allowlist Tests
+
+#[test]
+fn test_sender_allowlist_rejects_unlisted_sender() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        1_000,
+    ));
+
+    client.set_sender_allowlist_enabled(&admin, &true);
+    assert!(!client.is_sender_allowed(&admin));
+    assert!(client
+        .try_batch_transfer(&admin, &token, &transfers)
+        .is_err());
+
+    client.add_allowed_sender(&admin, &admin);
+    assert!(client.is_sender_allowed(&admin));
+    assert_eq!(
+        client.batch_transfer(&admin, &token, &transfers).successful,
+        1
+    );
+
+    client.remove_allowed_sender(&admin, &admin);
+    assert!(client
+        .try_batch_transfer(&admin, &token, &transfers)
+        .is_err());
+}
+
+// Liveness Tests
+
+#[test]
+fn test_last_batch_ledger_and_timestamp() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+
+    assert_eq!(client.get_last_batch_ledger(), 0);
+    assert_eq!(client.get_last_batch_timestamp(), 0);
+
+    env.ledger().with_mut(|li| {
+        li.sequence_number = 20_000;
+        li.timestamp = 1_700_000_000;
+    });
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        1_000,
+    ));
+    client.batch_transfer(&admin, &token, &transfers);
+
+    assert_eq!(client.get_last_batch_ledger(), 20_000);
+    assert_eq!(client.get_last_batch_timestamp(), 1_700_000_000);
+}
+
+// Error Description Tests
+
+#[test]
+fn test_error_description() {
+    let (env, _admin, _token, _token_client, client) = setup_test_env();
+
+    assert_eq!(
+        client.error_description(&0),
+        String::from_str(&env, "invalid address")
+    );
+    assert_eq!(
+        client.error_description(&2),
+        String::from_str(&env, "insufficient balance")
+    );
+    assert_eq!(
+        client.error_description(&4),
+        String::from_str(&env, "insufficient allowance")
+    );
+    assert_eq!(
+        client.error_description(&12),
+        String::from_str(&env, "wallet limit exceeded")
+    );
+    assert_eq!(
+        client.error_description(&999),
+        String::from_str(&env, "unknown")
+    );
+}
+
+// Expected Decimals Tests
+
+#[test]
+#[should_panic(expected = "Unexpected token decimals")]
+fn test_expected_decimals_rejects_mismatched_token() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+
+    // Stellar asset contracts report 7 decimals
+    client.set_expected_decimals(&admin, &Some(6));
+    assert_eq!(client.get_expected_decimals(), Some(6));
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        1_000,
+    ));
+    client.batch_transfer(&admin, &token, &transfers);
+}
+
+#[test]
+fn test_expected_decimals_accepts_matching_token() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+    client.set_expected_decimals(&admin, &Some(7));
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        1_000,
+    ));
+    assert_eq!(
+        client.batch_transfer(&admin, &token, &transfers).successful,
+        1
+    );
+
+    client.set_expected_decimals(&admin, &None);
+    assert_eq!(client.get_expected_decimals(), None);
+}
+
+// Operator Tests
+
+#[test]
+fn test_operator_list_tracks_additions_and_removals() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    let operator1 = Address::generate(&env);
+    let operator2 = Address::generate(&env);
+
+    client.add_operator(&admin, &operator1);
+    client.add_operator(&admin, &operator2);
+    client.add_operator(&admin, &operator1);
+    assert_eq!(
+        client.get_operators(),
+        Vec::from_array(&env, [operator1.clone(), operator2.clone()])
+    );
+    assert_eq!(client.get_governance().operators.len(), 2);
+
+    // Operators may submit batches from their own balance
+    token::StellarAssetClient::new(&env, &token).mint(&operator1, &10_000);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        1_000,
+    ));
+    assert_eq!(
+        client
+            .batch_transfer(&operator1, &token, &transfers)
+            .successful,
+        1
+    );
+
+    client.remove_operator(&admin, &operator1);
+    assert_eq!(client.get_operators(), Vec::from_array(&env, [operator2]));
+    assert!(!client.is_operator(&operator1));
+    assert!(client
+        .try_batch_transfer(&operator1, &token, &transfers)
+        .is_err());
+}
+
+// Scheduled Batch Tests
+
+#[test]
+fn test_submitter_can_cancel_own_scheduled_batch() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    let submitter = Address::generate(&env);
+    let other_operator = Address::generate(&env);
+    client.add_operator(&admin, &submitter);
+    client.add_operator(&admin, &other_operator);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        1_000,
+    ));
+    let schedule_id = client.schedule_batch(&submitter, &token, &transfers, &12_400);
+    assert_eq!(
+        client.get_scheduled(&schedule_id).unwrap().submitter,
+        submitter
+    );
+
+    assert!(client
+        .try_cancel_scheduled(&other_operator, &schedule_id)
+        .is_err());
+    assert!(client.get_scheduled(&schedule_id).is_some());
+
+    client.cancel_scheduled(&submitter, &schedule_id);
+    assert!(client.get_scheduled(&schedule_id).is_none());
+}
+
+#[test]
+fn test_execute_scheduled_batch_after_ledger() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+
+    let recipient = Address::generate(&env);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 1_000));
+    let schedule_id = client.schedule_batch(&admin, &token, &transfers, &12_400);
+
+    assert!(client.try_execute_scheduled(&schedule_id).is_err());
+
+    env.ledger().with_mut(|li| li.sequence_number = 12_400);
+    let result = client.execute_scheduled(&schedule_id);
+    assert_eq!(result.successful, 1);
+    assert_eq!(token_client.balance(&recipient), 1_000);
+    assert!(client.get_scheduled(&schedule_id).is_none());
+}
+
+// Duplicate Merging Tests
+
+#[test]
+fn test_merge_duplicates_reports_merged_count() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+    client.set_merge_duplicates(&admin, &true);
+
+    let recipient_a = Address::generate(&env);
+    let recipient_b = Address::generate(&env);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient_a.clone(), 100));
+    transfers.push_back(create_transfer_request(&env, recipient_b.clone(), 200));
+    transfers.push_back(create_transfer_request(&env, recipient_a.clone(), 300));
+    transfers.push_back(create_transfer_request(&env, recipient_b.clone(), 50));
+    transfers.push_back(create_transfer_request(&env, recipient_a.clone(), 10));
+
+    let result = client.batch_transfer(&admin, &token, &transfers);
+
+    assert_eq!(result.total_requests, 5);
+    assert_eq!(result.merged_count, 3);
+    assert_eq!(result.successful, 2);
+    assert_eq!(result.results.len(), 2);
+    assert_eq!(
+        result.attempted + result.not_attempted + result.merged_count,
+        result.total_requests
+    );
+    assert_eq!(result.total_transferred, 660);
+    assert_eq!(token_client.balance(&recipient_a), 410);
+    assert_eq!(token_client.balance(&recipient_b), 250);
+}
+
+// Rate Limit Tests
+
+#[test]
+fn test_rate_limit_remaining_and_window_reset() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &100_000);
+
+    assert_eq!(client.get_rate_limit_remaining(), i128::MAX);
+    client.set_rate_limit(&admin, &10_000, &100);
+    assert_eq!(client.get_rate_limit_remaining(), 10_000);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        6_000,
+    ));
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        5_000,
+    ));
+    let result = client.batch_transfer(&admin, &token, &transfers);
+
+    assert_eq!(result.successful, 1);
+    match result.results.get(1).unwrap() {
+        TransferResult::Failure(_, _, error_code) => assert_eq!(error_code, 6),
+        _ => panic!("Expected rate-limited failure"),
+    }
+    assert_eq!(client.get_rate_limit_remaining(), 4_000);
+
+    env.ledger().with_mut(|li| li.sequence_number += 99);
+    assert_eq!(client.get_rate_limit_remaining(), 4_000);
+    env.ledger().with_mut(|li| li.sequence_number += 1);
+    assert_eq!(client.get_rate_limit_remaining(), 10_000);
+}
+
+// Fee Rounding Tests
+
+#[test]
+fn test_fee_never_rounds_up_against_sender() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &1_000_000_000);
+    let collector = Address::generate(&env);
+    let fee_bps: i128 = 333;
+    client.set_fee(&admin, &(fee_bps as u32), &collector);
+
+    let mut batch_total: i128 = 0;
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    for amount in [1i128, 7, 29, 30, 31, 99, 301, 3_003, 10_001, 123_457] {
+        let collected_before = token_client.balance(&collector);
+        let mut single: Vec<TransferRequest> = Vec::new(&env);
+        single.push_back(create_transfer_request(
+            &env,
+            Address::generate(&env),
+            amount,
+        ));
+        client.batch_transfer(&admin, &token, &single);
+
+        let charged = token_client.balance(&collector) - collected_before;
+        assert_eq!(charged, amount * fee_bps / 10_000);
+
+        batch_total += amount;
+        transfers.push_back(create_transfer_request(
+            &env,
+            Address::generate(&env),
+            amount,
+        ));
+    }
+
+    // Per-item rounding across a batch never exceeds the fee on the total
+    let collected_before = token_client.balance(&collector);
+    client.batch_transfer(&admin, &token, &transfers);
+    let charged = token_client.balance(&collector) - collected_before;
+    assert!(charged <= batch_total * fee_bps / 10_000);
+}
+
+#[test]
+fn test_fee_overflow_fails_only_affected_transfer() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+    client.set_fee(&admin, &100, &Address::generate(&env));
+
+    let recipient = Address::generate(&env);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        i128::MAX / 2,
+    ));
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 1_000));
+    let result = client.batch_transfer(&admin, &token, &transfers);
+
+    assert_eq!(result.successful, 1);
+    match result.results.get(0).unwrap() {
+        TransferResult::Failure(_, _, error_code) => assert_eq!(error_code, 10),
+        _ => panic!("Expected fee overflow failure"),
+    }
+    assert_eq!(token_client.balance(&recipient), 1_000);
+}
+
+// Claim Tests
+
+#[test]
+fn test_total_locked_tracks_outstanding_claims() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+
+    let recipient1 = Address::generate(&env);
+    let recipient2 = Address::generate(&env);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient1.clone(), 1_000));
+    transfers.push_back(create_transfer_request(&env, recipient2.clone(), 2_500));
+    transfers.push_back(create_transfer_request(&env, recipient1.clone(), 500));
+
+    let claim_ids = client.batch_create_claims(&admin, &token, &transfers, &13_000);
+
+    assert_eq!(claim_ids.len(), 3);
+    assert_eq!(client.get_total_locked(&token), 4_000);
+    assert_eq!(token_client.balance(&client.address), 4_000);
+
+    assert_eq!(client.claim(&recipient2, &claim_ids.get(1).unwrap()), 2_500);
+    assert_eq!(client.get_total_locked(&token), 1_500);
+    assert_eq!(token_client.balance(&recipient2), 2_500);
+    assert!(client.get_claim(&claim_ids.get(1).unwrap()).is_none());
+
+    // Only the recipient may claim
+    assert!(client
+        .try_claim(&recipient2, &claim_ids.get(0).unwrap())
+        .is_err());
+}
+
+#[test]
+fn test_expired_claim_is_reclaimed_by_sender() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+
+    let recipient = Address::generate(&env);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 1_000));
+    let claim_id = client
+        .batch_create_claims(&admin, &token, &transfers, &12_400)
+        .get(0)
+        .unwrap();
+
+    assert!(client.try_reclaim_expired(&admin, &claim_id).is_err());
+
+    env.ledger().with_mut(|li| li.sequence_number = 12_400);
+    assert!(client.try_claim(&recipient, &claim_id).is_err());
+    assert_eq!(client.reclaim_expired(&admin, &claim_id), 1_000);
+    assert_eq!(client.get_total_locked(&token), 0);
+    assert_eq!(token_client.balance(&admin), 10_000);
+}
+
+#[test]
+fn test_get_claimable_sums_unexpired_claims() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+
+    let recipient = Address::generate(&env);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 1_000));
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 500));
+    client.batch_create_claims(&admin, &token, &transfers, &13_000);
+
+    let mut short_lived: Vec<TransferRequest> = Vec::new(&env);
+    short_lived.push_back(create_transfer_request(&env, recipient.clone(), 250));
+    short_lived.push_back(create_transfer_request(&env, Address::generate(&env), 700));
+    client.batch_create_claims(&admin, &token, &short_lived, &12_400);
+
+    assert_eq!(client.get_claimable(&recipient, &token), 1_750);
+
+    // The short-lived claim expires and drops out of the total
+    env.ledger().with_mut(|li| li.sequence_number = 12_400);
+    assert_eq!(client.get_claimable(&recipient, &token), 1_500);
+
+    let claim_ids = client.batch_create_claims(&admin, &token, &transfers, &13_000);
+    client.claim(&recipient, &claim_ids.get(0).unwrap());
+    assert_eq!(client.get_claimable(&recipient, &token), 2_000);
+    assert_eq!(
+        client.get_claimable(&recipient, &Address::generate(&env)),
+        0
+    );
+}
+
+#[test]
+fn test_claim_partial_drains_claim_in_steps() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+
+    let recipient = Address::generate(&env);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 1_000));
+    let claim_id = client
+        .batch_create_claims(&admin, &token, &transfers, &13_000)
+        .get(0)
+        .unwrap();
+
+    let first = client.claim_partial(&recipient, &claim_id, &400);
+    assert_eq!(client.get_claim(&claim_id).unwrap().amount, 600);
+    assert_eq!(client.get_total_locked(&token), 600);
+
+    assert!(client
+        .try_claim_partial(&recipient, &claim_id, &601)
+        .is_err());
+
+    let second = client.claim_partial(&recipient, &claim_id, &600);
+    assert_eq!(first + second, 1_000);
+    assert_eq!(token_client.balance(&recipient), 1_000);
+    assert!(client.get_claim(&claim_id).is_none());
+    assert_eq!(client.get_total_locked(&token), 0);
+}
+
+// Recipient Cap Tests
+
+#[test]
+fn test_recipient_cap_reached_across_batches() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &100_000);
+
+    let recipient = Address::generate(&env);
+    client.set_recipient_cap(&admin, &recipient, &5_000);
+    assert_eq!(client.get_recipient_cap(&recipient), 5_000);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 3_000));
+    let result = client.batch_transfer(&admin, &token, &transfers);
+    assert_eq!(result.successful, 1);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 2_000));
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 1));
+    let result = client.batch_transfer(&admin, &token, &transfers);
+
+    assert_eq!(result.successful, 1);
+    match result.results.get(1).unwrap() {
+        TransferResult::Failure(_, _, error_code) => {
+            assert_eq!(error_code, 7)
+        }
+        _ => panic!("Expected recipient cap failure"),
+    }
+    assert_eq!(client.get_recipient_received(&recipient), 5_000);
+    assert_eq!(token_client.balance(&recipient), 5_000);
+
+    // A cap of 0 lifts the limit
+    client.set_recipient_cap(&admin, &recipient, &0);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 1));
+    assert_eq!(
+        client.batch_transfer(&admin, &token, &transfers).successful,
+        1
+    );
+}
+
+// Contract Info Tests
+
+#[test]
+fn test_get_contract_info() {
+    let (env, _admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&client.address, &4_200);
+
+    assert_eq!(
+        client.get_contract_info(&token),
+        (client.address.clone(), 4_200)
+    );
+}
+
+// Token Stats Tests
+
+#[test]
+fn test_get_token_stats_bundles_per_token_totals() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    let token_admin = token::StellarAssetClient::new(&env, &token);
+    token_admin.mint(&admin, &100_000);
+    client.set_fee(&admin, &100, &Address::generate(&env));
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        10_000,
+    ));
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        5_000,
+    ));
+    transfers.push_back(create_transfer_request(&env, Address::generate(&env), 0));
+    client.batch_transfer(&admin, &token, &transfers);
+
+    let owner = Address::generate(&env);
+    token_admin.mint(&owner, &3_000);
+    let mut burns: Vec<BurnRequest> = Vec::new(&env);
+    burns.push_back(create_burn_request(&env, owner.clone(), 2_000));
+    client.batch_burn(&admin, &token, &burns);
+
+    let mut claims: Vec<TransferRequest> = Vec::new(&env);
+    claims.push_back(create_transfer_request(&env, Address::generate(&env), 700));
+    client.batch_create_claims(&admin, &token, &claims, &13_000);
+
+    let stats = client.get_token_stats(&token);
+    assert_eq!(stats.volume, 15_000);
+    assert_eq!(stats.transfers, 2);
+    assert_eq!(stats.burns, 2_000);
+    assert_eq!(stats.mints, 0);
+    assert_eq!(stats.fees_accrued, 150);
+    assert_eq!(stats.custody_locked, 700);
+
+    let other = client.get_token_stats(&Address::generate(&env));
+    assert_eq!(other.volume, 0);
+    assert_eq!(other.transfers, 0);
+}
+
+// Batch Record Tests
+
+#[test]
+fn test_get_batches_by_ids_aligns_with_input() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &100_000);
+
+    for amount in [1_000, 2_000] {
+        let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+        transfers.push_back(create_transfer_request(
+            &env,
+            Address::generate(&env),
+            amount,
+        ));
+        client.batch_transfer(&admin, &token, &transfers);
+    }
+
+    let ids = Vec::from_array(&env, [2u64, 7, 1, 0]);
+    let records = client.get_batches_by_ids(&ids);
+
+    assert_eq!(records.len(), 4);
+    let second = records.get(0).unwrap().unwrap();
+    assert_eq!(second.batch_id, 2);
+    assert_eq!(second.total_transferred, 2_000);
+    assert!(records.get(1).unwrap().is_none());
+    assert_eq!(records.get(2).unwrap().unwrap().total_transferred, 1_000);
+    assert!(records.get(3).unwrap().is_none());
+    assert_eq!(client.get_batch(&1), records.get(2).unwrap());
+}
+
+// Sender Suspension Tests
+
+#[test]
+#[should_panic(expected = "Sender suspended")]
+fn test_suspended_sender_is_blocked() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+    client.suspend_sender(&admin, &admin);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, Address::generate(&env), 100));
+    client.batch_transfer(&admin, &token, &transfers);
+}
+
+#[test]
+fn test_unsuspend_restores_sender_and_others_unaffected() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    let token_admin = token::StellarAssetClient::new(&env, &token);
+    let operator = Address::generate(&env);
+    token_admin.mint(&admin, &10_000);
+    token_admin.mint(&operator, &10_000);
+    client.add_operator(&admin, &operator);
+
+    client.suspend_sender(&admin, &admin);
+    assert!(client.is_sender_suspended(&admin));
+    assert!(!client.is_sender_suspended(&operator));
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, Address::generate(&env), 100));
+    assert!(client
+        .try_batch_transfer(&admin, &token, &transfers)
+        .is_err());
+    assert_eq!(
+        client
+            .batch_transfer(&operator, &token, &transfers)
+            .successful,
+        1
+    );
+
+    client.unsuspend_sender(&admin, &admin);
+    assert!(!client.is_sender_suspended(&admin));
+    assert_eq!(
+        client.batch_transfer(&admin, &token, &transfers).successful,
+        1
+    );
+}
+
+#[test]
+fn test_recent_success_rate() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &100_000);
+    assert_eq!(client.get_recent_success_rate(&5), 0);
+
+    // Batch 1: 1 of 2 succeeds; batch 2: 3 of 3; batch 3: 1 of 4
+    for (valid, invalid) in [(1, 1), (3, 0), (1, 3)] {
+        let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+        for _ in 0..valid {
+            transfers.push_back(create_transfer_request(&env, Address::generate(&env), 100));
+        }
+        for _ in 0..invalid {
+            transfers.push_back(create_transfer_request(&env, Address::generate(&env), 0));
+        }
+        client.batch_transfer(&admin, &token, &transfers);
+    }
+
+    assert_eq!(client.get_recent_success_rate(&1), 25);
+    assert_eq!(client.get_recent_success_rate(&2), 57);
+    assert_eq!(client.get_recent_success_rate(&3), 55);
+    assert_eq!(client.get_recent_success_rate(&100), 55);
+    assert!(client.try_get_recent_success_rate(&101).is_err());
+}
+
+// Balance Percentage Tests
+
+#[test]
+fn test_batch_transfer_balance_pct_distributes_whole_balance() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_001);
+
+    let recipient1 = Address::generate(&env);
+    let recipient2 = Address::generate(&env);
+    let recipient3 = Address::generate(&env);
+    let shares = Vec::from_array(
+        &env,
+        [
+            (recipient1.clone(), 5_000u32),
+            (recipient2.clone(), 3_000u32),
+            (recipient3.clone(), 2_000u32),
+        ],
+    );
+    let result = client.batch_transfer_balance_pct(&admin, &token, &shares, &None);
+
+    assert_eq!(result.successful, 3);
+    assert_eq!(result.total_transferred, 10_001);
+    // 5000 + 3000 + 2000 floors to 10_000; the 1 unit of dust goes first
+    assert_eq!(token_client.balance(&recipient1), 5_001);
+    assert_eq!(token_client.balance(&recipient2), 3_000);
+    assert_eq!(token_client.balance(&recipient3), 2_000);
+    assert_eq!(token_client.balance(&admin), 0);
+}
+
+#[test]
+fn test_batch_transfer_balance_pct_rejects_over_full_share() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+
+    let shares = Vec::from_array(
+        &env,
+        [
+            (Address::generate(&env), 6_000u32),
+            (Address::generate(&env), 4_001u32),
+        ],
+    );
+    assert!(client
+        .try_batch_transfer_balance_pct(&admin, &token, &shares, &None)
+        .is_err());
+}
+
+// Recipient Readiness Tests
+
+#[test]
+fn test_deauthorized_recipient_reports_not_ready() {
+    let (env, admin, _token, _token_client, client) = setup_test_env();
+    // Deauthorizing balances needs an issuer with the revocable flag
+    let stellar_asset = env.register_stellar_asset_contract_v2(Address::generate(&env));
+    stellar_asset.issuer().set_flag(IssuerFlags::RevocableFlag);
+    let token = stellar_asset.address();
+    let token_client = token::Client::new(&env, &token);
+    let token_admin = token::StellarAssetClient::new(&env, &token);
+    token_admin.mint(&admin, &10_000);
+
+    let blocked = Address::generate(&env);
+    let ready = Address::generate(&env);
+    // The SAC needs a balance entry before it can be deauthorized
+    token_admin.mint(&blocked, &1);
+    token_admin.set_authorized(&blocked, &false);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, blocked.clone(), 1_000));
+    transfers.push_back(create_transfer_request(&env, ready.clone(), 2_000));
+    let result = client.batch_transfer(&admin, &token, &transfers);
+
+    assert_eq!(result.successful, 1);
+    assert_eq!(result.failed, 1);
+    match result.results.get(0).unwrap() {
+        TransferResult::Failure(_, _, error_code) => assert_eq!(error_code, 8),
+        _ => panic!("Expected recipient not ready failure"),
+    }
+    assert_eq!(token_client.balance(&ready), 2_000);
+    assert_eq!(token_client.balance(&admin), 8_000);
+}
+
+#[test]
+fn test_deauthorized_sender_is_not_blamed_on_recipient() {
+    let (env, admin, _token, _token_client, client) = setup_test_env();
+    let stellar_asset = env.register_stellar_asset_contract_v2(Address::generate(&env));
+    stellar_asset.issuer().set_flag(IssuerFlags::RevocableFlag);
+    let token = stellar_asset.address();
+    let token_client = token::Client::new(&env, &token);
+    let token_admin = token::StellarAssetClient::new(&env, &token);
+    token_admin.mint(&admin, &10_000);
+    // The sender keeps its balance but can no longer send it
+    token_admin.set_authorized(&admin, &false);
+
+    let recipient = Address::generate(&env);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 1_000));
+    let result = client.batch_transfer(&admin, &token, &transfers);
+
+    assert_eq!(result.failed, 1);
+    match result.results.get(0).unwrap() {
+        TransferResult::Failure(_, _, error_code) => assert_eq!(error_code, 13),
+        _ => panic!("Expected transfer rejected failure"),
+    }
+    assert_eq!(
+        client.error_description(&13),
+        String::from_str(&env, "transfer rejected")
+    );
+    assert_eq!(token_client.balance(&admin), 10_000);
+}
+
+#[test]
+fn test_trailing_zero_amounts_are_trimmed_from_counts() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+    client.set_trim_trailing_zeros(&admin, &true);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        1_000,
+    ));
+    transfers.push_back(create_transfer_request(&env, Address::generate(&env), 0));
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        2_000,
+    ));
+    for _ in 0..3 {
+        transfers.push_back(create_transfer_request(&env, Address::generate(&env), 0));
+    }
+    let result = client.batch_transfer(&admin, &token, &transfers);
+
+    // The interior zero still fails; the padding is not counted at all
+    assert_eq!(result.total_requests, 3);
+    assert_eq!(result.successful, 2);
+    assert_eq!(result.failed, 1);
+    assert_eq!(result.results.len(), 3);
+
+    let mut padding: Vec<TransferRequest> = Vec::new(&env);
+    padding.push_back(create_transfer_request(&env, Address::generate(&env), 0));
+    assert!(client.try_batch_transfer(&admin, &token, &padding).is_err());
+}
+
+#[test]
+fn test_sender_drained_event_only_when_enabled() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    let token_admin = token::StellarAssetClient::new(&env, &token);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        1_000,
+    ));
+    let drained = |env: &Env| {
+        env.events().all().iter().any(|(_, topics, _)| {
+            topics.len() > 1 && symbol_topic(env, &topics, 1) == symbol_short!("drained")
+        })
+    };
+
+    token_admin.mint(&admin, &1_000);
+    client.batch_transfer(&admin, &token, &transfers);
+    assert!(!drained(&env));
+
+    // Draining is reported, not blocked
+    client.set_warn_on_drain(&admin, &true);
+    token_admin.mint(&admin, &2_000);
+    client.batch_transfer(&admin, &token, &transfers);
+    assert!(!drained(&env));
+    client.batch_transfer(&admin, &token, &transfers);
+    assert!(drained(&env));
+    assert_eq!(token_client.balance(&admin), 0);
+}
+
+#[test]
+fn test_unreachable_fee_collector_does_not_revert_batch() {
+    let (env, admin, _token, _token_client, client) = setup_test_env();
+    let stellar_asset = env.register_stellar_asset_contract_v2(Address::generate(&env));
+    stellar_asset.issuer().set_flag(IssuerFlags::RevocableFlag);
+    let token = stellar_asset.address();
+    let token_client = token::Client::new(&env, &token);
+    let token_admin = token::StellarAssetClient::new(&env, &token);
+    token_admin.mint(&admin, &10_000);
+
+    let collector = Address::generate(&env);
+    token_admin.mint(&collector, &1);
+    token_admin.set_authorized(&collector, &false);
+    client.set_fee(&admin, &100, &collector); // 1%
+
+    let recipient = Address::generate(&env);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 5_000));
+    let result = client.batch_transfer(&admin, &token, &transfers);
+    let fee_failed = env.events().all().iter().any(|(_, topics, _)| {
+        topics.len() > 1 && symbol_topic(&env, &topics, 1) == symbol_short!("failed")
+    });
+
+    // The transfer commits; the fee is skipped and flagged
+    assert!(fee_failed);
+    assert_eq!(result.successful, 1);
+    assert_eq!(token_client.balance(&recipient), 5_000);
+    assert_eq!(token_client.balance(&admin), 5_000);
+    assert_eq!(token_client.balance(&collector), 1);
+    assert_eq!(client.get_total_fees_collected(), 0);
+}
+
+// Lifetime Cap Tests
+
+#[test]
+fn test_lifetime_cap_auto_pauses_until_raised() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &100_000);
+    client.set_lifetime_cap(&admin, &5_000);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        3_000,
+    ));
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        3_000,
+    ));
+    let result = client.batch_transfer(&admin, &token, &transfers);
+
+    // The crossing batch completes, then the contract pauses itself
+    assert_eq!(result.successful, 2);
+    let events = env.events().all();
+    let (_, topics, _) = events.get(events.len() - 2).unwrap();
+    assert_eq!(symbol_topic(&env, &topics, 1), symbol_short!("autopause"));
+    assert!(client.is_paused());
+
+    assert!(client
+        .try_batch_transfer(&admin, &token, &transfers)
+        .is_err());
+    assert!(client.try_unpause(&admin).is_err());
+
+    client.set_lifetime_cap(&admin, &20_000);
+    client.unpause(&admin);
+    assert_eq!(
+        client.batch_transfer(&admin, &token, &transfers).successful,
+        2
+    );
+}
+
+// Staggered Release Tests
+
+#[test]
+fn test_scheduled_transfers_release_and_refund() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+
+    let recipient1 = Address::generate(&env);
+    let recipient2 = Address::generate(&env);
+    let recipient3 = Address::generate(&env);
+    let transfers = Vec::from_array(
+        &env,
+        [
+            ScheduledTransfer {
+                recipient: recipient1.clone(),
+                amount: 1_000,
+                release_ledger: 12_400,
+            },
+            ScheduledTransfer {
+                recipient: recipient2.clone(),
+                amount: 2_000,
+                release_ledger: 12_500,
+            },
+            ScheduledTransfer {
+                recipient: recipient3.clone(),
+                amount: 3_000,
+                release_ledger: 12_600,
+            },
+        ],
+    );
+    let schedule_id = client.batch_schedule_transfers(&admin, &token, &transfers);
+    assert_eq!(client.get_total_locked(&token), 6_000);
+    assert_eq!(token_client.balance(&admin), 4_000);
+
+    assert!(client.try_release_scheduled(&schedule_id, &0).is_err());
+
+    env.ledger().with_mut(|li| li.sequence_number = 12_400);
+    assert_eq!(client.release_scheduled(&schedule_id, &0), 1_000);
+    assert_eq!(token_client.balance(&recipient1), 1_000);
+    assert!(client.try_release_scheduled(&schedule_id, &0).is_err());
+    assert!(client.try_release_scheduled(&schedule_id, &1).is_err());
+
+    assert_eq!(
+        client.cancel_scheduled_transfer(&admin, &schedule_id),
+        5_000
+    );
+    assert_eq!(token_client.balance(&admin), 9_000);
+    assert_eq!(token_client.balance(&recipient2), 0);
+    assert_eq!(client.get_total_locked(&token), 0);
+    assert!(client.get_transfer_schedule(&schedule_id).is_none());
+}
+
+#[test]
+fn test_scheduled_transfers_fully_released_are_removed() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+
+    let recipient = Address::generate(&env);
+    let transfers = Vec::from_array(
+        &env,
+        [ScheduledTransfer {
+            recipient: recipient.clone(),
+            amount: 1_500,
+            release_ledger: 12_345,
+        }],
+    );
+    let schedule_id = client.batch_schedule_transfers(&admin, &token, &transfers);
+
+    assert_eq!(client.release_scheduled(&schedule_id, &0), 1_500);
+    assert_eq!(token_client.balance(&recipient), 1_500);
+    assert!(client.get_transfer_schedule(&schedule_id).is_none());
+    assert!(client
+        .try_cancel_scheduled_transfer(&admin, &schedule_id)
+        .is_err());
+}
+
+// Memo Tests
+
+#[test]
+fn test_unique_memos_fail_repeated_invoice() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+
+    let recipient = Address::generate(&env);
+    let memo_request = |memo: &str| MemoTransferRequest {
+        recipient: recipient.clone(),
+        amount: 1_000,
+        memo: String::from_str(&env, memo),
+    };
+    let transfers = Vec::from_array(
+        &env,
+        [
+            memo_request("INV-1"),
+            memo_request("INV-2"),
+            memo_request("INV-1"),
+        ],
+    );
+
+    // Without the flag every item is paid
+    assert_eq!(
+        client
+            .batch_transfer_with_memos(&admin, &token, &transfers)
+            .successful,
+        3
+    );
+
+    client.set_unique_memos(&admin, &true);
+    let result = client.batch_transfer_with_memos(&admin, &token, &transfers);
+
+    assert_eq!(result.successful, 2);
+    assert!(matches!(
+        result.results.get(0).unwrap(),
+        TransferResult::Success(_, _)
+    ));
+    match result.results.get(2).unwrap() {
+        TransferResult::Failure(_, _, error_code) => assert_eq!(error_code, 9),
+        _ => panic!("Expected duplicate memo failure"),
+    }
+    assert_eq!(token_client.balance(&recipient), 5_000);
+}
+
+// Storage Version Tests
+
+#[test]
+fn test_storage_version_guard_until_migrated() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+    assert_eq!(client.get_storage_version(), STORAGE_VERSION);
+
+    // Simulate storage left behind by code predating the current layout
+    env.as_contract(&client.address, || {
+        env.storage()
+            .instance()
+            .set(&DataKey::StorageVersion, &(STORAGE_VERSION - 1));
+    });
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        1_000,
+    ));
+    assert!(client
+        .try_batch_transfer(&admin, &token, &transfers)
+        .is_err());
+    assert!(client.try_pause(&admin).is_err());
+
+    assert_eq!(client.migrate(&admin), STORAGE_VERSION);
+    assert_eq!(
+        client.batch_transfer(&admin, &token, &transfers).successful,
+        1
+    );
+}
+
+#[test]
+#[should_panic(expected = "Storage migration required")]
+fn test_storage_version_mismatch_panics() {
+    let (env, admin, _token, _token_client, client) = setup_test_env();
+    env.as_contract(&client.address, || {
+        env.storage().instance().remove(&DataKey::StorageVersion);
+    });
+
+    client.pause(&admin);
+}
+
+// Callback Tests
+
+mod recording_callback {
+    use crate::BatchRecord;
+    use soroban_sdk::{contract, contractimpl, symbol_short, Env};
+
+    /// Callback that stores the last summary it received.
+    #[contract]
+    pub struct RecordingCallback;
+
+    #[contractimpl]
+    impl RecordingCallback {
+        pub fn on_batch_complete(env: Env, batch_id: u64, summary: BatchRecord) {
+            env.storage()
+                .instance()
+                .set(&symbol_short!("last"), &(batch_id, summary));
+        }
+
+        pub fn last(env: Env) -> Option<(u64, BatchRecord)> {
+            env.storage().instance().get(&symbol_short!("last"))
+        }
+    }
+}
+
+mod failing_callback {
+    use crate::BatchRecord;
+    use soroban_sdk::{contract, contractimpl, Env};
+
+    /// Callback that always fails.
+    #[contract]
+    pub struct PanickingCallback;
+
+    #[contractimpl]
+    impl PanickingCallback {
+        pub fn on_batch_complete(_env: Env, _batch_id: u64, _summary: BatchRecord) {
+            panic!("callback failure");
+        }
+    }
+}
+
+mod multisig_admin {
+    use soroban_sdk::{
+        auth::{Context, CustomAccountInterface},
+        contract, contracterror, contractimpl,
+        crypto::Hash,
+        symbol_short, Env, Map, Vec,
+    };
+
+    #[contracterror]
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    #[repr(u32)]
+    pub enum MultisigError {
+        NotEnoughApprovals = 1,
+        UnknownSigner = 2,
+    }
+
+    /// Account contract approving when at least `threshold` of its
+    /// `signers` distinct signers (identified by index) sign off.
+    #[contract]
+    pub struct ThresholdMultisig;
+
+    #[contractimpl]
+    impl ThresholdMultisig {
+        pub fn __constructor(env: Env, signers: u32, threshold: u32) {
+            env.storage()
+                .instance()
+                .set(&symbol_short!("config"), &(signers, threshold));
+        }
+    }
+
+    #[contractimpl]
+    impl CustomAccountInterface for ThresholdMultisig {
+        type Signature = Vec<u32>;
+        type Error = MultisigError;
+
+        #[allow(non_snake_case)]
+        fn __check_auth(
+            env: Env,
+            _signature_payload: Hash<32>,
+            approvals: Vec<u32>,
+            _auth_contexts: Vec<Context>,
+        ) -> Result<(), MultisigError> {
+            let (signers, threshold): (u32, u32) = env
+                .storage()
+                .instance()
+                .get(&symbol_short!("config"))
+                .unwrap();
+            let mut distinct: Map<u32, bool> = Map::new(&env);
+            for signer in approvals.iter() {
+                if signer >= signers {
+                    return Err(MultisigError::UnknownSigner);
+                }
+                distinct.set(signer, true);
+            }
+            if distinct.len() < threshold {
+                return Err(MultisigError::NotEnoughApprovals);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Builds an authorization entry signed by the multisig account `signer`
+/// with the given signer `approvals`.
+fn multisig_auth_entry(
+    env: &Env,
+    signer: &Address,
+    nonce: i64,
+    approvals: &[u32],
+    invocation: SorobanAuthorizedInvocation,
+) -> SorobanAuthorizationEntry {
+    let approvals: Vec<u32> = Vec::from_slice(env, approvals);
+    SorobanAuthorizationEntry {
+        credentials: SorobanCredentials::Address(SorobanAddressCredentials {
+            address: ScAddress::from(signer),
+            nonce,
+            signature_expiration_ledger: env.ledger().sequence() + 100,
+            signature: ScVal::try_from_val(env, &approvals.to_val()).unwrap(),
+        }),
+        root_invocation: invocation,
+    }
+}
+
+/// Describes a call to `function` on `contract` for an authorization entry.
+fn authorized_call(
+    env: &Env,
+    contract: &Address,
+    function: &str,
+    args: Vec<Val>,
+    sub_invocations: std::vec::Vec<SorobanAuthorizedInvocation>,
+) -> SorobanAuthorizedInvocation {
+    let args: std::vec::Vec<ScVal> = args
+        .iter()
+        .map(|arg| ScVal::try_from_val(env, &arg).unwrap())
+        .collect();
+    SorobanAuthorizedInvocation {
+        function: SorobanAuthorizedFunction::ContractFn(InvokeContractArgs {
+            contract_address: ScAddress::from(contract),
+            function_name: function.try_into().unwrap(),
+            args: args.try_into().unwrap(),
+        }),
+        sub_invocations: sub_invocations.try_into().unwrap(),
+    }
+}
+
+mod wallet_registry {
+    use crate::{RegistryWallet, RegistryWalletRequest};
+    use soroban_sdk::{contract, contractimpl, symbol_short, Address, Env, Vec};
+
+    /// Minimal stand-in for the wallet contract's owner lookup and creation.
+    #[contract]
+    pub struct MockWalletRegistry;
+
+    #[contractimpl]
+    impl MockWalletRegistry {
+        pub fn add_wallet(env: Env, owner: Address, id: u64) {
+            let wallet = RegistryWallet {
+                id,
+                owner: owner.clone(),
+                created_at: env.ledger().timestamp(),
+            };
+            env.storage().persistent().set(&owner, &wallet);
+        }
+
+        pub fn get_wallet(env: Env, owner: Address) -> Option<RegistryWallet> {
+            env.storage().persistent().get(&owner)
+        }
+
+        pub fn batch_create_wallets(
+            env: Env,
+            caller: Address,
+            requests: Vec<RegistryWalletRequest>,
+        ) {
+            caller.require_auth();
+            for request in requests.iter() {
+                let id: u64 = env
+                    .storage()
+                    .instance()
+                    .get(&symbol_short!("next"))
+                    .unwrap_or(1);
+                Self::add_wallet(env.clone(), request.owner, id);
+                env.storage()
+                    .instance()
+                    .set(&symbol_short!("next"), &(id + 1));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_wallet_spend_limit_resets_each_day() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+    let registry = env.register(wallet_registry::MockWalletRegistry, ());
+    client.set_wallet_registry(&admin, &registry);
+    client.set_wallet_spend_limit(&admin, &token, &1_000);
+    let recipient = Address::generate(&env);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 800));
+
+    // The limit only binds senders with a registered wallet
+    client.batch_transfer(&admin, &token, &transfers);
+    wallet_registry::MockWalletRegistryClient::new(&env, &registry).add_wallet(&admin, &1);
+    client.batch_transfer(&admin, &token, &transfers);
+
+    let result = client.batch_transfer(&admin, &token, &transfers);
+    match result.results.get(0).unwrap() {
+        TransferResult::Failure(_, _, error_code) => assert_eq!(error_code, 12),
+        _ => panic!("Expected wallet limit failure"),
+    }
+    assert_eq!(token_client.balance(&recipient), 1_600);
+
+    env.ledger().with_mut(|li| li.sequence_number += 17_280);
+    assert_eq!(
+        client.batch_transfer(&admin, &token, &transfers).successful,
+        1
+    );
+    assert_eq!(token_client.balance(&recipient), 2_400);
+}
+
+#[test]
+fn test_callback_receives_batch_summary() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+    let callback = env.register(recording_callback::RecordingCallback, ());
+    client.set_callback(&admin, &callback);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        1_000,
+    ));
+    transfers.push_back(create_transfer_request(&env, Address::generate(&env), 0));
+    client.batch_transfer(&admin, &token, &transfers);
+
+    let (batch_id, summary) = recording_callback::RecordingCallbackClient::new(&env, &callback)
+        .last()
+        .unwrap();
+    assert_eq!(batch_id, 1);
+    assert_eq!(summary.successful, 1);
+    assert_eq!(summary.failed, 1);
+    assert_eq!(summary.total_transferred, 1_000);
+}
+
+#[test]
+fn test_failing_callback_does_not_revert_batch() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+    let callback = env.register(failing_callback::PanickingCallback, ());
+    client.set_callback(&admin, &callback);
+
+    let recipient = Address::generate(&env);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 1_000));
+    let result = client.batch_transfer(&admin, &token, &transfers);
+
+    let events = env.events().all();
+    assert!(events.iter().any(|(_, topics, _)| {
+        symbol_topic(&env, &topics, 0) == symbol_short!("callback")
+            && symbol_topic(&env, &topics, 1) == symbol_short!("failed")
+    }));
+    assert_eq!(result.successful, 1);
+    assert_eq!(token_client.balance(&recipient), 1_000);
+}
+
+#[test]
+fn test_rounding_modes_place_dust_differently() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    let token_admin = token::StellarAssetClient::new(&env, &token);
+
+    // 19 split 10/70/10/10% floors to 1/13/1/1, leaving 3 units of dust
+    let cases = [
+        (RoundingMode::RemainderToFirst, [4, 13, 1, 1]),
+        (RoundingMode::RemainderToLast, [1, 13, 1, 4]),
+        (RoundingMode::RemainderToLargest, [1, 16, 1, 1]),
+        (RoundingMode::BankersApprox, [2, 13, 2, 2]),
+    ];
+    for (mode, expected) in cases {
+        token_admin.mint(&admin, &19);
+        let recipients: [Address; 4] = core::array::from_fn(|_| Address::generate(&env));
+        let shares = Vec::from_array(
+            &env,
+            [
+                (recipients[0].clone(), 1_000u32),
+                (recipients[1].clone(), 7_000u32),
+                (recipients[2].clone(), 1_000u32),
+                (recipients[3].clone(), 1_000u32),
+            ],
+        );
+        let result = client.batch_transfer_balance_pct(&admin, &token, &shares, &Some(mode));
+
+        assert_eq!(result.total_transferred, 19);
+        for (recipient, amount) in recipients.iter().zip(expected) {
+            assert_eq!(token_client.balance(recipient), amount);
+        }
+    }
+}
+
+// Config Freeze Tests
+
+#[test]
+fn test_freeze_config_blocks_setters_but_not_transfers() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+    client.set_rate_limit(&admin, &5_000, &100);
+
+    client.freeze_config(&admin);
+    assert!(client.is_config_frozen());
+
+    assert!(client.try_set_rate_limit(&admin, &50_000, &100).is_err());
+    assert!(client.try_clear_rate_limit(&admin).is_err());
+    assert!(client
+        .try_set_fee(&admin, &10, &Address::generate(&env))
+        .is_err());
+    assert!(client.try_set_verify_deltas(&admin, &true).is_err());
+    assert_eq!(client.get_rate_limit().unwrap().max_volume, 5_000);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        1_000,
+    ));
+    assert_eq!(
+        client.batch_transfer(&admin, &token, &transfers).successful,
+        1
+    );
+    client.pause(&admin);
+    assert!(client.is_paused());
+}
+
+#[test]
+#[should_panic(expected = "Config frozen")]
+fn test_frozen_config_setter_panics() {
+    let (_env, admin, _token, _token_client, client) = setup_test_env();
+    client.freeze_config(&admin);
+
+    client.set_merge_duplicates(&admin, &true);
+}
+
+#[test]
+fn test_lookup_address_reports_every_role() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    let registry = env.register(wallet_registry::MockWalletRegistry, ());
+    client.set_wallet_registry(&admin, &registry);
+
+    let target = Address::generate(&env);
+    client.add_operator(&admin, &target);
+    client.set_fee(&admin, &10, &target);
+    client.add_allowed_sender(&admin, &target);
+    client.suspend_sender(&admin, &target);
+    wallet_registry::MockWalletRegistryClient::new(&env, &registry).add_wallet(&target, &7);
+
+    let info = client.lookup_address(&target);
+    assert!(!info.is_admin);
+    assert!(info.is_operator);
+    assert!(info.is_fee_collector);
+    assert!(info.is_allowlisted);
+    assert!(!info.is_blocked_token);
+    assert!(info.is_suspended);
+    assert_eq!(info.wallet_id, Some(7));
+
+    client.block_token(&admin, &token);
+    let info = client.lookup_address(&token);
+    assert!(info.is_blocked_token);
+    assert!(!info.is_operator);
+    assert_eq!(info.wallet_id, None);
+
+    let info = client.lookup_address(&admin);
+    assert!(info.is_admin);
+    assert!(!info.is_suspended);
+}
+
+#[test]
+fn test_ensure_wallets_creates_missing_then_funds() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+    let registry = env.register(wallet_registry::MockWalletRegistry, ());
+    let registry_client = wallet_registry::MockWalletRegistryClient::new(&env, &registry);
+    client.set_wallet_registry(&admin, &registry);
+
+    let existing = Address::generate(&env);
+    let newcomer = Address::generate(&env);
+    registry_client.add_wallet(&existing, &100);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, existing.clone(), 1_000));
+    transfers.push_back(create_transfer_request(&env, newcomer.clone(), 2_000));
+    let result = client.batch_transfer_ensure_wallets(&admin, &token, &transfers);
+
+    assert_eq!(result.wallets_created.get(0), Some(false));
+    assert_eq!(result.wallets_created.get(1), Some(true));
+    assert_eq!(result.transfers.successful, 2);
+    assert_eq!(registry_client.get_wallet(&existing).unwrap().id, 100);
+    assert!(registry_client.get_wallet(&newcomer).is_some());
+    assert_eq!(token_client.balance(&newcomer), 2_000);
+    assert_eq!(token_client.balance(&existing), 1_000);
+}
+
+#[test]
+fn test_large_transfer_alert_fires_only_for_oversized_transfers() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &20_000);
+    client.set_large_transfer_alert(&admin, &5_000);
+
+    let small = Address::generate(&env);
+    let large = Address::generate(&env);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, small.clone(), 1_000));
+    transfers.push_back(create_transfer_request(&env, large.clone(), 6_000));
+    client.batch_transfer(&admin, &token, &transfers);
+
+    let alerts: std::vec::Vec<_> = env
+        .events()
+        .all()
+        .iter()
+        .filter(|(_, topics, _)| symbol_topic(&env, topics, 1) == symbol_short!("large"))
+        .collect();
+    assert_eq!(alerts.len(), 1);
+    let (from, recipient, amount): (Address, Address, i128) =
+        TryFromVal::try_from_val(&env, &alerts[0].2).unwrap();
+    assert_eq!(from, admin);
+    assert_eq!(recipient, large);
+    assert_eq!(amount, 6_000);
+}
+
+#[test]
+fn test_batch_auth_is_bound_to_transfer_set() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+    let recipient = Address::generate(&env);
+
+    let mut signed: Vec<TransferRequest> = Vec::new(&env);
+    signed.push_back(create_transfer_request(&env, recipient.clone(), 1_000));
+    let mut tampered: Vec<TransferRequest> = Vec::new(&env);
+    tampered.push_back(create_transfer_request(&env, recipient.clone(), 9_000));
+
+    let transfer_auth = MockAuthInvoke {
+        contract: &token,
+        fn_name: "transfer",
+        args: (admin.clone(), recipient.clone(), 1_000_i128).into_val(&env),
+        sub_invokes: &[],
+    };
+    let batch_auth = MockAuthInvoke {
+        contract: &client.address,
+        fn_name: "batch_transfer",
+        args: (token.clone(), signed.clone()).into_val(&env),
+        sub_invokes: &[transfer_auth],
+    };
+
+    env.mock_auths(&[MockAuth {
+        address: &admin,
+        invoke: &batch_auth,
+    }]);
+    assert!(client
+        .try_batch_transfer(&admin, &token, &tampered)
+        .is_err());
+    assert_eq!(token_client.balance(&recipient), 0);
+
+    env.mock_auths(&[MockAuth {
+        address: &admin,
+        invoke: &batch_auth,
+    }]);
+    client.batch_transfer(&admin, &token, &signed);
+    assert_eq!(token_client.balance(&recipient), 1_000);
+}
+
+#[test]
+fn test_last_batch_result_tracks_latest_batch() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+    assert!(client.get_last_batch_result().is_none());
+
+    let mut first: Vec<TransferRequest> = Vec::new(&env);
+    first.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        1_000,
+    ));
+    client.batch_transfer(&admin, &token, &first);
+
+    let mut second: Vec<TransferRequest> = Vec::new(&env);
+    second.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        2_000,
+    ));
+    second.push_back(create_transfer_request(&env, Address::generate(&env), 0));
+    let expected = client.batch_transfer(&admin, &token, &second);
+
+    let last = client.get_last_batch_result().unwrap();
+    assert_eq!(last.total_requests, 2);
+    assert_eq!(last.successful, 1);
+    assert_eq!(last.failed, 1);
+    assert_eq!(last.total_transferred, 2_000);
+    assert_eq!(last.results, expected.results);
+}
+
+#[test]
+fn test_operator_limits_are_independent() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    let asset_client = token::StellarAssetClient::new(&env, &token);
+    let capped = Address::generate(&env);
+    let roomy = Address::generate(&env);
+    for operator in [&capped, &roomy] {
+        client.add_operator(&admin, operator);
+        asset_client.mint(operator, &10_000);
+    }
+    client.set_operator_limit(&admin, &capped, &1_000);
+    client.set_operator_limit(&admin, &roomy, &5_000);
+
+    let recipient = Address::generate(&env);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 600));
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 600));
+
+    let result = client.batch_transfer(&capped, &token, &transfers);
+    assert_eq!(result.successful, 1);
+    match result.results.get(1).unwrap() {
+        TransferResult::Failure(_, amount, code) => {
+            assert_eq!(amount, 600);
+            assert_eq!(code, 11); // Operator limit exceeded
+        }
+        _ => panic!("Expected operator limit failure"),
+    }
+    assert_eq!(client.get_operator_remaining(&capped), 400);
+
+    let result = client.batch_transfer(&roomy, &token, &transfers);
+    assert_eq!(result.successful, 2);
+    assert_eq!(client.get_operator_remaining(&roomy), 3_800);
+    assert_eq!(token_client.balance(&recipient), 1_800);
+
+    // The budget resets on the next day
+    env.ledger().with_mut(|li| li.sequence_number += 17_280);
+    assert_eq!(client.get_operator_remaining(&capped), 1_000);
+}
+
+#[test]
+fn test_unique_recipients_count_first_receipts_only() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    let unpaid = Address::generate(&env);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, first.clone(), 100));
+    transfers.push_back(create_transfer_request(&env, first.clone(), 100));
+    transfers.push_back(create_transfer_request(&env, unpaid.clone(), 0));
+    client.batch_transfer(&admin, &token, &transfers);
+    assert_eq!(client.get_unique_recipients(), 1);
+    assert!(!client.has_received(&unpaid));
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, first.clone(), 100));
+    transfers.push_back(create_transfer_request(&env, second.clone(), 100));
+    client.batch_transfer(&admin, &token, &transfers);
+    assert_eq!(client.get_unique_recipients(), 2);
+    assert!(client.has_received(&first));
+    assert!(client.has_received(&second));
+}
+
+#[test]
+fn test_checked_batch_requires_matching_expected_total() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+    let recipient = Address::generate(&env);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 1_000));
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 2_000));
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), -7));
+
+    // Invalid items are not part of the total
+    assert!(client
+        .try_batch_transfer_checked(&admin, &token, &transfers, &3_007)
+        .is_err());
+    assert_eq!(token_client.balance(&recipient), 0);
+
+    let result = client.batch_transfer_checked(&admin, &token, &transfers, &3_000);
+    assert_eq!(result.successful, 2);
+    assert_eq!(token_client.balance(&recipient), 3_000);
+
+    let result = client.batch_transfer_checked(&admin, &token, &transfers, &-1);
+    assert_eq!(result.successful, 2);
+}
+
+#[test]
+fn test_ordered_batch_decides_who_is_paid_when_underfunded() {
+    let amounts = [100, 600, 100, 500];
+    let paid = |order: OrderMode| {
+        let (env, admin, token, token_client, client) = setup_test_env();
+        token::StellarAssetClient::new(&env, &token).mint(&admin, &1_100);
+        let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+        for amount in amounts {
+            transfers.push_back(create_transfer_request(
+                &env,
+                Address::generate(&env),
+                amount,
+            ));
+        }
+        let result = client.batch_transfer_ordered(&admin, &token, &transfers, &order);
+
+        // Results come back in input order whatever the execution order
+        let mut paid = std::vec::Vec::new();
+        for (request, outcome) in transfers.iter().zip(result.results.iter()) {
+            let success = match outcome {
+                TransferResult::Success(recipient, _) => {
+                    assert_eq!(recipient, request.recipient);
+                    true
+                }
+                TransferResult::Failure(recipient, _, error_code) => {
+                    assert_eq!(recipient, request.recipient);
+                    assert_eq!(error_code, 2);
+                    false
+                }
+                _ => panic!("Unexpected result"),
+            };
+            assert_eq!(
+                token_client.balance(&request.recipient),
+                if success { request.amount } else { 0 }
+            );
+            paid.push(success);
+        }
+        paid
+    };
+
+    assert_eq!(
+        paid(OrderMode::DescendingAmount),
+        [false, true, false, true]
+    );
+    assert_eq!(paid(OrderMode::AscendingAmount), [true, false, true, true]);
+    assert_eq!(paid(OrderMode::AsGiven), [true, true, true, false]);
+}
+
+#[test]
+fn test_stat_snapshots_keep_each_period() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        1_000,
+    ));
+    transfers.push_back(create_transfer_request(&env, Address::generate(&env), 500));
+
+    client.batch_transfer(&admin, &token, &transfers);
+    client.batch_transfer(&admin, &token, &transfers);
+    client.reset_stats(&admin);
+
+    env.ledger().with_mut(|li| li.sequence_number += 10);
+    client.batch_transfer(&admin, &token, &transfers);
+    client.reset_stats(&admin);
+
+    let snapshots = client.get_stat_snapshots(&5);
+    assert_eq!(snapshots.len(), 2);
+    let latest = snapshots.get(0).unwrap();
+    assert_eq!(latest.reset_ledger, 12355);
+    assert_eq!(latest.batches, 1);
+    assert_eq!(latest.transfers_processed, 2);
+    assert_eq!(latest.volume, 1_500);
+    let first = snapshots.get(1).unwrap();
+    assert_eq!(first.reset_ledger, 12345);
+    assert_eq!(first.batches, 2);
+    assert_eq!(first.transfers_processed, 4);
+    assert_eq!(first.volume, 3_000);
+
+    // Lifetime totals are not reset
+    assert_eq!(client.get_total_volume_transferred(), 4_500);
+    assert_eq!(client.get_stat_snapshots(&1).len(), 1);
+}
+
+#[test]
+fn test_failure_breakdown_tallies_codes() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &1_000);
+    assert_eq!(client.get_failure_breakdown().len(), 0);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, Address::generate(&env), 0));
+    transfers.push_back(create_transfer_request(&env, Address::generate(&env), -5));
+    transfers.push_back(create_transfer_request(&env, Address::generate(&env), 600));
+    transfers.push_back(create_transfer_request(&env, Address::generate(&env), 600));
+    client.batch_transfer(&admin, &token, &transfers);
+
+    let mut burns: Vec<BurnRequest> = Vec::new(&env);
+    burns.push_back(create_burn_request(&env, Address::generate(&env), 10));
+    client.batch_burn(&admin, &token, &burns);
+
+    let mut expected: Vec<(u32, u64)> = Vec::new(&env);
+    expected.push_back((1, 2)); // invalid amount
+    expected.push_back((2, 2)); // insufficient balance, one from the burn
+    assert_eq!(client.get_failure_breakdown(), expected);
+}
+
+#[test]
+fn test_multisig_contract_can_act_as_admin() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    let multisig = env.register(multisig_admin::ThresholdMultisig, (3_u32, 2_u32));
+    token::StellarAssetClient::new(&env, &token).mint(&multisig, &10_000);
+    client.set_admin(&admin, &multisig);
+
+    // A single approval is below the 2-of-3 threshold
+    let pause = authorized_call(
+        &env,
+        &client.address,
+        "pause",
+        (multisig.clone(),).into_val(&env),
+        std::vec![],
+    );
+    env.set_auths(&[multisig_auth_entry(&env, &multisig, 1, &[0], pause.clone())]);
+    assert!(client.try_pause(&multisig).is_err());
+    assert!(!client.is_paused());
+
+    env.set_auths(&[multisig_auth_entry(&env, &multisig, 2, &[0, 2], pause)]);
+    client.pause(&multisig);
+    assert!(client.is_paused());
+
+    let unpause = authorized_call(
+        &env,
+        &client.address,
+        "unpause",
+        (multisig.clone(),).into_val(&env),
+        std::vec![],
+    );
+    env.set_auths(&[multisig_auth_entry(&env, &multisig, 3, &[1, 2], unpause)]);
+    client.unpause(&multisig);
+
+    let recipient = Address::generate(&env);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 1_000));
+    let token_transfer = authorized_call(
+        &env,
+        &token,
+        "transfer",
+        (multisig.clone(), recipient.clone(), 1_000_i128).into_val(&env),
+        std::vec![],
+    );
+    let batch = authorized_call(
+        &env,
+        &client.address,
+        "batch_transfer",
+        (token.clone(), transfers.clone()).into_val(&env),
+        std::vec![token_transfer],
+    );
+    env.set_auths(&[multisig_auth_entry(&env, &multisig, 4, &[0], batch.clone())]);
+    assert!(client
+        .try_batch_transfer(&multisig, &token, &transfers)
+        .is_err());
+    env.set_auths(&[multisig_auth_entry(&env, &multisig, 5, &[0, 1], batch)]);
+    client.batch_transfer(&multisig, &token, &transfers);
+    assert_eq!(token_client.balance(&recipient), 1_000);
+
+    let new_admin = Address::generate(&env);
+    let handoff = authorized_call(
+        &env,
+        &client.address,
+        "set_admin",
+        (multisig.clone(), new_admin.clone()).into_val(&env),
+        std::vec![],
+    );
+    env.set_auths(&[multisig_auth_entry(
+        &env,
+        &multisig,
+        6,
+        &[1, 1],
+        handoff.clone(),
+    )]);
+    assert!(client.try_set_admin(&multisig, &new_admin).is_err());
+    env.set_auths(&[multisig_auth_entry(&env, &multisig, 7, &[1, 2], handoff)]);
+    client.set_admin(&multisig, &new_admin);
+    assert_eq!(client.get_admin(), new_admin);
+}
+
+#[test]
+fn test_batch_cooldown_throttles_each_sender() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    let asset_client = token::StellarAssetClient::new(&env, &token);
+    let operator = Address::generate(&env);
+    client.add_operator(&admin, &operator);
+    asset_client.mint(&admin, &10_000);
+    asset_client.mint(&operator, &10_000);
+    client.set_batch_cooldown(&admin, &10);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, Address::generate(&env), 100));
+
+    client.batch_transfer(&admin, &token, &transfers);
+    assert!(client
+        .try_batch_transfer(&admin, &token, &transfers)
+        .is_err());
+
+    // Another sender has its own cooldown
+    client.batch_transfer(&operator, &token, &transfers);
+
+    env.ledger().with_mut(|li| li.sequence_number += 10);
+    assert_eq!(
+        client.batch_transfer(&admin, &token, &transfers).successful,
+        1
+    );
+}
+
+#[test]
+fn test_min_amount_fails_cleanly_as_invalid_amount() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    let asset_client = token::StellarAssetClient::new(&env, &token);
+    asset_client.mint(&admin, &10_000);
+    let recipient = Address::generate(&env);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), i128::MIN));
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 500));
+    let result = client.batch_transfer(&admin, &token, &transfers);
+    assert_eq!(result.successful, 1);
+    match result.results.get(0).unwrap() {
+        TransferResult::Failure(_, amount, code) => {
+            assert_eq!(amount, i128::MIN);
+            assert_eq!(code, 1); // Invalid amount
+        }
+        _ => panic!("Expected i128::MIN to be rejected"),
+    }
+
+    let source = Address::generate(&env);
+    asset_client.mint(&source, &1_000);
+    let mut requests: Vec<TransferFromRequest> = Vec::new(&env);
+    requests.push_back(TransferFromRequest {
+        from: source.clone(),
+        amount: i128::MIN,
+    });
+    let result = client.batch_transfer_from(&admin, &token, &recipient, &requests);
+    match result.results.get(0).unwrap() {
+        TransferResult::Failure(_, _, code) => assert_eq!(code, 1),
+        _ => panic!("Expected i128::MIN to be rejected"),
+    }
+    assert_eq!(token_client.balance(&recipient), 500);
+    assert_eq!(token_client.balance(&source), 1_000);
+}
+
+#[test]
+fn test_token_fee_overrides_global_rate() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    let other = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let other_client = token::Client::new(&env, &other);
+    let fallback = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let fallback_client = token::Client::new(&env, &fallback);
+    for asset in [&token, &other, &fallback] {
+        token::StellarAssetClient::new(&env, asset).mint(&admin, &100_000);
+    }
+
+    let collector = Address::generate(&env);
+    client.set_fee(&admin, &100, &collector);
+    client.set_token_fee(&admin, &token, &50);
+    client.set_token_fee(&admin, &other, &200);
+    assert_eq!(client.get_token_fee(&token), Some(50));
+    assert_eq!(client.get_token_fee(&fallback), None);
+
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        10_000,
+    ));
+    client.batch_transfer(&admin, &token, &transfers);
+    client.batch_transfer(&admin, &other, &transfers);
+    client.batch_transfer(&admin, &fallback, &transfers);
+
+    assert_eq!(token_client.balance(&collector), 50);
+    assert_eq!(other_client.balance(&collector), 200);
+    assert_eq!(fallback_client.balance(&collector), 100);
+}
+
+#[test]
+fn test_reversible_transfer_settles_after_window() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+    let recipient = Address::generate(&env);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 4_000));
+
+    let ids = client.batch_transfer_reversible(&admin, &token, &transfers, &100);
+    let id = ids.get(0).unwrap();
+    assert_eq!(token_client.balance(&admin), 6_000);
+    assert_eq!(client.get_total_locked(&token), 4_000);
+    assert!(client.try_settle(&id).is_err());
+
+    env.ledger().with_mut(|li| li.sequence_number += 100);
+    assert_eq!(client.settle(&id), 4_000);
+    assert_eq!(token_client.balance(&recipient), 4_000);
+    assert_eq!(client.get_total_locked(&token), 0);
+    assert_eq!(
+        client.get_reversible_transfer(&id).unwrap().state,
+        ReversalState::Settled
+    );
+
+    // Settled transfers can no longer be reversed or settled twice
+    assert!(client.try_reverse(&id).is_err());
+    assert!(client.try_settle(&id).is_err());
+}
+
+#[test]
+fn test_reversible_transfer_reversed_within_window() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+    let recipient = Address::generate(&env);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 4_000));
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 1_000));
+
+    let ids = client.batch_transfer_reversible(&admin, &token, &transfers, &100);
+    env.ledger().with_mut(|li| li.sequence_number += 99);
+    assert_eq!(client.reverse(&ids.get(0).unwrap()), 4_000);
+    assert_eq!(token_client.balance(&admin), 9_000);
+    assert_eq!(
+        client
+            .get_reversible_transfer(&ids.get(0).unwrap())
+            .unwrap()
+            .state,
+        ReversalState::Reversed
+    );
+
+    // The window has closed for the second transfer
+    env.ledger().with_mut(|li| li.sequence_number += 1);
+    assert!(client.try_reverse(&ids.get(1).unwrap()).is_err());
+    client.settle(&ids.get(1).unwrap());
+    assert_eq!(token_client.balance(&recipient), 1_000);
+}
+
+#[test]
+fn test_batch_revoke_zeroes_allowances() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    let owner = Address::generate(&env);
+    let mut spenders: Vec<Address> = Vec::new(&env);
+    for amount in [100_i128, 200, 300] {
+        let spender = Address::generate(&env);
+        token_client.approve(&owner, &spender, &amount, &20_000);
+        spenders.push_back(spender);
+    }
+    // Revocation still works while paused
+    client.pause(&admin);
+
+    let results = client.batch_revoke(&owner, &token, &spenders);
+    assert_eq!(results.len(), 3);
+    for (spender, result) in spenders.iter().zip(results.iter()) {
+        assert_eq!(token_client.allowance(&owner, &spender), 0);
+        match result {
+            TransferResult::Success(revoked_spender, revoked) => {
+                assert_eq!(revoked_spender, spender);
+                assert!(revoked > 0);
+            }
+            _ => panic!("Expected revocation to succeed"),
+        }
+    }
+}
+
+#[test]
+fn test_stream_withdrawals_follow_accrual() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+    let recipient = Address::generate(&env);
+
+    // 10 per ledger over 100 ledgers locks 1_000 up front
+    let id = client.open_stream(&admin, &token, &recipient, &10, &12_345, &12_445);
+    assert_eq!(token_client.balance(&admin), 9_000);
+    assert_eq!(client.get_total_locked(&token), 1_000);
+
+    env.ledger().with_mut(|li| li.sequence_number += 30);
+    assert_eq!(client.withdraw_stream(&recipient, &id), 300);
+    assert_eq!(client.withdraw_stream(&recipient, &id), 0);
+    assert_eq!(client.get_stream(&id).unwrap().withdrawn_ledger, 12_375);
+
+    // Nothing accrues past the stop ledger, and a drained stream is removed
+    env.ledger().with_mut(|li| li.sequence_number += 500);
+    assert_eq!(client.withdraw_stream(&recipient, &id), 700);
+    assert_eq!(token_client.balance(&recipient), 1_000);
+    assert_eq!(client.get_total_locked(&token), 0);
+    assert!(client.get_stream(&id).is_none());
+    assert!(client.try_withdraw_stream(&recipient, &id).is_err());
+}
+
+#[test]
+fn test_closing_stream_early_refunds_unaccrued_remainder() {
+    let (env, admin, token, token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+    let recipient = Address::generate(&env);
+    let id = client.open_stream(&admin, &token, &recipient, &10, &12_345, &12_445);
+
+    env.ledger().with_mut(|li| li.sequence_number += 20);
+    client.withdraw_stream(&recipient, &id);
+    env.ledger().with_mut(|li| li.sequence_number += 20);
+    assert!(client
+        .try_close_stream(&Address::generate(&env), &id)
+        .is_err());
+
+    // Accrued funds still reach the recipient; the rest goes back
+    assert_eq!(client.close_stream(&admin, &id), 600);
+    assert_eq!(token_client.balance(&recipient), 400);
+    assert_eq!(token_client.balance(&admin), 9_600);
+    assert_eq!(client.get_total_locked(&token), 0);
+    assert!(client.get_stream(&id).is_none());
+}
+
+#[test]
+fn test_list_pending_reports_schedules_and_claims() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+    let recipient = Address::generate(&env);
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 700));
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 300));
+
+    let schedule_id = client.schedule_batch(&admin, &token, &transfers, &20_000);
+    let claim_ids = client.batch_create_claims(&admin, &token, &transfers, &30_000);
+
+    let pending = client.list_pending(&1, &10);
+    assert_eq!(pending.len(), 3);
+    assert_eq!(
+        pending.get(0).unwrap(),
+        PendingOp {
+            kind: PendingKind::ScheduledBatch,
+            id: schedule_id,
+            amount: 1_000,
+            ledger: 20_000,
+        }
+    );
+    assert_eq!(
+        pending.get(1).unwrap(),
+        PendingOp {
+            kind: PendingKind::Claim,
+            id: claim_ids.get(0).unwrap(),
+            amount: 700,
+            ledger: 30_000,
+        }
+    );
+    assert_eq!(pending.get(2).unwrap().id, claim_ids.get(1).unwrap());
+
+    // Settled claims drop out of the list
+    client.claim(&recipient, &claim_ids.get(0).unwrap());
+    assert_eq!(client.list_pending(&1, &10).len(), 2);
+}
+
+#[test]
+fn test_transferred_total_stays_within_requested_total() {
+    let (env, admin, token, _token_client, client) = setup_test_env();
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &10_000);
+    client.set_merge_duplicates(&admin, &true);
+    let recipient = Address::generate(&env);
+
+    // Merged duplicates, invalid items and balance failures all keep the
+    // delivered total at or below the valid requested total
+    let mut transfers: Vec<TransferRequest> = Vec::new(&env);
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 4_000));
+    transfers.push_back(create_transfer_request(&env, recipient.clone(), 3_000));
+    transfers.push_back(create_transfer_request(&env, Address::generate(&env), -5));
+    transfers.push_back(create_transfer_request(
+        &env,
+        Address::generate(&env),
+        9_000,
+    ));
+    let result = client.batch_transfer(&admin, &token, &transfers);
+
+    assert_eq!(result.successful, 1);
+    assert_eq!(result.total_transferred, 7_000);
+}
@@ -1,7 +1,78 @@
-use soroban_sdk::{contracttype, symbol_short, Address, Env, Vec};
+use soroban_sdk::{contracttype, symbol_short, Address, Bytes, BytesN, Env, String, Vec};
 
 pub const MAX_BATCH_SIZE: u32 = 100;
 
+/// Storage layout version this code expects; bumped by `migrate`.
+pub const STORAGE_VERSION: u32 = 1;
+
+/// Maximum fee rate (100%) in basis points.
+pub const MAX_FEE_BPS: u32 = 10_000;
+
+/// Largest decimals value whose scale (`10^decimals`) fits in an i128.
+pub const MAX_TOKEN_DECIMALS: u32 = 38;
+
+/// TTL threshold (in ledgers) below which stored batch results are extended (~1 day).
+pub const RESULT_TTL_THRESHOLD: u32 = 17_280;
+
+/// TTL (in ledgers) stored batch results are extended to (~30 days).
+pub const RESULT_TTL_EXTEND: u32 = 518_400;
+
+/// Maximum number of operators that can be registered.
+pub const MAX_OPERATORS: u32 = 20;
+
+/// Ledgers a proposed fee collector must wait before it can be applied (~1 day).
+pub const FEE_COLLECTOR_DELAY_LEDGERS: u32 = 17_280;
+
+/// Ledgers per day used to reset per-operator limits (~5s per ledger).
+pub const LEDGERS_PER_DAY: u32 = 17_280;
+
+/// Number of `reset_stats` snapshots kept on-chain.
+pub const MAX_STAT_SNAPSHOTS: u32 = 10;
+
+/// Per-item failure codes reported in `TransferResult` and `BurnResult`.
+#[allow(non_snake_case)]
+pub mod ErrorCode {
+    /// Recipient, owner or source address is invalid
+    pub const INVALID_ADDRESS: u32 = 0;
+    /// Amount is zero or negative
+    pub const INVALID_AMOUNT: u32 = 1;
+    /// Source balance does not cover the amount (and fee)
+    pub const INSUFFICIENT_BALANCE: u32 = 2;
+    /// Recipient received less than requested (batch log only)
+    pub const PARTIAL_DELIVERY: u32 = 3;
+    /// Allowance granted to the contract does not cover the amount
+    pub const INSUFFICIENT_ALLOWANCE: u32 = 4;
+    /// Source is not on the sender allowlist
+    pub const SENDER_NOT_ALLOWED: u32 = 5;
+    /// Transfer would exceed the volume allowed in the current rate-limit window
+    pub const RATE_LIMITED: u32 = 6;
+    /// Transfer would push the recipient past its cumulative cap
+    pub const RECIPIENT_CAP_EXCEEDED: u32 = 7;
+    /// Token rejected the credit to the recipient (e.g. missing or
+    /// deauthorized trustline) after the sender checks passed
+    pub const RECIPIENT_NOT_READY: u32 = 8;
+    /// Memo repeats an earlier one in the same batch while memos must be unique
+    pub const DUPLICATE_MEMO: u32 = 9;
+    /// Fee (or amount plus fee) for the transfer does not fit in an i128
+    pub const FEE_OVERFLOW: u32 = 10;
+    /// Transfer would push the submitting operator past its daily limit
+    pub const OPERATOR_LIMIT_EXCEEDED: u32 = 11;
+    /// Transfer would push a registered wallet owner past its daily spend limit
+    pub const WALLET_LIMIT_EXCEEDED: u32 = 12;
+    /// Token rejected the transfer for a reason not attributable to the
+    /// recipient (e.g. the sender's balance was deauthorized by the issuer)
+    pub const TRANSFER_REJECTED: u32 = 13;
+}
+
+/// Stellar Asset Contract error: the account does not exist.
+pub const SAC_ACCOUNT_MISSING: u32 = 6;
+
+/// Stellar Asset Contract error: the balance is deauthorized.
+pub const SAC_BALANCE_DEAUTHORIZED: u32 = 11;
+
+/// Stellar Asset Contract error: the account has no trustline for the asset.
+pub const SAC_TRUSTLINE_MISSING: u32 = 13;
+
 #[derive(Clone, Debug)]
 #[contracttype]
 pub struct TransferRequest {
@@ -9,6 +80,22 @@ pub struct TransferRequest {
     pub amount: i128,
 }
 
+/// A transfer tagged with a memo, e.g. the invoice it pays.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct MemoTransferRequest {
+    pub recipient: Address,
+    pub amount: i128,
+    pub memo: String,
+}
+
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct TransferFromRequest {
+    pub from: Address,
+    pub amount: i128,
+}
+
 #[derive(Clone, Debug)]
 #[contracttype]
 pub struct BurnRequest {
@@ -21,6 +108,8 @@ pub struct BurnRequest {
 pub enum TransferResult {
     Success(Address, i128),
     Failure(Address, i128, u32),
+    /// Recipient received less than requested: (recipient, requested, delivered)
+    PartialDelivery(Address, i128, i128),
 }
 
 #[derive(Clone, Debug)]
@@ -33,9 +122,19 @@ pub enum BurnResult {
 #[derive(Clone, Debug)]
 #[contracttype]
 pub struct BatchTransferResult {
+    /// Token the batch was executed with
+    pub token: Address,
     pub total_requests: u32,
     pub successful: u32,
     pub failed: u32,
+    /// Requests that were processed (`successful + failed`)
+    pub attempted: u32,
+    /// Requests skipped without being processed (excluding merged requests).
+    /// No batch path skips requests yet, so this is always 0; it is reserved
+    /// for early-abort, failure-threshold and range execution.
+    pub not_attempted: u32,
+    /// Duplicate-recipient requests folded into an earlier request
+    pub merged_count: u32,
     pub total_transferred: i128,
     pub results: Vec<TransferResult>,
 }
@@ -50,6 +149,279 @@ pub struct BatchBurnResult {
     pub results: Vec<BurnResult>,
 }
 
+/// Maximum volume that may be transferred per window of `window_ledgers` ledgers.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct RateLimit {
+    pub max_volume: i128,
+    pub window_ledgers: u32,
+}
+
+/// Volume consumed in the rate-limit window starting at `start_ledger`.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct RateLimitWindow {
+    pub start_ledger: u32,
+    pub used: i128,
+}
+
+/// Volume an operator has submitted, or a wallet owner has spent, on day
+/// `day` (ledger / `LEDGERS_PER_DAY`).
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct OperatorUsage {
+    pub day: u32,
+    pub used: i128,
+}
+
+/// Lifetime stats of a single token. `mints` is always 0 since this
+/// contract never mints.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TokenStats {
+    pub volume: i128,
+    pub transfers: u64,
+    pub burns: i128,
+    pub mints: i128,
+    pub fees_accrued: i128,
+    pub custody_locked: i128,
+}
+
+/// Summary of a completed batch, kept per batch id.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct BatchRecord {
+    pub batch_id: u64,
+    pub sender: Address,
+    pub token: Address,
+    pub total_requests: u32,
+    pub successful: u32,
+    pub failed: u32,
+    pub total_transferred: i128,
+    pub ledger: u32,
+    pub timestamp: u64,
+}
+
+/// Totals accumulated over one stats period, closed by `reset_stats`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct StatSnapshot {
+    /// Ledger the period was closed in
+    pub reset_ledger: u32,
+    pub batches: u64,
+    pub transfers_processed: u64,
+    pub volume: i128,
+    pub fees: i128,
+}
+
+/// Funds held in custody until `recipient` claims them or they expire.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct Claim {
+    pub sender: Address,
+    pub recipient: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub expires_ledger: u32,
+}
+
+/// Lifecycle of a reversible transfer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum ReversalState {
+    /// Held in custody while the dispute window is open or until settled
+    Pending,
+    /// Paid out to the recipient
+    Settled,
+    /// Returned to the sender within the dispute window
+    Reversed,
+}
+
+/// Funds held in custody until `settle_ledger`, reversible by `sender`
+/// before then.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct ReversibleTransfer {
+    pub sender: Address,
+    pub recipient: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub settle_ledger: u32,
+    pub state: ReversalState,
+}
+
+/// Tokens paid to `recipient` at `rate_per_ledger` from `start_ledger` until
+/// `stop_ledger`, see `open_stream`.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct Stream {
+    pub sender: Address,
+    pub recipient: Address,
+    pub token: Address,
+    pub rate_per_ledger: i128,
+    pub start_ledger: u32,
+    pub stop_ledger: u32,
+    /// Ledger up to which the recipient has been paid
+    pub withdrawn_ledger: u32,
+}
+
+/// A batch stored for execution once `execute_after_ledger` is reached.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct ScheduledBatch {
+    pub submitter: Address,
+    pub token: Address,
+    pub transfers: Vec<TransferRequest>,
+    pub execute_after_ledger: u32,
+}
+
+/// Kind of an outstanding operation reported by `list_pending`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum PendingKind {
+    ScheduledBatch,
+    TransferSchedule,
+    Claim,
+    Reversible,
+    Stream,
+}
+
+/// An operation that has not been finalized yet, see `list_pending`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct PendingOp {
+    pub kind: PendingKind,
+    pub id: u64,
+    /// Amount still to be paid out
+    pub amount: i128,
+    /// Ledger the operation unlocks at; for claims, the ledger they expire
+    /// at, and for streams, the ledger they stop at
+    pub ledger: u32,
+}
+
+/// A single transfer released from custody once `release_ledger` is reached.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct ScheduledTransfer {
+    pub recipient: Address,
+    pub amount: i128,
+    pub release_ledger: u32,
+}
+
+/// Transfers locked in custody by `sender`, released item by item.
+///
+/// `released[i]` records whether `transfers[i]` has been paid out.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct TransferSchedule {
+    pub sender: Address,
+    pub token: Address,
+    pub transfers: Vec<ScheduledTransfer>,
+    pub released: Vec<bool>,
+}
+
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct PendingFeeCollector {
+    pub collector: Address,
+    pub unlock_ledger: u32,
+}
+
+/// Every role and flag this contract holds for one address, see
+/// `lookup_address`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct AddressInfo {
+    pub is_admin: bool,
+    pub is_operator: bool,
+    pub is_fee_collector: bool,
+    pub is_allowlisted: bool,
+    pub is_blocked_token: bool,
+    pub is_suspended: bool,
+    /// Wallet id in the configured wallet registry, if any
+    pub wallet_id: Option<u64>,
+}
+
+/// Wallet creation request in the wallet registry's `batch_create_wallets`
+/// format.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct RegistryWalletRequest {
+    pub owner: Address,
+    pub guardians: Vec<Address>,
+    pub active: bool,
+}
+
+/// Outcome of `batch_transfer_ensure_wallets`.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct EnsureWalletsResult {
+    /// Whether a wallet was created for each request's recipient, in order
+    pub wallets_created: Vec<bool>,
+    pub transfers: BatchTransferResult,
+}
+
+/// Wallet record as returned by the wallet registry's `get_wallet`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct RegistryWallet {
+    pub id: u64,
+    pub owner: Address,
+    pub created_at: u64,
+}
+
+/// Snapshot of every governance-related address, see `get_governance`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Governance {
+    pub owner: Address,
+    pub pending_admin: Option<Address>,
+    pub operators: Vec<Address>,
+    pub fee_collector: Option<Address>,
+    /// Reserved for price-aware limits; no oracle is configurable yet
+    pub price_oracle: Option<Address>,
+    /// Reserved for a linked transfer contract; none is configurable yet
+    pub transfer_contract: Option<Address>,
+}
+
+/// Order in which `batch_transfer_ordered` executes transfers. Results are
+/// always returned in input order.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum OrderMode {
+    /// Execute in the order given
+    AsGiven,
+    /// Execute smallest amounts first (earliest on ties)
+    AscendingAmount,
+    /// Execute largest amounts first (earliest on ties)
+    DescendingAmount,
+}
+
+/// Controls where rounding dust lands when an amount is split into shares.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum RoundingMode {
+    /// All dust goes to the first share
+    RemainderToFirst,
+    /// All dust goes to the last share
+    RemainderToLast,
+    /// All dust goes to the largest share (earliest on ties)
+    RemainderToLargest,
+    /// Dust is handed out one unit at a time to the shares whose rounded-off
+    /// fraction is largest (earliest on ties), approximating round-half-even
+    BankersApprox,
+}
+
+/// Controls how per-item outcomes are reported through events.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum EventMode {
+    /// One `transfer` event per item
+    Full,
+    /// A single `batch_log` event per batch, see `encode_batch_log`
+    Compressed,
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
@@ -57,6 +429,110 @@ pub enum DataKey {
     TotalBatches,
     TotalTransfersProcessed,
     TotalVolumeTransferred,
+    FeeBps,
+    FeeCollector,
+    TotalFeesCollected,
+    PendingFeeCollector,
+    EventMode,
+    BlockedToken(Address),
+    BatchResult(BytesN<32>),
+    TokenVolume(Address),
+    TokenDecimals(Address),
+    VerifyDeltas,
+    Paused,
+    SenderAllowlistEnabled,
+    AllowedSender(Address),
+    LastBatchLedger,
+    LastBatchTimestamp,
+    ExpectedDecimals,
+    Operators,
+    NextScheduleId,
+    ScheduledBatch(u64),
+    MergeDuplicates,
+    RateLimit,
+    RateLimitWindow,
+    NextClaimId,
+    Claim(u64),
+    TotalLocked(Address),
+    RecipientCap(Address),
+    RecipientReceived(Address),
+    Batch(u64),
+    SuspendedSender(Address),
+    LifetimeCap,
+    TransferSchedule(u64),
+    UniqueMemos,
+    StorageVersion,
+    Callback,
+    ConfigFrozen,
+    TokenTransfers(Address),
+    TokenBurned(Address),
+    TokenFees(Address),
+    WalletRegistry,
+    LargeTransferThreshold,
+    LastBatchResult,
+    PendingAdmin,
+    OperatorLimit(Address),
+    OperatorUsage(Address),
+    HasReceived(Address),
+    UniqueRecipients,
+}
+
+/// Storage keys added after `DataKey` reached the 50-variant limit of
+/// contract type enums.
+#[derive(Clone)]
+#[contracttype]
+pub enum ExtDataKey {
+    BatchCooldown,
+    LastBatchLedger(Address),
+    TokenFee(Address),
+    NextReversibleId,
+    Reversible(u64),
+    FailureCount(u32),
+    StatSnapshots,
+    StatsBaseline,
+    WalletSpendLimit(Address, Address),
+    WalletSpend(Address, Address),
+    NextStreamId,
+    Stream(u64),
+    WarnOnDrain,
+    TrimTrailingZeros,
+    RecipientClaims(Address),
+}
+
+/// Encodes the outcomes of a batch into the compact `batch_log` format.
+///
+/// Layout (all integers big-endian):
+/// - `u32` number of items `n`
+/// - `ceil(n / 8)` bytes of success bits; item `i` is bit `i % 8` (LSB first)
+///   of byte `i / 8`, set when the item succeeded
+/// - for each failed item in input order: `u32` index followed by `u32` error code
+///
+/// Partial deliveries leave their success bit clear and are listed with
+/// `ErrorCode::PARTIAL_DELIVERY`.
+pub fn encode_batch_log(env: &Env, results: &Vec<TransferResult>) -> Bytes {
+    let count = results.len();
+    let mut log = Bytes::new(env);
+    log.extend_from_array(&count.to_be_bytes());
+
+    let mut bitset = [0u8; (MAX_BATCH_SIZE as usize).div_ceil(8)];
+    let mut failures = Bytes::new(env);
+    for (i, result) in results.iter().enumerate() {
+        match result {
+            TransferResult::Success(_, _) => bitset[i / 8] |= 1 << (i % 8),
+            TransferResult::Failure(_, _, code) => {
+                failures.extend_from_array(&(i as u32).to_be_bytes());
+                failures.extend_from_array(&code.to_be_bytes());
+            }
+            TransferResult::PartialDelivery(_, _, _) => {
+                failures.extend_from_array(&(i as u32).to_be_bytes());
+                failures.extend_from_array(&ErrorCode::PARTIAL_DELIVERY.to_be_bytes());
+            }
+        }
+    }
+
+    log.extend_from_slice(&bitset[..(count as usize).div_ceil(8)]);
+    log.append(&failures);
+    log
 }
 
 pub struct TransferEvents;
@@ -76,6 +552,50 @@ impl TransferEvents {
         env.events().publish(topics, (recipient.clone(), amount));
     }
 
+    pub fn large_transfer(
+        env: &Env,
+        batch_id: u64,
+        from: &Address,
+        recipient: &Address,
+        amount: i128,
+    ) {
+        let topics = (symbol_short!("transfer"), symbol_short!("large"), batch_id);
+        env.events()
+            .publish(topics, (from.clone(), recipient.clone(), amount));
+    }
+
+    pub fn stats_reset(env: &Env, snapshot: &StatSnapshot) {
+        let topics = (symbol_short!("stats"), symbol_short!("reset"));
+        env.events().publish(topics, snapshot.clone());
+    }
+
+    pub fn callback_failed(env: &Env, batch_id: u64, callback: &Address) {
+        let topics = (symbol_short!("callback"), symbol_short!("failed"), batch_id);
+        env.events().publish(topics, callback.clone());
+    }
+
+    pub fn transfer_memo(env: &Env, batch_id: u64, recipient: &Address, memo: &String) {
+        let topics = (symbol_short!("transfer"), symbol_short!("memo"), batch_id);
+        env.events()
+            .publish(topics, (recipient.clone(), memo.clone()));
+    }
+
+    pub fn transfer_partial(
+        env: &Env,
+        batch_id: u64,
+        recipient: &Address,
+        requested: i128,
+        delivered: i128,
+    ) {
+        let topics = (
+            symbol_short!("transfer"),
+            symbol_short!("partial"),
+            batch_id,
+        );
+        env.events()
+            .publish(topics, (recipient.clone(), requested, delivered));
+    }
+
     pub fn transfer_failure(
         env: &Env,
         batch_id: u64,
@@ -92,6 +612,11 @@ impl TransferEvents {
             .publish(topics, (recipient.clone(), requested_amount, error_code));
     }
 
+    pub fn batch_log(env: &Env, batch_id: u64, log: &Bytes) {
+        let topics = (symbol_short!("batch"), symbol_short!("log"), batch_id);
+        env.events().publish(topics, log.clone());
+    }
+
     pub fn batch_completed(
         env: &Env,
         batch_id: u64,
@@ -104,6 +629,210 @@ impl TransferEvents {
             .publish(topics, (successful, failed, total_transferred));
     }
 
+    pub fn fee_collected(env: &Env, batch_id: u64, collector: &Address, amount: i128) {
+        let topics = (symbol_short!("fee"), symbol_short!("collected"), batch_id);
+        env.events().publish(topics, (collector.clone(), amount));
+    }
+
+    pub fn sender_drained(env: &Env, batch_id: u64, sender: &Address, token: &Address) {
+        let topics = (symbol_short!("sender"), symbol_short!("drained"), batch_id);
+        env.events()
+            .publish(topics, (sender.clone(), token.clone()));
+    }
+
+    pub fn fee_transfer_failed(env: &Env, batch_id: u64, collector: &Address, amount: i128) {
+        let topics = (symbol_short!("fee"), symbol_short!("failed"), batch_id);
+        env.events().publish(topics, (collector.clone(), amount));
+    }
+
+    pub fn fee_collector_proposed(env: &Env, collector: &Address, unlock_ledger: u32) {
+        let topics = (symbol_short!("fee"), symbol_short!("proposed"));
+        env.events()
+            .publish(topics, (collector.clone(), unlock_ledger));
+    }
+
+    pub fn fee_collector_applied(env: &Env, collector: &Address) {
+        let topics = (symbol_short!("fee"), symbol_short!("applied"));
+        env.events().publish(topics, collector.clone());
+    }
+
+    pub fn batch_scheduled(
+        env: &Env,
+        schedule_id: u64,
+        submitter: &Address,
+        execute_after_ledger: u32,
+    ) {
+        let topics = (
+            symbol_short!("schedule"),
+            symbol_short!("created"),
+            schedule_id,
+        );
+        env.events()
+            .publish(topics, (submitter.clone(), execute_after_ledger));
+    }
+
+    pub fn schedule_cancelled(env: &Env, schedule_id: u64, cancelled_by: &Address) {
+        let topics = (
+            symbol_short!("schedule"),
+            symbol_short!("cancelled"),
+            schedule_id,
+        );
+        env.events().publish(topics, cancelled_by.clone());
+    }
+
+    pub fn transfers_scheduled(env: &Env, schedule_id: u64, sender: &Address, total: i128) {
+        let topics = (
+            symbol_short!("schedule"),
+            symbol_short!("locked"),
+            schedule_id,
+        );
+        env.events().publish(topics, (sender.clone(), total));
+    }
+
+    pub fn scheduled_released(
+        env: &Env,
+        schedule_id: u64,
+        index: u32,
+        recipient: &Address,
+        amount: i128,
+    ) {
+        let topics = (
+            symbol_short!("schedule"),
+            symbol_short!("released"),
+            schedule_id,
+        );
+        env.events()
+            .publish(topics, (index, recipient.clone(), amount));
+    }
+
+    pub fn schedule_executed(env: &Env, schedule_id: u64, batch_id: u64) {
+        let topics = (
+            symbol_short!("schedule"),
+            symbol_short!("executed"),
+            schedule_id,
+        );
+        env.events().publish(topics, batch_id);
+    }
+
+    pub fn claim_created(env: &Env, claim_id: u64, recipient: &Address, amount: i128) {
+        let topics = (symbol_short!("claim"), symbol_short!("created"), claim_id);
+        env.events().publish(topics, (recipient.clone(), amount));
+    }
+
+    pub fn claim_settled(env: &Env, claim_id: u64, recipient: &Address, amount: i128) {
+        let topics = (symbol_short!("claim"), symbol_short!("claimed"), claim_id);
+        env.events().publish(topics, (recipient.clone(), amount));
+    }
+
+    pub fn claim_reclaimed(env: &Env, claim_id: u64, sender: &Address, amount: i128) {
+        let topics = (symbol_short!("claim"), symbol_short!("reclaimed"), claim_id);
+        env.events().publish(topics, (sender.clone(), amount));
+    }
+
+    pub fn reversible_created(
+        env: &Env,
+        transfer_id: u64,
+        recipient: &Address,
+        amount: i128,
+        settle_ledger: u32,
+    ) {
+        let topics = (
+            symbol_short!("dispute"),
+            symbol_short!("locked"),
+            transfer_id,
+        );
+        env.events()
+            .publish(topics, (recipient.clone(), amount, settle_ledger));
+    }
+
+    pub fn stream_opened(env: &Env, stream_id: u64, stream: &Stream) {
+        let topics = (symbol_short!("stream"), symbol_short!("opened"), stream_id);
+        env.events().publish(
+            topics,
+            (
+                stream.recipient.clone(),
+                stream.rate_per_ledger,
+                stream.start_ledger,
+                stream.stop_ledger,
+            ),
+        );
+    }
+
+    pub fn stream_withdrawn(env: &Env, stream_id: u64, recipient: &Address, amount: i128) {
+        let topics = (
+            symbol_short!("stream"),
+            symbol_short!("withdrawn"),
+            stream_id,
+        );
+        env.events().publish(topics, (recipient.clone(), amount));
+    }
+
+    pub fn stream_closed(env: &Env, stream_id: u64, paid: i128, refunded: i128) {
+        let topics = (symbol_short!("stream"), symbol_short!("closed"), stream_id);
+        env.events().publish(topics, (paid, refunded));
+    }
+
+    pub fn reversible_settled(env: &Env, transfer_id: u64, recipient: &Address, amount: i128) {
+        let topics = (
+            symbol_short!("dispute"),
+            symbol_short!("settled"),
+            transfer_id,
+        );
+        env.events().publish(topics, (recipient.clone(), amount));
+    }
+
+    pub fn reversible_reversed(env: &Env, transfer_id: u64, sender: &Address, amount: i128) {
+        let topics = (
+            symbol_short!("dispute"),
+            symbol_short!("reversed"),
+            transfer_id,
+        );
+        env.events().publish(topics, (sender.clone(), amount));
+    }
+
+    pub fn token_blocked(env: &Env, token: &Address) {
+        let topics = (symbol_short!("token"), symbol_short!("blocked"));
+        env.events().publish(topics, token.clone());
+    }
+
+    pub fn token_unblocked(env: &Env, token: &Address) {
+        let topics = (symbol_short!("token"), symbol_short!("unblocked"));
+        env.events().publish(topics, token.clone());
+    }
+
+    pub fn operator_added(env: &Env, operator: &Address) {
+        let topics = (symbol_short!("operator"), symbol_short!("added"));
+        env.events().publish(topics, operator.clone());
+    }
+
+    pub fn operator_removed(env: &Env, operator: &Address) {
+        let topics = (symbol_short!("operator"), symbol_short!("removed"));
+        env.events().publish(topics, operator.clone());
+    }
+
+    pub fn paused(env: &Env, admin: &Address) {
+        let topics = (symbol_short!("contract"), symbol_short!("paused"));
+        env.events()
+            .publish(topics, (admin.clone(), env.ledger().sequence()));
+    }
+
+    pub fn config_frozen(env: &Env, admin: &Address) {
+        let topics = (symbol_short!("config"), symbol_short!("frozen"));
+        env.events()
+            .publish(topics, (admin.clone(), env.ledger().sequence()));
+    }
+
+    pub fn auto_paused(env: &Env, total_volume: i128, cap: i128) {
+        let topics = (symbol_short!("contract"), symbol_short!("autopause"));
+        env.events().publish(topics, (total_volume, cap));
+    }
+
+    pub fn unpaused(env: &Env, admin: &Address) {
+        let topics = (symbol_short!("contract"), symbol_short!("unpaused"));
+        env.events()
+            .publish(topics, (admin.clone(), env.ledger().sequence()));
+    }
+
     pub fn burn_success(env: &Env, batch_id: u64, owner: &Address, amount: i128) {
         let topics = (symbol_short!("burn"), symbol_short!("success"), batch_id);
         env.events().publish(topics, (owner.clone(), amount));
@@ -117,10 +846,8 @@ impl TransferEvents {
         error_code: u32,
     ) {
         let topics = (symbol_short!("burn"), symbol_short!("failure"), batch_id);
-        env.events().publish(
-            topics,
-            (owner.clone(), requested_amount, error_code),
-        );
+        env.events()
+            .publish(topics, (owner.clone(), requested_amount, error_code));
     }
 
     pub fn burn_batch_completed(
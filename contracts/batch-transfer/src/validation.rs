@@ -15,7 +15,9 @@ pub fn validate_address(_env: &Env, _address: &Address) -> Result<(), Validation
 }
 
 /// Validates a transfer amount.
-/// Ensures the amount is positive and within reasonable bounds.
+/// Ensures the amount is positive and within reasonable bounds. This also
+/// rejects `i128::MIN`, the one value whose negation overflows, so validated
+/// amounts can be safely negated.
 pub fn validate_amount(amount: i128) -> Result<(), ValidationError> {
     // Amount must be positive
     if amount <= 0 {
@@ -42,6 +44,14 @@ mod tests {
         assert_eq!(validate_amount(-1000), Err(ValidationError::InvalidAmount));
     }
 
+    #[test]
+    fn test_validate_amount_min() {
+        assert_eq!(
+            validate_amount(i128::MIN),
+            Err(ValidationError::InvalidAmount)
+        );
+    }
+
     #[test]
     fn test_validate_amount_zero() {
         assert_eq!(validate_amount(0), Err(ValidationError::InvalidAmount));
@@ -5,14 +5,21 @@ mod types;
 mod validation;
 
 use soroban_sdk::{
-    contract, contractimpl, panic_with_error, Address, Env, Vec,
+    contract, contractimpl, panic_with_error, xdr::ToXdr, Address, Bytes, BytesN, Env, Map,
+    String, Vec,
 };
 
 pub use crate::types::{
-    BatchCreateResult, BatchRecoveryResult, DataKey, Wallet, WalletCreateRequest,
-    WalletCreateResult, WalletEvents, WalletRecoveryRequest, WalletRecoveryResult, MAX_BATCH_SIZE,
+    BatchCreateResult, BatchDeleteResult, BatchFreezeResult, BatchLinkResult, BatchRecoveryResult,
+    DataKey, ExternalIdLinkResult, PendingRecovery, RecoveryWithGuardians, Wallet, WalletConfig,
+    WalletCreateRequest, WalletCreateResult, WalletDeleteResult, WalletEvents, WalletFreezeResult,
+    WalletProfile, WalletRecoveryRequest, WalletRecoveryResult, DEFAULT_MAX_GUARDIANS,
+    EXTERNAL_PREFIX_LEN, MAX_BATCH_SIZE, RESULT_TTL_EXTEND, RESULT_TTL_THRESHOLD, WALLET_TTL_EXTEND,
+    WALLET_TTL_THRESHOLD,
+};
+use crate::validation::{
+    is_contract_address, is_deleted, is_frozen, validate_address, wallet_exists, wallet_live,
 };
-use crate::validation::{validate_address, wallet_exists};
 
 /// Error codes for the batch wallet creation contract.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -28,6 +35,38 @@ pub enum BatchWalletError {
     EmptyBatch = 4,
     /// Batch exceeds maximum size
     BatchTooLarge = 5,
+    /// No wallet exists for the owner
+    WalletNotFound = 6,
+    /// External id is linked to another wallet
+    ExternalIdTaken = 7,
+    /// Wallet id is assigned to another wallet
+    IdTaken = 8,
+    /// Operation is only allowed while migration mode is enabled
+    MigrationInactive = 9,
+    /// Wallet creation is paused
+    CreationPaused = 10,
+    /// Wallet recovery is paused
+    RecoveryPaused = 11,
+    /// Wallet id was not reserved or has already been used
+    IdNotReserved = 12,
+    /// Owner address is invalid or already has a wallet
+    WalletAlreadyExists = 13,
+    /// Maximum number of wallets reached
+    WalletLimitReached = 14,
+    /// Wallet does not meet the guardian policy
+    GuardiansRequired = 15,
+    /// Wallet id is outside the assignable range
+    InvalidWalletId = 16,
+    /// Id offset can only be set before any id is assigned
+    IdOffsetLocked = 17,
+    /// Guardian list is longer than the configured maximum
+    TooManyGuardians = 18,
+    /// Recovery request fails validation
+    RecoveryRejected = 19,
+    /// No recovery is pending for the wallet
+    NoPendingRecovery = 20,
+    /// Pending recovery is still timelocked
+    RecoveryLocked = 21,
 }
 
 impl From<BatchWalletError> for soroban_sdk::Error {
@@ -50,6 +89,7 @@ impl BatchWalletContract {
         env.storage().instance().set(&DataKey::Admin, &admin);
         env.storage().instance().set(&DataKey::TotalBatches, &0u64);
         env.storage().instance().set(&DataKey::TotalWalletsCreated, &0u64);
+        env.storage().instance().set(&DataKey::NextWalletId, &1u64);
     }
 
     /// Executes batch creation of wallets for multiple owners.
@@ -61,13 +101,16 @@ impl BatchWalletContract {
         // Verify authorization
         caller.require_auth();
         Self::require_admin(&env, &caller);
+        if Self::is_creation_paused(env.clone()) {
+            panic_with_error!(&env, BatchWalletError::CreationPaused);
+        }
 
         // Validate batch size
         let request_count = requests.len();
         if request_count == 0 {
             panic_with_error!(&env, BatchWalletError::EmptyBatch);
         }
-        if request_count > MAX_BATCH_SIZE {
+        if request_count > Self::max_batch_size(&env) {
             panic_with_error!(&env, BatchWalletError::BatchTooLarge);
         }
 
@@ -84,15 +127,28 @@ impl BatchWalletContract {
 
         // Initialize result vectors
         let mut results: Vec<WalletCreateResult> = Vec::new(&env);
+        let mut owner_ids: Vec<(Address, u64)> = Vec::new(&env);
         let mut successful_count: u32 = 0;
         let mut failed_count: u32 = 0;
 
-        // Get current total wallets for ID assignment
-        let mut next_wallet_id: u64 = env
+        // Get the next id to assign and the number of wallets created so far
+        let mut next_wallet_id = Self::next_wallet_id(&env);
+        let created_before: u64 = env
             .storage()
             .instance()
             .get(&DataKey::TotalWalletsCreated)
-            .unwrap_or(0) + 1;
+            .unwrap_or(0);
+        let max_wallets: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::MaxWallets)
+            .unwrap_or(0);
+        let min_guardians: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::MinGuardians)
+            .unwrap_or(0);
+        let max_guardians = Self::get_max_guardians(env.clone());
 
         // Process each request
         for request in requests.iter() {
@@ -109,6 +165,21 @@ impl BatchWalletContract {
                 is_valid = false;
                 error_code = 1; // Wallet already exists
             }
+            // Check the wallet cap
+            else if max_wallets > 0 && created_before + successful_count as u64 >= max_wallets {
+                is_valid = false;
+                error_code = 2; // Wallet limit reached
+            }
+            // Check the guardian policy
+            else if request.guardians.len() < min_guardians {
+                is_valid = false;
+                error_code = 3; // Guardians required
+            }
+            // Check the guardian limit
+            else if request.guardians.len() > max_guardians {
+                is_valid = false;
+                error_code = 4; // Too many guardians
+            }
 
             if !is_valid {
                 // Validation failed - record and continue
@@ -134,13 +205,19 @@ impl BatchWalletContract {
             };
 
             // Store wallet
-            env.storage().persistent().set(&DataKey::Wallets(request.owner.clone()), &wallet);
+            Self::store_wallet(&env, &wallet, &request.guardians);
+            if !request.active {
+                env.storage()
+                    .persistent()
+                    .set(&DataKey::Inactive(request.owner.clone()), &true);
+            }
 
             // Increment ID
             next_wallet_id += 1;
 
             // Record success
             results.push_back(WalletCreateResult::Success(request.owner.clone()));
+            owner_ids.push_back((request.owner.clone(), wallet.id));
             successful_count += 1;
 
             WalletEvents::wallet_created(
@@ -157,18 +234,16 @@ impl BatchWalletContract {
             .instance()
             .get(&DataKey::TotalBatches)
             .unwrap_or(0);
-        let total_created: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::TotalWalletsCreated)
-            .unwrap_or(0);
 
         env.storage()
             .instance()
             .set(&DataKey::TotalBatches, &(total_batches + 1));
         env.storage()
             .instance()
-            .set(&DataKey::TotalWalletsCreated, &(total_created + successful_count as u64));
+            .set(&DataKey::TotalWalletsCreated, &(created_before + successful_count as u64));
+        env.storage()
+            .instance()
+            .set(&DataKey::NextWalletId, &next_wallet_id);
 
         // Emit batch completed event
         WalletEvents::batch_completed(
@@ -183,22 +258,236 @@ impl BatchWalletContract {
             successful: successful_count,
             failed: failed_count,
             results,
+            owner_ids,
+        }
+    }
+
+    /// Starts automatic id assignment at `start_id` so deployments can keep
+    /// disjoint id spaces. Only allowed before any id is assigned.
+    pub fn set_id_offset(env: Env, caller: Address, start_id: u64) {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+        if Self::next_wallet_id(&env) != 1 {
+            panic_with_error!(&env, BatchWalletError::IdOffsetLocked);
+        }
+        if start_id == 0 || start_id == u64::MAX {
+            panic_with_error!(&env, BatchWalletError::InvalidWalletId);
+        }
+
+        env.storage().instance().set(&DataKey::NextWalletId, &start_id);
+        env.storage().instance().set(&DataKey::MinWalletId, &start_id);
+    }
+
+    /// Reserves `count` consecutive wallet ids without creating wallets.
+    ///
+    /// Reserved ids are skipped by `batch_create_wallets` and can later be
+    /// materialized, in any order, with `create_wallet_with_id`.
+    pub fn reserve_ids(env: Env, caller: Address, count: u32) -> Vec<u64> {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+        if count == 0 {
+            panic_with_error!(&env, BatchWalletError::EmptyBatch);
+        }
+        if count > MAX_BATCH_SIZE {
+            panic_with_error!(&env, BatchWalletError::BatchTooLarge);
+        }
+
+        let first_id = Self::next_wallet_id(&env);
+        let mut ids: Vec<u64> = Vec::new(&env);
+        for id in first_id..first_id + count as u64 {
+            env.storage().persistent().set(&DataKey::ReservedId(id), &true);
+            ids.push_back(id);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::NextWalletId, &(first_id + count as u64));
+
+        ids
+    }
+
+    /// Creates a wallet for `owner` under a previously reserved id.
+    ///
+    /// Fails with `IdNotReserved` if `id` was never reserved or is already used.
+    /// During migration any free id may be assigned; the auto-assignment
+    /// counter then moves past it so batch creation never reuses it.
+    pub fn create_wallet_with_id(env: Env, caller: Address, owner: Address, id: u64) {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+        if Self::is_creation_paused(env.clone()) {
+            panic_with_error!(&env, BatchWalletError::CreationPaused);
+        }
+
+        if id == 0 || id == u64::MAX {
+            panic_with_error!(&env, BatchWalletError::InvalidWalletId);
+        }
+        let reserved_key = DataKey::ReservedId(id);
+        if !env.storage().persistent().has(&reserved_key) && !Self::is_migration_mode(env.clone())
+        {
+            panic_with_error!(&env, BatchWalletError::IdNotReserved);
+        }
+        if env.storage().persistent().has(&DataKey::WalletById(id)) {
+            panic_with_error!(&env, BatchWalletError::IdTaken);
+        }
+        if validate_address(&owner).is_err() || wallet_exists(&env, &owner) {
+            panic_with_error!(&env, BatchWalletError::WalletAlreadyExists);
+        }
+        let total_created = Self::get_total_wallets_created(env.clone());
+        let max_wallets: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::MaxWallets)
+            .unwrap_or(0);
+        if max_wallets > 0 && total_created >= max_wallets {
+            panic_with_error!(&env, BatchWalletError::WalletLimitReached);
+        }
+        if Self::get_guardian_policy(env.clone()) > 0 {
+            panic_with_error!(&env, BatchWalletError::GuardiansRequired);
+        }
+
+        let wallet = Wallet {
+            id,
+            owner: owner.clone(),
+            created_at: env.ledger().timestamp(),
+        };
+        Self::store_wallet(&env, &wallet, &Vec::new(&env));
+        env.storage().persistent().remove(&reserved_key);
+        Self::advance_id_counter(&env, id);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalWalletsCreated, &(total_created + 1));
+
+        // Batch id 0 marks a wallet created outside of a batch
+        WalletEvents::wallet_created(&env, 0, &owner, id);
+    }
+
+    /// Sets how many ledgers a recovery started with `initiate_recovery`
+    /// must wait before it can be executed.
+    pub fn set_recovery_delay(env: Env, caller: Address, ledgers: u32) {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+
+        env.storage().instance().set(&DataKey::RecoveryDelay, &ledgers);
+    }
+
+    /// Returns the recovery timelock in ledgers.
+    pub fn get_recovery_delay(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::RecoveryDelay)
+            .unwrap_or(0)
+    }
+
+    /// Starts a timelocked recovery of the wallet of `old_owner` to
+    /// `new_owner`, replacing any recovery already pending for it.
+    ///
+    /// The owner can cancel it with `cancel_recovery` until it is executed.
+    /// While a recovery delay is set, batch recoveries of the wallet fail
+    /// with error code `6` unless they match this recovery once unlocked.
+    pub fn initiate_recovery(env: Env, caller: Address, old_owner: Address, new_owner: Address) {
+        caller.require_auth();
+        Self::require_recovery_admin(&env, &caller);
+        if Self::is_recovery_paused(env.clone()) {
+            panic_with_error!(&env, BatchWalletError::RecoveryPaused);
+        }
+        Self::require_recoverable(&env, &old_owner, &new_owner);
+
+        let unlock_ledger = env
+            .ledger()
+            .sequence()
+            .saturating_add(Self::get_recovery_delay(env.clone()));
+        env.storage().persistent().set(
+            &DataKey::PendingRecovery(old_owner.clone()),
+            &PendingRecovery {
+                new_owner: new_owner.clone(),
+                unlock_ledger,
+            },
+        );
+        WalletEvents::recovery_initiated(&env, &old_owner, &new_owner, unlock_ledger);
+    }
+
+    /// Cancels the recovery pending for the wallet of `owner`.
+    pub fn cancel_recovery(env: Env, owner: Address) {
+        owner.require_auth();
+        let pending = Self::load_pending_recovery(&env, &owner);
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::PendingRecovery(owner.clone()));
+        WalletEvents::recovery_cancelled(&env, &owner, &pending.new_owner);
+    }
+
+    /// Completes the recovery pending for the wallet of `old_owner` once its
+    /// timelock has elapsed.
+    pub fn execute_recovery(env: Env, caller: Address, old_owner: Address) {
+        caller.require_auth();
+        Self::require_recovery_admin(&env, &caller);
+        if Self::is_recovery_paused(env.clone()) {
+            panic_with_error!(&env, BatchWalletError::RecoveryPaused);
+        }
+        let pending = Self::load_pending_recovery(&env, &old_owner);
+        if env.ledger().sequence() < pending.unlock_ledger {
+            panic_with_error!(&env, BatchWalletError::RecoveryLocked);
         }
+        // Revalidate, as either wallet may have changed since initiation
+        Self::require_recoverable(&env, &old_owner, &pending.new_owner);
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::PendingRecovery(old_owner.clone()));
+        let wallet_id = Self::reassign_wallet(&env, &old_owner, &pending.new_owner);
+        // Batch id 0 marks a recovery made outside of a batch
+        WalletEvents::wallet_recovered(&env, 0, &old_owner, &pending.new_owner, wallet_id);
     }
 
+    /// Returns the target owner and unlock ledger of the recovery pending
+    /// for the wallet of `old_owner`, if any.
+    pub fn get_pending_recovery(env: Env, old_owner: Address) -> Option<(Address, u32)> {
+        env.storage()
+            .persistent()
+            .get::<_, PendingRecovery>(&DataKey::PendingRecovery(old_owner))
+            .map(|pending| (pending.new_owner, pending.unlock_ledger))
+    }
+
+    /// Executes batch recovery of wallets to new owners.
+    ///
+    /// When `recovery_key` is given and a batch with the same key already ran,
+    /// the stored result is returned without re-executing, so a retried
+    /// migration cannot re-own wallets twice. While a recovery delay is set,
+    /// each request must match an unlocked `initiate_recovery` or it fails
+    /// with error code `6`.
     pub fn batch_recover_wallets(
         env: Env,
         caller: Address,
         requests: Vec<WalletRecoveryRequest>,
+        recovery_key: Option<BytesN<32>>,
     ) -> BatchRecoveryResult {
         caller.require_auth();
-        Self::require_admin(&env, &caller);
+        Self::require_recovery_admin(&env, &caller);
+        if Self::is_recovery_paused(env.clone()) {
+            panic_with_error!(&env, BatchWalletError::RecoveryPaused);
+        }
+
+        if let Some(key) = &recovery_key {
+            let storage_key = DataKey::RecoveryResult(key.clone());
+            if let Some(stored) = env
+                .storage()
+                .persistent()
+                .get::<_, BatchRecoveryResult>(&storage_key)
+            {
+                env.storage().persistent().extend_ttl(
+                    &storage_key,
+                    RESULT_TTL_THRESHOLD,
+                    RESULT_TTL_EXTEND,
+                );
+                return stored;
+            }
+        }
 
         let request_count = requests.len();
         if request_count == 0 {
             panic_with_error!(&env, BatchWalletError::EmptyBatch);
         }
-        if request_count > MAX_BATCH_SIZE {
+        if request_count > Self::max_recovery_batch_size(&env) {
             panic_with_error!(&env, BatchWalletError::BatchTooLarge);
         }
 
@@ -211,28 +500,28 @@ impl BatchWalletContract {
 
         WalletEvents::recovery_started(&env, batch_id, request_count);
 
+        let allow_contract_destinations: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::AllowContractDestinations)
+            .unwrap_or(false);
+
         let mut results: Vec<WalletRecoveryResult> = Vec::new(&env);
         let mut successful_count: u32 = 0;
         let mut failed_count: u32 = 0;
 
+        let identity_ok = Self::get_identity_recovery_ok(env.clone());
+        let no_overlay: Map<Address, bool> = Map::new(&env);
         for request in requests.iter() {
-            let mut is_valid = true;
-            let mut error_code = 0u32;
-
-            if validate_address(&request.old_owner).is_err()
-                || validate_address(&request.new_owner).is_err()
-            {
-                is_valid = false;
-                error_code = 0;
-            } else if !wallet_exists(&env, &request.old_owner) {
-                is_valid = false;
-                error_code = 1;
-            } else if wallet_exists(&env, &request.new_owner) {
-                is_valid = false;
-                error_code = 2;
+            let error =
+                Self::recovery_error(&env, &request, allow_contract_destinations, &no_overlay)
+                    .or_else(|| Self::timelock_error(&env, &request));
+            if Self::is_identity_noop(identity_ok, &request, error) {
+                results.push_back(WalletRecoveryResult::NoOp(request.old_owner.clone()));
+                successful_count += 1;
+                continue;
             }
-
-            if !is_valid {
+            if let Some(error_code) = error {
                 results.push_back(WalletRecoveryResult::Failure(
                     request.old_owner.clone(),
                     request.new_owner.clone(),
@@ -249,19 +538,7 @@ impl BatchWalletContract {
                 continue;
             }
 
-            let mut wallet: Wallet = env
-                .storage()
-                .persistent()
-                .get(&DataKey::Wallets(request.old_owner.clone()))
-                .unwrap();
-            wallet.owner = request.new_owner.clone();
-
-            env.storage()
-                .persistent()
-                .set(&DataKey::Wallets(request.new_owner.clone()), &wallet);
-            env.storage()
-                .persistent()
-                .remove(&DataKey::Wallets(request.old_owner.clone()));
+            let wallet_id = Self::reassign_wallet(&env, &request.old_owner, &request.new_owner);
 
             results.push_back(WalletRecoveryResult::Success(
                 request.old_owner.clone(),
@@ -274,7 +551,7 @@ impl BatchWalletContract {
                 batch_id,
                 &request.old_owner,
                 &request.new_owner,
-                wallet.id,
+                wallet_id,
             );
         }
 
@@ -290,49 +567,1473 @@ impl BatchWalletContract {
 
         WalletEvents::recovery_completed(&env, batch_id, successful_count, failed_count);
 
-        BatchRecoveryResult {
+        let result = BatchRecoveryResult {
             total_requests: request_count,
             successful: successful_count,
             failed: failed_count,
             results,
+        };
+
+        if let Some(key) = recovery_key {
+            let storage_key = DataKey::RecoveryResult(key);
+            env.storage().persistent().set(&storage_key, &result);
+            env.storage().persistent().extend_ttl(
+                &storage_key,
+                RESULT_TTL_THRESHOLD,
+                RESULT_TTL_EXTEND,
+            );
         }
-    }
 
-    /// Returns the admin address.
-    pub fn get_admin(env: Env) -> Address {
-        env.storage()
-            .instance()
-            .get(&DataKey::Admin)
-            .expect("Contract not initialized")
+        result
     }
 
-    /// Updates the admin address.
-    pub fn set_admin(env: Env, current_admin: Address, new_admin: Address) {
-        current_admin.require_auth();
-        Self::require_admin(&env, &current_admin);
+    /// Recovers wallets to new owners and installs a fresh guardian set and
+    /// approval threshold on each, e.g. after a guardian key compromise.
+    ///
+    /// Items fail with the usual recovery codes, or code `5` if the guardian
+    /// config is invalid: duplicate or invalid guardians, fewer guardians than
+    /// the guardian policy, or a threshold outside `1..=guardians.len()`.
+    pub fn batch_recover_reset_guardians(
+        env: Env,
+        caller: Address,
+        requests: Vec<RecoveryWithGuardians>,
+    ) -> BatchRecoveryResult {
+        caller.require_auth();
+        Self::require_recovery_admin(&env, &caller);
+        if Self::is_recovery_paused(env.clone()) {
+            panic_with_error!(&env, BatchWalletError::RecoveryPaused);
+        }
 
-        env.storage().instance().set(&DataKey::Admin, &new_admin);
-    }
+        let request_count = requests.len();
+        if request_count == 0 {
+            panic_with_error!(&env, BatchWalletError::EmptyBatch);
+        }
+        if request_count > Self::max_recovery_batch_size(&env) {
+            panic_with_error!(&env, BatchWalletError::BatchTooLarge);
+        }
 
-    /// Returns the total number of batches processed.
-    pub fn get_total_batches(env: Env) -> u64 {
-        env.storage()
+        let batch_id: u64 = env
+            .storage()
             .instance()
             .get(&DataKey::TotalBatches)
             .unwrap_or(0)
-    }
+            + 1;
+
+        WalletEvents::recovery_started(&env, batch_id, request_count);
+
+        let allow_contract_destinations = Self::get_allow_contract_destinations(env.clone());
+        let min_guardians = Self::get_guardian_policy(env.clone());
+        let mut results: Vec<WalletRecoveryResult> = Vec::new(&env);
+        let mut successful_count: u32 = 0;
+        let mut failed_count: u32 = 0;
+
+        let no_overlay: Map<Address, bool> = Map::new(&env);
+        for request in requests.iter() {
+            let recovery = WalletRecoveryRequest {
+                old_owner: request.old_owner.clone(),
+                new_owner: request.new_owner.clone(),
+            };
+            let error =
+                Self::recovery_error(&env, &recovery, allow_contract_destinations, &no_overlay)
+                    .or_else(|| Self::timelock_error(&env, &recovery))
+                    .or_else(|| {
+                        Self::guardian_config_error(
+                            &env,
+                            &request.guardians,
+                            request.threshold,
+                            min_guardians,
+                        )
+                    });
+            if let Some(error_code) = error {
+                results.push_back(WalletRecoveryResult::Failure(
+                    request.old_owner.clone(),
+                    request.new_owner.clone(),
+                    error_code,
+                ));
+                failed_count += 1;
+                WalletEvents::wallet_recovery_failure(
+                    &env,
+                    batch_id,
+                    &request.old_owner,
+                    &request.new_owner,
+                    error_code,
+                );
+                continue;
+            }
+
+            let wallet_id = Self::reassign_wallet(&env, &request.old_owner, &request.new_owner);
+            env.storage()
+                .persistent()
+                .set(&DataKey::Guardians(request.new_owner.clone()), &request.guardians);
+            env.storage().persistent().set(
+                &DataKey::GuardianThreshold(request.new_owner.clone()),
+                &request.threshold,
+            );
+
+            results.push_back(WalletRecoveryResult::Success(
+                request.old_owner.clone(),
+                request.new_owner.clone(),
+            ));
+            successful_count += 1;
+
+            WalletEvents::wallet_recovered(
+                &env,
+                batch_id,
+                &request.old_owner,
+                &request.new_owner,
+                wallet_id,
+            );
+        }
 
-    /// Returns the total number of wallets created.
-    pub fn get_total_wallets_created(env: Env) -> u64 {
         env.storage()
             .instance()
-            .get(&DataKey::TotalWalletsCreated)
-            .unwrap_or(0)
+            .set(&DataKey::TotalBatches, &batch_id);
+
+        WalletEvents::recovery_completed(&env, batch_id, successful_count, failed_count);
+
+        BatchRecoveryResult {
+            total_requests: request_count,
+            successful: successful_count,
+            failed: failed_count,
+            results,
+        }
     }
 
-    /// Returns wallet information for a given address.
-    pub fn get_wallet(env: Env, address: Address) -> Option<Wallet> {
-        env.storage().persistent().get(&DataKey::Wallets(address))
+    /// Previews a recovery batch without changing any wallet state.
+    ///
+    /// Runs the same per-item validation as `batch_recover_wallets`, including
+    /// the effect of earlier items in the batch, so the returned result has the
+    /// success/failure pattern a real run would produce right now.
+    pub fn simulate_batch_recover(
+        env: Env,
+        caller: Address,
+        requests: Vec<WalletRecoveryRequest>,
+    ) -> BatchRecoveryResult {
+        caller.require_auth();
+        Self::require_recovery_admin(&env, &caller);
+
+        let request_count = requests.len();
+        if request_count == 0 {
+            panic_with_error!(&env, BatchWalletError::EmptyBatch);
+        }
+        if request_count > Self::max_recovery_batch_size(&env) {
+            panic_with_error!(&env, BatchWalletError::BatchTooLarge);
+        }
+
+        let allow_contract_destinations = Self::get_allow_contract_destinations(env.clone());
+        let mut results: Vec<WalletRecoveryResult> = Vec::new(&env);
+        let mut successful_count: u32 = 0;
+        let mut failed_count: u32 = 0;
+        // Wallet existence after the simulated items so far
+        let mut overlay: Map<Address, bool> = Map::new(&env);
+
+        let identity_ok = Self::get_identity_recovery_ok(env.clone());
+        for request in requests.iter() {
+            let error = Self::recovery_error(&env, &request, allow_contract_destinations, &overlay)
+                .or_else(|| Self::timelock_error(&env, &request));
+            if Self::is_identity_noop(identity_ok, &request, error) {
+                results.push_back(WalletRecoveryResult::NoOp(request.old_owner.clone()));
+                successful_count += 1;
+                continue;
+            }
+            match error {
+                Some(error_code) => {
+                    results.push_back(WalletRecoveryResult::Failure(
+                        request.old_owner.clone(),
+                        request.new_owner.clone(),
+                        error_code,
+                    ));
+                    failed_count += 1;
+                }
+                None => {
+                    overlay.set(request.old_owner.clone(), false);
+                    overlay.set(request.new_owner.clone(), true);
+                    results.push_back(WalletRecoveryResult::Success(
+                        request.old_owner.clone(),
+                        request.new_owner.clone(),
+                    ));
+                    successful_count += 1;
+                }
+            }
+        }
+
+        BatchRecoveryResult {
+            total_requests: request_count,
+            successful: successful_count,
+            failed: failed_count,
+            results,
+        }
+    }
+
+    /// Freezes the wallets of the given owners.
+    ///
+    /// Frozen wallets cannot be recovered until unfrozen. Owners without a
+    /// wallet fail with code 1.
+    pub fn batch_freeze_wallets(
+        env: Env,
+        caller: Address,
+        owners: Vec<Address>,
+    ) -> BatchFreezeResult {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+
+        Self::set_frozen_batch(&env, &owners, true)
+    }
+
+    /// Unfreezes the wallets of the given owners.
+    pub fn batch_unfreeze_wallets(
+        env: Env,
+        caller: Address,
+        owners: Vec<Address>,
+    ) -> BatchFreezeResult {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+
+        Self::set_frozen_batch(&env, &owners, false)
+    }
+
+    /// Soft-deletes the wallets of the given owners.
+    ///
+    /// Each wallet is left as a tombstone: `get_wallet` still returns it for
+    /// audit, but it is no longer active and cannot be recovered, frozen or
+    /// relabelled. Owners without a live wallet fail with code 1. Use
+    /// `purge_deleted` to remove tombstones for good.
+    pub fn batch_delete_wallets(
+        env: Env,
+        caller: Address,
+        owners: Vec<Address>,
+    ) -> BatchDeleteResult {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+
+        let request_count = owners.len();
+        if request_count == 0 {
+            panic_with_error!(&env, BatchWalletError::EmptyBatch);
+        }
+        if request_count > Self::max_batch_size(&env) {
+            panic_with_error!(&env, BatchWalletError::BatchTooLarge);
+        }
+
+        let mut results: Vec<WalletDeleteResult> = Vec::new(&env);
+        let mut successful_count: u32 = 0;
+        for owner in owners.iter() {
+            if !wallet_live(&env, &owner) {
+                results.push_back(WalletDeleteResult::Failure(owner.clone(), 1));
+                continue;
+            }
+
+            env.storage()
+                .persistent()
+                .set(&DataKey::Deleted(owner.clone()), &true);
+            env.storage()
+                .persistent()
+                .remove(&DataKey::PendingRecovery(owner.clone()));
+            WalletEvents::wallet_deleted(&env, &owner);
+            results.push_back(WalletDeleteResult::Success(owner.clone()));
+            successful_count += 1;
+        }
+
+        let deleted: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::DeletedWallets)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::DeletedWallets, &(deleted + successful_count as u64));
+
+        BatchDeleteResult {
+            total_requests: request_count,
+            successful: successful_count,
+            failed: request_count - successful_count,
+            results,
+        }
+    }
+
+    /// Permanently removes the tombstoned wallets with the given ids along
+    /// with everything attached to them, freeing their owners for new
+    /// wallets. Ids of live or unknown wallets are skipped. Returns the
+    /// number of wallets purged.
+    pub fn purge_deleted(env: Env, caller: Address, ids: Vec<u64>) -> u32 {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+        if ids.len() > Self::max_batch_size(&env) {
+            panic_with_error!(&env, BatchWalletError::BatchTooLarge);
+        }
+
+        let storage = env.storage().persistent();
+        let mut purged = 0u32;
+        for id in ids.iter() {
+            let Some(owner) = storage.get::<_, Address>(&DataKey::WalletById(id)) else {
+                continue;
+            };
+            if !is_deleted(&env, &owner) {
+                continue;
+            }
+
+            if let Some(external_id) =
+                storage.get::<_, BytesN<32>>(&DataKey::ExternalId(owner.clone()))
+            {
+                storage.remove(&DataKey::ExternalIdOwner(external_id.clone()));
+                Self::adjust_prefix_count(&env, &external_id, false);
+            }
+            for key in [
+                DataKey::Wallets(owner.clone()),
+                DataKey::WalletById(id),
+                DataKey::Deleted(owner.clone()),
+                DataKey::ExternalId(owner.clone()),
+                DataKey::Frozen(owner.clone()),
+                DataKey::Inactive(owner.clone()),
+                DataKey::Guardians(owner.clone()),
+                DataKey::GuardianThreshold(owner.clone()),
+                DataKey::Label(owner.clone()),
+                DataKey::CreatedLedger(owner.clone()),
+                DataKey::RecoveryNonce(owner.clone()),
+                DataKey::Delegate(owner.clone()),
+                DataKey::PendingRecovery(owner.clone()),
+            ] {
+                storage.remove(&key);
+            }
+            WalletEvents::wallet_purged(&env, &owner, id);
+            purged += 1;
+        }
+        purged
+    }
+
+    /// Pauses wallet creation without affecting recovery.
+    pub fn pause_creation(env: Env, caller: Address) {
+        Self::set_pause_flag(&env, &caller, DataKey::CreationPaused, true);
+    }
+
+    /// Resumes wallet creation.
+    pub fn unpause_creation(env: Env, caller: Address) {
+        Self::set_pause_flag(&env, &caller, DataKey::CreationPaused, false);
+    }
+
+    /// Returns whether wallet creation is paused.
+    pub fn is_creation_paused(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::CreationPaused)
+            .unwrap_or(false)
+    }
+
+    /// Pauses wallet recovery without affecting creation.
+    pub fn pause_recovery(env: Env, caller: Address) {
+        Self::set_pause_flag(&env, &caller, DataKey::RecoveryPaused, true);
+    }
+
+    /// Resumes wallet recovery.
+    pub fn unpause_recovery(env: Env, caller: Address) {
+        Self::set_pause_flag(&env, &caller, DataKey::RecoveryPaused, false);
+    }
+
+    /// Returns whether wallet recovery is paused.
+    pub fn is_recovery_paused(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::RecoveryPaused)
+            .unwrap_or(false)
+    }
+
+    /// Allows or blocks recovering wallets to contract addresses.
+    ///
+    /// Blocked by default so self-custody wallets are not recovered to a
+    /// contract by mistake; enable it for smart-wallet deployments.
+    pub fn set_allow_contract_destinations(env: Env, caller: Address, allow: bool) {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::AllowContractDestinations, &allow);
+    }
+
+    /// Returns whether wallets may be recovered to contract addresses.
+    pub fn get_allow_contract_destinations(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::AllowContractDestinations)
+            .unwrap_or(false)
+    }
+
+    /// Records identity recoveries (`old_owner == new_owner`) of existing
+    /// wallets as successful no-ops instead of failing them with code `2`.
+    pub fn set_identity_recovery_ok(env: Env, caller: Address, enabled: bool) {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::IdentityRecoveryOk, &enabled);
+    }
+
+    /// Returns whether identity recoveries are accepted as no-ops.
+    pub fn get_identity_recovery_ok(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::IdentityRecoveryOk)
+            .unwrap_or(false)
+    }
+
+    /// Sets the maximum number of items accepted per batch.
+    ///
+    /// Applies to creation, freezing and linking; it may only lower the
+    /// hard `MAX_BATCH_SIZE` limit.
+    pub fn set_max_batch_size(env: Env, caller: Address, size: u32) {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+        if size == 0 || size > MAX_BATCH_SIZE {
+            panic_with_error!(&env, BatchWalletError::InvalidBatch);
+        }
+
+        env.storage().instance().set(&DataKey::MaxBatchSize, &size);
+    }
+
+    /// Sets the maximum number of items accepted per recovery batch.
+    pub fn set_max_recovery_batch_size(env: Env, caller: Address, size: u32) {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+        if size == 0 || size > MAX_BATCH_SIZE {
+            panic_with_error!(&env, BatchWalletError::InvalidBatch);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::MaxRecoveryBatchSize, &size);
+    }
+
+    /// Caps the total number of wallets that may be created (0 = unlimited).
+    pub fn set_max_wallets(env: Env, caller: Address, max_wallets: u64) {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+
+        env.storage().instance().set(&DataKey::MaxWallets, &max_wallets);
+    }
+
+    /// Returns all admin-configurable limits in one call.
+    pub fn get_wallet_config(env: Env) -> WalletConfig {
+        WalletConfig {
+            max_batch_size: Self::max_batch_size(&env),
+            max_recovery_batch_size: Self::max_recovery_batch_size(&env),
+            recovery_delay_ledgers: Self::get_recovery_delay(env.clone()),
+            max_wallets: env
+                .storage()
+                .instance()
+                .get(&DataKey::MaxWallets)
+                .unwrap_or(0),
+            batch_cooldown_ledgers: 0,
+            allow_contract_destinations: env
+                .storage()
+                .instance()
+                .get(&DataKey::AllowContractDestinations)
+                .unwrap_or(false),
+        }
+    }
+
+    /// Requires every newly created wallet to list at least `min_guardians` guardians.
+    ///
+    /// Entries with fewer guardians fail with error code `3`; `0` disables the policy.
+    pub fn set_guardian_policy(env: Env, caller: Address, min_guardians: u32) {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::MinGuardians, &min_guardians);
+    }
+
+    /// Returns the minimum number of guardians required at creation.
+    pub fn get_guardian_policy(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::MinGuardians)
+            .unwrap_or(0)
+    }
+
+    /// Caps the number of guardians a wallet may list. Creation entries
+    /// above the cap fail with error code `4`.
+    pub fn set_max_guardians(env: Env, caller: Address, max_guardians: u32) {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::MaxGuardians, &max_guardians);
+    }
+
+    /// Returns the maximum number of guardians per wallet.
+    pub fn get_max_guardians(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::MaxGuardians)
+            .unwrap_or(DEFAULT_MAX_GUARDIANS)
+    }
+
+    /// Replaces the guardians of the wallet of `owner`.
+    ///
+    /// The list must respect the guardian policy and limit and still cover
+    /// the wallet's approval threshold.
+    pub fn set_guardians(env: Env, caller: Address, owner: Address, guardians: Vec<Address>) {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+        if !wallet_live(&env, &owner) {
+            panic_with_error!(&env, BatchWalletError::WalletNotFound);
+        }
+        if guardians.len() > Self::get_max_guardians(env.clone()) {
+            panic_with_error!(&env, BatchWalletError::TooManyGuardians);
+        }
+        if guardians.len() < Self::get_guardian_policy(env.clone())
+            || guardians.len() < Self::get_guardian_threshold(env.clone(), owner.clone())
+        {
+            panic_with_error!(&env, BatchWalletError::GuardiansRequired);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::Guardians(owner), &guardians);
+    }
+
+    /// Swaps `old_guardian` for `new_guardian` on the wallets with ids in
+    /// `start_id..start_id + limit`, returning the number updated.
+    ///
+    /// Rotating a guardian across the registry means paging over the range
+    /// reported by `get_id_bounds`. `limit` is clamped to `MAX_BATCH_SIZE`.
+    /// Deleted wallets are left unchanged; wallets that already list
+    /// `new_guardian` just drop `old_guardian`.
+    pub fn replace_guardian_range(
+        env: Env,
+        caller: Address,
+        old_guardian: Address,
+        new_guardian: Address,
+        start_id: u64,
+        limit: u32,
+    ) -> u32 {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+
+        let mut replaced = 0u32;
+        for offset in 0..limit.min(MAX_BATCH_SIZE) as u64 {
+            let id = match start_id.checked_add(offset) {
+                Some(id) => id,
+                None => break,
+            };
+            replaced += Self::replace_guardian_at(&env, id, &old_guardian, &new_guardian) as u32;
+        }
+        replaced
+    }
+
+    /// Returns the guardians of the wallet of `owner`.
+    pub fn get_guardians(env: Env, owner: Address) -> Vec<Address> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Guardians(owner))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Returns the number of guardian approvals the wallet of `owner`
+    /// requires, or 0 if no threshold has been installed.
+    pub fn get_guardian_threshold(env: Env, owner: Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::GuardianThreshold(owner))
+            .unwrap_or(0)
+    }
+
+    /// Attaches a display label to the wallet of `owner`; it follows the
+    /// wallet through recovery.
+    pub fn set_wallet_label(env: Env, caller: Address, owner: Address, label: String) {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+        if !wallet_live(&env, &owner) {
+            panic_with_error!(&env, BatchWalletError::WalletNotFound);
+        }
+
+        env.storage().persistent().set(&DataKey::Label(owner), &label);
+    }
+
+    /// Returns everything known about the wallet of `owner` in one call, or
+    /// `None` if it has no wallet.
+    pub fn get_wallet_profile(env: Env, owner: Address) -> Option<WalletProfile> {
+        let wallet = Self::get_wallet(env.clone(), owner.clone())?;
+        let storage = env.storage().persistent();
+        Some(WalletProfile {
+            wallet,
+            frozen: Self::is_frozen(env.clone(), owner.clone()),
+            active: Self::is_active(env.clone(), owner.clone()),
+            guardians: Self::get_guardians(env.clone(), owner.clone()),
+            threshold: Self::get_guardian_threshold(env.clone(), owner.clone()),
+            external_id: Self::get_external_id(env.clone(), owner.clone()),
+            label: storage.get(&DataKey::Label(owner.clone())),
+            created_ledger: storage
+                .get(&DataKey::CreatedLedger(owner.clone()))
+                .unwrap_or(0),
+            recovery_nonce: storage
+                .get(&DataKey::RecoveryNonce(owner.clone()))
+                .unwrap_or(0),
+            deleted: is_deleted(&env, &owner),
+        })
+    }
+
+    /// Lets `delegate` authorize delegated operations on the wallet of
+    /// `owner` without taking ownership. Replaces any previous delegate.
+    pub fn set_delegate(env: Env, owner: Address, delegate: Address) {
+        owner.require_auth();
+        if !wallet_live(&env, &owner) {
+            panic_with_error!(&env, BatchWalletError::WalletNotFound);
+        }
+
+        env.storage().persistent().set(&DataKey::Delegate(owner), &delegate);
+    }
+
+    /// Revokes the delegate of `owner`, if any.
+    pub fn clear_delegate(env: Env, owner: Address) {
+        owner.require_auth();
+        env.storage().persistent().remove(&DataKey::Delegate(owner));
+    }
+
+    /// Returns the delegate of the wallet of `owner`, if one is set.
+    pub fn get_delegate(env: Env, owner: Address) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::Delegate(owner))
+    }
+
+    /// Marks the wallet of `owner` as active or dormant.
+    pub fn set_wallet_active(env: Env, caller: Address, owner: Address, active: bool) {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+        if !wallet_live(&env, &owner) {
+            panic_with_error!(&env, BatchWalletError::WalletNotFound);
+        }
+
+        if active {
+            env.storage().persistent().remove(&DataKey::Inactive(owner));
+        } else {
+            env.storage().persistent().set(&DataKey::Inactive(owner), &true);
+        }
+    }
+
+    /// Returns whether the wallet of `owner` exists and is active.
+    pub fn is_active(env: Env, owner: Address) -> bool {
+        wallet_live(&env, &owner)
+            && !env
+                .storage()
+                .persistent()
+                .get(&DataKey::Inactive(owner))
+                .unwrap_or(false)
+    }
+
+    /// Returns whether the wallet of the given owner is frozen.
+    pub fn is_frozen(env: Env, owner: Address) -> bool {
+        is_frozen(&env, &owner)
+    }
+
+    /// Links an external id to the wallet of `owner`.
+    ///
+    /// Relinking replaces the wallet's previous external id.
+    pub fn link_external_id(env: Env, caller: Address, owner: Address, external_id: BytesN<32>) {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+
+        if let Err(error) = Self::try_link_external_id(&env, &owner, &external_id) {
+            panic_with_error!(&env, error);
+        }
+    }
+
+    /// Links external ids to multiple wallets.
+    ///
+    /// Each item fails with code 1 if the owner has no wallet and code 2 if
+    /// the external id is linked to another wallet.
+    pub fn batch_link_external_ids(
+        env: Env,
+        caller: Address,
+        links: Vec<(Address, BytesN<32>)>,
+    ) -> BatchLinkResult {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+
+        let request_count = links.len();
+        if request_count == 0 {
+            panic_with_error!(&env, BatchWalletError::EmptyBatch);
+        }
+        if request_count > Self::max_batch_size(&env) {
+            panic_with_error!(&env, BatchWalletError::BatchTooLarge);
+        }
+
+        let mut results: Vec<ExternalIdLinkResult> = Vec::new(&env);
+        let mut successful_count: u32 = 0;
+        let mut failed_count: u32 = 0;
+
+        for (owner, external_id) in links.iter() {
+            match Self::try_link_external_id(&env, &owner, &external_id) {
+                Ok(()) => {
+                    results.push_back(ExternalIdLinkResult::Success(owner, external_id));
+                    successful_count += 1;
+                }
+                Err(error) => {
+                    let error_code = match error {
+                        BatchWalletError::WalletNotFound => 1,
+                        _ => 2, // External id taken
+                    };
+                    results.push_back(ExternalIdLinkResult::Failure(
+                        owner,
+                        external_id,
+                        error_code,
+                    ));
+                    failed_count += 1;
+                }
+            }
+        }
+
+        BatchLinkResult {
+            total_requests: request_count,
+            successful: successful_count,
+            failed: failed_count,
+            results,
+        }
+    }
+
+    /// Removes the external id linked to the wallet of `owner`, if any.
+    pub fn unlink_external_id(env: Env, caller: Address, owner: Address) {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+
+        let owner_key = DataKey::ExternalId(owner.clone());
+        if let Some(external_id) = env.storage().persistent().get::<_, BytesN<32>>(&owner_key) {
+            env.storage().persistent().remove(&owner_key);
+            env.storage()
+                .persistent()
+                .remove(&DataKey::ExternalIdOwner(external_id.clone()));
+            Self::adjust_prefix_count(&env, &external_id, false);
+            WalletEvents::external_id_unlinked(&env, &owner, &external_id);
+        }
+    }
+
+    /// Returns how many wallets are linked to external ids starting with
+    /// `prefix`, the first `EXTERNAL_PREFIX_LEN` bytes identifying a tenant.
+    ///
+    /// Backed by a counter maintained on link and unlink; prefixes of any
+    /// other length count 0.
+    pub fn count_by_external_prefix(env: Env, prefix: Bytes) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ExternalPrefixCount(prefix))
+            .unwrap_or(0)
+    }
+
+    /// Returns the external id linked to the wallet of `owner`, if any.
+    pub fn get_external_id(env: Env, owner: Address) -> Option<BytesN<32>> {
+        env.storage().persistent().get(&DataKey::ExternalId(owner))
+    }
+
+    /// Returns the owner of the wallet linked to `external_id`, if any.
+    pub fn get_owner_by_external_id(env: Env, external_id: BytesN<32>) -> Option<Address> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ExternalIdOwner(external_id))
+    }
+
+    /// Returns the admin address.
+    pub fn get_admin(env: Env) -> Address {
+        env.storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .expect("Contract not initialized")
+    }
+
+    /// Updates the admin address.
+    pub fn set_admin(env: Env, current_admin: Address, new_admin: Address) {
+        current_admin.require_auth();
+        Self::require_admin(&env, &current_admin);
+
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
+    }
+
+    /// Hands recovery authority to a dedicated key; the main admin can no
+    /// longer recover wallets once one is set.
+    pub fn set_recovery_admin(env: Env, caller: Address, recovery_admin: Address) {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::RecoveryAdmin, &recovery_admin);
+    }
+
+    /// Returns the address authorized to recover wallets: the recovery admin,
+    /// or the main admin if none is set.
+    pub fn get_recovery_admin(env: Env) -> Address {
+        env.storage()
+            .instance()
+            .get(&DataKey::RecoveryAdmin)
+            .unwrap_or_else(|| Self::get_admin(env))
+    }
+
+    /// Returns the total number of batches processed.
+    pub fn get_total_batches(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TotalBatches)
+            .unwrap_or(0)
+    }
+
+    /// Returns the total number of wallets created.
+    pub fn get_total_wallets_created(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TotalWalletsCreated)
+            .unwrap_or(0)
+    }
+
+    /// Returns the number of wallets created that have not been deleted.
+    pub fn get_live_wallet_count(env: Env) -> u64 {
+        let deleted: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::DeletedWallets)
+            .unwrap_or(0);
+        Self::get_total_wallets_created(env).saturating_sub(deleted)
+    }
+
+    /// Returns the smallest and largest ids of wallets that have not been
+    /// deleted, or `(0, 0)` if no such wallet exists.
+    ///
+    /// Ids vacated by remapping or held by tombstones are skipped, so clients
+    /// can page `compute_wallet_root` and `get_wallet_by_id` over this range.
+    /// The scan walks inward from both ends, starting no lower than the id
+    /// offset (or the lowest id a wallet was remapped to), and reads at most
+    /// `MAX_BATCH_SIZE` ids from each end. Gaps wider than that at an edge
+    /// leave that bound loose: the range still holds every wallet, but its
+    /// edge may sit inside the gap.
+    pub fn get_id_bounds(env: Env) -> (u64, u64) {
+        let assigned = |id: u64| {
+            env.storage()
+                .persistent()
+                .get::<_, Address>(&DataKey::WalletById(id))
+                .is_some_and(|owner| !is_deleted(&env, &owner))
+        };
+
+        let mut min_id = Self::min_wallet_id(&env);
+        let mut max_id = Self::next_wallet_id(&env) - 1;
+        let mut budget = MAX_BATCH_SIZE;
+        while budget > 0 && max_id >= min_id && !assigned(max_id) {
+            max_id -= 1;
+            budget -= 1;
+        }
+        if max_id < min_id {
+            return (0, 0);
+        }
+
+        let mut budget = MAX_BATCH_SIZE;
+        while budget > 0 && min_id < max_id && !assigned(min_id) {
+            min_id += 1;
+            budget -= 1;
+        }
+        // Both scans met on a single unassigned id: nothing is left
+        if min_id == max_id && !assigned(min_id) {
+            return (0, 0);
+        }
+        (min_id, max_id)
+    }
+
+    /// Enables or disables migration mode, which gates migration-only operations.
+    pub fn set_migration_mode(env: Env, caller: Address, enabled: bool) {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+
+        env.storage().instance().set(&DataKey::MigrationMode, &enabled);
+    }
+
+    /// Returns whether migration mode is enabled.
+    pub fn is_migration_mode(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::MigrationMode)
+            .unwrap_or(false)
+    }
+
+    /// Reassigns the id of `owner`'s wallet to match an external system.
+    ///
+    /// Migration-only: fails with `MigrationInactive` outside migration mode
+    /// and with `IdTaken` if another wallet already uses `new_id` or it is
+    /// reserved.
+    pub fn remap_wallet_id(env: Env, caller: Address, owner: Address, new_id: u64) {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+        if !Self::is_migration_mode(env.clone()) {
+            panic_with_error!(&env, BatchWalletError::MigrationInactive);
+        }
+
+        let wallet_key = DataKey::Wallets(owner.clone());
+        let mut wallet: Wallet = match env.storage().persistent().get(&wallet_key) {
+            Some(wallet) => wallet,
+            None => panic_with_error!(&env, BatchWalletError::WalletNotFound),
+        };
+        if wallet.id == new_id {
+            return;
+        }
+        if env.storage().persistent().has(&DataKey::WalletById(new_id))
+            || env.storage().persistent().has(&DataKey::ReservedId(new_id))
+        {
+            panic_with_error!(&env, BatchWalletError::IdTaken);
+        }
+
+        if new_id == 0 || new_id == u64::MAX {
+            panic_with_error!(&env, BatchWalletError::InvalidWalletId);
+        }
+
+        let old_id = wallet.id;
+        env.storage().persistent().remove(&DataKey::WalletById(old_id));
+        Self::advance_id_counter(&env, new_id);
+        if new_id < Self::min_wallet_id(&env) {
+            env.storage().instance().set(&DataKey::MinWalletId, &new_id);
+        }
+        env.storage()
+            .persistent()
+            .set(&DataKey::WalletById(new_id), &owner);
+        wallet.id = new_id;
+        env.storage().persistent().set(&wallet_key, &wallet);
+
+        WalletEvents::wallet_id_remapped(&env, &owner, old_id, new_id);
+    }
+
+    /// Checks that `expected` is the smart-wallet address derived for `owner` and `salt`.
+    ///
+    /// The address is the one this contract would deploy to with
+    /// `env.deployer().with_current_contract(sha256(owner_xdr || salt))`, so
+    /// clients can confirm a derived wallet before creating it.
+    pub fn verify_wallet_address(
+        env: Env,
+        owner: Address,
+        salt: BytesN<32>,
+        expected: Address,
+    ) -> bool {
+        if validate_address(&owner).is_err() {
+            return false;
+        }
+        Self::derive_wallet_address(&env, &owner, &salt) == expected
+    }
+
+    /// Extends the storage TTL of every live wallet with an id in
+    /// `start_id..start_id + limit`, returning how many were bumped.
+    /// Tombstones are left to expire.
+    ///
+    /// `limit` is clamped to `MAX_BATCH_SIZE`. Both the wallet and its id
+    /// index entry are extended to `WALLET_TTL_EXTEND` once below
+    /// `WALLET_TTL_THRESHOLD`.
+    pub fn bump_wallets_ttl(env: Env, caller: Address, start_id: u64, limit: u32) -> u32 {
+        caller.require_auth();
+        Self::require_admin(&env, &caller);
+
+        let mut bumped: u32 = 0;
+        for offset in 0..limit.min(MAX_BATCH_SIZE) as u64 {
+            let id = match start_id.checked_add(offset) {
+                Some(id) => id,
+                None => break,
+            };
+            let id_key = DataKey::WalletById(id);
+            let owner: Address = match env.storage().persistent().get(&id_key) {
+                Some(owner) if !is_deleted(&env, &owner) => owner,
+                _ => continue,
+            };
+            env.storage()
+                .persistent()
+                .extend_ttl(&id_key, WALLET_TTL_THRESHOLD, WALLET_TTL_EXTEND);
+            env.storage().persistent().extend_ttl(
+                &DataKey::Wallets(owner),
+                WALLET_TTL_THRESHOLD,
+                WALLET_TTL_EXTEND,
+            );
+            bumped += 1;
+        }
+        bumped
+    }
+
+    /// Returns the Merkle root over the wallets with ids in
+    /// `start_id..start_id + limit`, so light clients can verify membership.
+    ///
+    /// Leaves are `sha256(id_be_bytes || owner_xdr)` in id order, skipping
+    /// unassigned ids and deleted wallets. Parents are `sha256(left || right)`; an unpaired node
+    /// is carried up unchanged. An empty page yields the all-zero root.
+    pub fn compute_wallet_root(env: Env, start_id: u64, limit: u32) -> BytesN<32> {
+        if limit > MAX_BATCH_SIZE {
+            panic_with_error!(&env, BatchWalletError::BatchTooLarge);
+        }
+
+        let mut level: Vec<BytesN<32>> = Vec::new(&env);
+        for offset in 0..limit as u64 {
+            let id = match start_id.checked_add(offset) {
+                Some(id) => id,
+                None => break,
+            };
+            if let Some(owner) = env
+                .storage()
+                .persistent()
+                .get::<_, Address>(&DataKey::WalletById(id))
+                .filter(|owner| !is_deleted(&env, owner))
+            {
+                let mut preimage = Bytes::from_array(&env, &id.to_be_bytes());
+                preimage.append(&owner.to_xdr(&env));
+                level.push_back(env.crypto().sha256(&preimage).into());
+            }
+        }
+        if level.is_empty() {
+            return BytesN::from_array(&env, &[0u8; 32]);
+        }
+
+        while level.len() > 1 {
+            let mut next: Vec<BytesN<32>> = Vec::new(&env);
+            let mut i = 0;
+            while i < level.len() {
+                let left = level.get_unchecked(i);
+                if i + 1 < level.len() {
+                    let mut preimage = Bytes::from(left);
+                    preimage.append(&Bytes::from(level.get_unchecked(i + 1)));
+                    next.push_back(env.crypto().sha256(&preimage).into());
+                } else {
+                    next.push_back(left);
+                }
+                i += 2;
+            }
+            level = next;
+        }
+        level.get_unchecked(0)
+    }
+
+    /// Returns the wallet assigned the given id.
+    pub fn get_wallet_by_id(env: Env, id: u64) -> Option<Wallet> {
+        let owner: Address = env.storage().persistent().get(&DataKey::WalletById(id))?;
+        env.storage().persistent().get(&DataKey::Wallets(owner))
+    }
+
+    /// Returns wallet information for a given address.
+    pub fn get_wallet(env: Env, address: Address) -> Option<Wallet> {
+        env.storage().persistent().get(&DataKey::Wallets(address))
+    }
+
+    // Internal helper deriving the deterministic smart-wallet address of an owner
+    fn derive_wallet_address(env: &Env, owner: &Address, salt: &BytesN<32>) -> Address {
+        let mut preimage = owner.clone().to_xdr(env);
+        preimage.append(&salt.clone().into());
+        let owner_salt: BytesN<32> = env.crypto().sha256(&preimage).into();
+        env.deployer()
+            .with_current_contract(owner_salt)
+            .deployed_address()
+    }
+
+    // Internal helper setting or clearing one of the per-path pause flags
+    fn set_pause_flag(env: &Env, caller: &Address, key: DataKey, paused: bool) {
+        caller.require_auth();
+        Self::require_admin(env, caller);
+
+        if paused {
+            env.storage().instance().set(&key, &true);
+        } else {
+            env.storage().instance().remove(&key);
+        }
+    }
+
+    // Internal helper returning the failure code of a recovery request, if any.
+    // `overlay` overrides wallet existence for addresses touched earlier in a
+    // simulated batch.
+    fn recovery_error(
+        env: &Env,
+        request: &WalletRecoveryRequest,
+        allow_contract_destinations: bool,
+        overlay: &Map<Address, bool>,
+    ) -> Option<u32> {
+        let exists = |owner: &Address| {
+            overlay
+                .get(owner.clone())
+                .unwrap_or_else(|| wallet_exists(env, owner))
+        };
+
+        if validate_address(&request.old_owner).is_err()
+            || validate_address(&request.new_owner).is_err()
+        {
+            Some(0) // Invalid address
+        } else if !exists(&request.old_owner) || is_deleted(env, &request.old_owner) {
+            Some(1) // Source wallet not found
+        } else if exists(&request.new_owner) {
+            Some(2) // Destination wallet exists
+        } else if is_frozen(env, &request.old_owner) {
+            Some(3) // Source wallet frozen
+        } else if !allow_contract_destinations && is_contract_address(&request.new_owner) {
+            Some(4) // Contract destination blocked
+        } else {
+            None
+        }
+    }
+
+    // Internal helper rejecting a single recovery that would fail in a batch
+    fn require_recoverable(env: &Env, old_owner: &Address, new_owner: &Address) {
+        let request = WalletRecoveryRequest {
+            old_owner: old_owner.clone(),
+            new_owner: new_owner.clone(),
+        };
+        let allow_contract_destinations = Self::get_allow_contract_destinations(env.clone());
+        if Self::recovery_error(env, &request, allow_contract_destinations, &Map::new(env))
+            .is_some()
+        {
+            panic_with_error!(env, BatchWalletError::RecoveryRejected);
+        }
+    }
+
+    // Internal helper holding back direct recoveries while a recovery delay
+    // is set, unless they match a pending recovery whose timelock elapsed
+    fn timelock_error(env: &Env, request: &WalletRecoveryRequest) -> Option<u32> {
+        if Self::get_recovery_delay(env.clone()) == 0 {
+            return None;
+        }
+        match env
+            .storage()
+            .persistent()
+            .get::<_, PendingRecovery>(&DataKey::PendingRecovery(request.old_owner.clone()))
+        {
+            Some(pending)
+                if pending.new_owner == request.new_owner
+                    && env.ledger().sequence() >= pending.unlock_ledger =>
+            {
+                None
+            }
+            _ => Some(6), // Recovery timelocked
+        }
+    }
+
+    // Internal helper loading the recovery pending for `old_owner`
+    fn load_pending_recovery(env: &Env, old_owner: &Address) -> PendingRecovery {
+        env.storage()
+            .persistent()
+            .get(&DataKey::PendingRecovery(old_owner.clone()))
+            .unwrap_or_else(|| panic_with_error!(env, BatchWalletError::NoPendingRecovery))
+    }
+
+    // Internal helper detecting an identity recovery of an existing wallet,
+    // which otherwise fails as "destination wallet exists"
+    fn is_identity_noop(
+        identity_ok: bool,
+        request: &WalletRecoveryRequest,
+        error: Option<u32>,
+    ) -> bool {
+        identity_ok && request.old_owner == request.new_owner && error == Some(2)
+    }
+
+    // Internal helper returning the next wallet id to assign; id 0 is never handed out
+    fn next_wallet_id(env: &Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::NextWalletId)
+            .unwrap_or(1u64)
+            .max(1)
+    }
+
+    // Internal helper returning the lowest id any wallet can hold: the id
+    // offset, lowered by remaps below it
+    fn min_wallet_id(env: &Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::MinWalletId)
+            .unwrap_or(1u64)
+            .max(1)
+    }
+
+    // Internal helper moving the auto-assignment counter past a manually assigned id
+    fn advance_id_counter(env: &Env, id: u64) {
+        let next_id = id.saturating_add(1);
+        if next_id > Self::next_wallet_id(env) {
+            env.storage().instance().set(&DataKey::NextWalletId, &next_id);
+        }
+    }
+
+    // Internal helper persisting a new wallet with its id index and guardians
+    fn store_wallet(env: &Env, wallet: &Wallet, guardians: &Vec<Address>) {
+        env.storage()
+            .persistent()
+            .set(&DataKey::Wallets(wallet.owner.clone()), wallet);
+        env.storage().persistent().set(
+            &DataKey::CreatedLedger(wallet.owner.clone()),
+            &env.ledger().sequence(),
+        );
+        env.storage()
+            .persistent()
+            .set(&DataKey::WalletById(wallet.id), &wallet.owner);
+        if !guardians.is_empty() {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Guardians(wallet.owner.clone()), guardians);
+        }
+    }
+
+    // Internal helper returning the configured batch size limit
+    fn max_batch_size(env: &Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::MaxBatchSize)
+            .unwrap_or(MAX_BATCH_SIZE)
+    }
+
+    // Internal helper returning the configured recovery batch size limit
+    fn max_recovery_batch_size(env: &Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::MaxRecoveryBatchSize)
+            .unwrap_or(MAX_BATCH_SIZE)
+    }
+
+    // Internal helper applying a freeze flag to each owner's wallet
+    fn set_frozen_batch(env: &Env, owners: &Vec<Address>, frozen: bool) -> BatchFreezeResult {
+        let request_count = owners.len();
+        if request_count == 0 {
+            panic_with_error!(env, BatchWalletError::EmptyBatch);
+        }
+        if request_count > Self::max_batch_size(env) {
+            panic_with_error!(env, BatchWalletError::BatchTooLarge);
+        }
+
+        let mut results: Vec<WalletFreezeResult> = Vec::new(env);
+        let mut successful_count: u32 = 0;
+        let mut failed_count: u32 = 0;
+
+        for owner in owners.iter() {
+            if !wallet_live(env, &owner) {
+                // Wallet not found
+                results.push_back(WalletFreezeResult::Failure(owner.clone(), 1));
+                failed_count += 1;
+                continue;
+            }
+
+            if frozen {
+                env.storage()
+                    .persistent()
+                    .set(&DataKey::Frozen(owner.clone()), &true);
+                WalletEvents::wallet_frozen(env, &owner);
+            } else {
+                env.storage()
+                    .persistent()
+                    .remove(&DataKey::Frozen(owner.clone()));
+                WalletEvents::wallet_unfrozen(env, &owner);
+            }
+
+            results.push_back(WalletFreezeResult::Success(owner.clone()));
+            successful_count += 1;
+        }
+
+        BatchFreezeResult {
+            total_requests: request_count,
+            successful: successful_count,
+            failed: failed_count,
+            results,
+        }
+    }
+
+    // Internal helper linking an external id, keeping both directions of the index in sync
+    fn try_link_external_id(
+        env: &Env,
+        owner: &Address,
+        external_id: &BytesN<32>,
+    ) -> Result<(), BatchWalletError> {
+        if !wallet_live(env, owner) {
+            return Err(BatchWalletError::WalletNotFound);
+        }
+
+        let id_key = DataKey::ExternalIdOwner(external_id.clone());
+        let linked_owner: Option<Address> = env.storage().persistent().get(&id_key);
+        if linked_owner.as_ref().is_some_and(|linked| linked != owner) {
+            return Err(BatchWalletError::ExternalIdTaken);
+        }
+
+        let owner_key = DataKey::ExternalId(owner.clone());
+        if let Some(previous) = env.storage().persistent().get::<_, BytesN<32>>(&owner_key) {
+            env.storage()
+                .persistent()
+                .remove(&DataKey::ExternalIdOwner(previous.clone()));
+            Self::adjust_prefix_count(env, &previous, false);
+        }
+        env.storage().persistent().set(&owner_key, external_id);
+        env.storage().persistent().set(&id_key, owner);
+        Self::adjust_prefix_count(env, external_id, true);
+
+        WalletEvents::external_id_linked(env, owner, external_id);
+        Ok(())
+    }
+
+    // Internal helper counting a link or unlink against the external id's tenant prefix
+    fn adjust_prefix_count(env: &Env, external_id: &BytesN<32>, linked: bool) {
+        let prefix = Bytes::from(external_id.clone()).slice(0..EXTERNAL_PREFIX_LEN);
+        let key = DataKey::ExternalPrefixCount(prefix);
+        let count: u32 = env.storage().persistent().get(&key).unwrap_or(0);
+        let count = if linked {
+            count + 1
+        } else {
+            count.saturating_sub(1)
+        };
+        if count == 0 {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &count);
+        }
+    }
+
+    // Internal helper validating a guardian set and threshold, returning code 5
+    // when the config is unusable
+    fn guardian_config_error(
+        env: &Env,
+        guardians: &Vec<Address>,
+        threshold: u32,
+        min_guardians: u32,
+    ) -> Option<u32> {
+        let mut seen: Map<Address, bool> = Map::new(env);
+        for guardian in guardians.iter() {
+            if validate_address(&guardian).is_err() || seen.contains_key(guardian.clone()) {
+                return Some(5); // Invalid guardian config
+            }
+            seen.set(guardian, true);
+        }
+        if guardians.len() < min_guardians
+            || guardians.len() > Self::get_max_guardians(env.clone())
+            || threshold == 0
+            || threshold > guardians.len()
+        {
+            return Some(5); // Invalid guardian config
+        }
+        None
+    }
+
+    // Internal helper re-owning a wallet and its attachments, returning its id
+    fn reassign_wallet(env: &Env, old_owner: &Address, new_owner: &Address) -> u64 {
+        let mut wallet: Wallet = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Wallets(old_owner.clone()))
+            .unwrap();
+        wallet.owner = new_owner.clone();
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::Wallets(new_owner.clone()), &wallet);
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Wallets(old_owner.clone()));
+        env.storage()
+            .persistent()
+            .set(&DataKey::WalletById(wallet.id), new_owner);
+        Self::move_wallet_attachments(env, old_owner, new_owner);
+        let nonce_key = DataKey::RecoveryNonce(new_owner.clone());
+        let nonce: u32 = env.storage().persistent().get(&nonce_key).unwrap_or(0);
+        env.storage().persistent().set(&nonce_key, &(nonce + 1));
+        wallet.id
+    }
+
+    // Internal helper moving per-wallet data from the old owner to the new one on recovery
+    fn move_wallet_attachments(env: &Env, old_owner: &Address, new_owner: &Address) {
+        let inactive_key = DataKey::Inactive(old_owner.clone());
+        if env.storage().persistent().has(&inactive_key) {
+            env.storage().persistent().remove(&inactive_key);
+            env.storage()
+                .persistent()
+                .set(&DataKey::Inactive(new_owner.clone()), &true);
+        }
+
+        let guardians_key = DataKey::Guardians(old_owner.clone());
+        if let Some(guardians) = env
+            .storage()
+            .persistent()
+            .get::<_, Vec<Address>>(&guardians_key)
+        {
+            env.storage().persistent().remove(&guardians_key);
+            env.storage()
+                .persistent()
+                .set(&DataKey::Guardians(new_owner.clone()), &guardians);
+        }
+
+        let threshold_key = DataKey::GuardianThreshold(old_owner.clone());
+        if let Some(threshold) = env.storage().persistent().get::<_, u32>(&threshold_key) {
+            env.storage().persistent().remove(&threshold_key);
+            env.storage()
+                .persistent()
+                .set(&DataKey::GuardianThreshold(new_owner.clone()), &threshold);
+        }
+
+        let label_key = DataKey::Label(old_owner.clone());
+        if let Some(label) = env.storage().persistent().get::<_, String>(&label_key) {
+            env.storage().persistent().remove(&label_key);
+            env.storage()
+                .persistent()
+                .set(&DataKey::Label(new_owner.clone()), &label);
+        }
+
+        for (old_key, new_key) in [
+            (
+                DataKey::CreatedLedger(old_owner.clone()),
+                DataKey::CreatedLedger(new_owner.clone()),
+            ),
+            (
+                DataKey::RecoveryNonce(old_owner.clone()),
+                DataKey::RecoveryNonce(new_owner.clone()),
+            ),
+        ] {
+            if let Some(value) = env.storage().persistent().get::<_, u32>(&old_key) {
+                env.storage().persistent().remove(&old_key);
+                env.storage().persistent().set(&new_key, &value);
+            }
+        }
+
+        // A delegate chosen by the previous key must not outlive the recovery,
+        // and neither may a recovery still pending against it
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Delegate(old_owner.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::PendingRecovery(old_owner.clone()));
+
+        let old_key = DataKey::ExternalId(old_owner.clone());
+        if let Some(external_id) = env.storage().persistent().get::<_, BytesN<32>>(&old_key) {
+            env.storage().persistent().remove(&old_key);
+            env.storage()
+                .persistent()
+                .set(&DataKey::ExternalId(new_owner.clone()), &external_id);
+            env.storage()
+                .persistent()
+                .set(&DataKey::ExternalIdOwner(external_id), new_owner);
+        }
+    }
+
+    // Internal helper to verify the recovery admin
+    fn require_recovery_admin(env: &Env, caller: &Address) {
+        if *caller != Self::get_recovery_admin(env.clone()) {
+            panic_with_error!(env, BatchWalletError::Unauthorized);
+        }
+    }
+
+    // Internal helper swapping a guardian on the wallet with id `id`,
+    // returning whether the wallet was updated
+    fn replace_guardian_at(
+        env: &Env,
+        id: u64,
+        old_guardian: &Address,
+        new_guardian: &Address,
+    ) -> bool {
+        let storage = env.storage().persistent();
+        let Some(owner) = storage.get::<_, Address>(&DataKey::WalletById(id)) else {
+            return false;
+        };
+        if !wallet_live(env, &owner) {
+            return false;
+        }
+
+        let key = DataKey::Guardians(owner);
+        let mut guardians: Vec<Address> = storage.get(&key).unwrap_or(Vec::new(env));
+        let Some(index) = guardians.first_index_of(old_guardian) else {
+            return false;
+        };
+        // A wallet already listing the new guardian just drops the old one
+        if guardians.contains(new_guardian) {
+            guardians.remove(index);
+        } else {
+            guardians.set(index, new_guardian.clone());
+        }
+        storage.set(&key, &guardians);
+        true
     }
 
     // Internal helper to verify admin
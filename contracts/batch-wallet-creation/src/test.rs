@@ -4,11 +4,14 @@
 
 use crate::{
     BatchCreateResult, BatchRecoveryResult, BatchWalletContract, BatchWalletContractClient,
-    WalletCreateRequest, WalletCreateResult, WalletRecoveryRequest, WalletRecoveryResult,
+    BatchWalletError, DataKey, ExternalIdLinkResult, RecoveryWithGuardians, WalletConfig,
+    WalletCreateRequest, WalletCreateResult, WalletDeleteResult, WalletFreezeResult,
+    WalletRecoveryRequest, WalletRecoveryResult, WALLET_TTL_EXTEND,
 };
 use soroban_sdk::{
-    testutils::{Address as _, Events as _, Ledger},
-    Address, Env, Vec,
+    testutils::{storage::Persistent as _, Address as _, Events as _, Ledger},
+    xdr::{AccountId, Hash, PublicKey, ScAddress, ToXdr, Uint256},
+    Address, Bytes, BytesN, Env, String, TryFromVal, Vec,
 };
 
 /// Creates a test environment with the contract deployed and initialized.
@@ -29,9 +32,22 @@ fn setup_test_env() -> (Env, Address, BatchWalletContractClient<'static>) {
     (env, admin, client)
 }
 
+/// Generates a unique account (`G...`) address; `Address::generate` yields contracts.
+fn generate_account(env: &Env) -> Address {
+    let ScAddress::Contract(Hash(bytes)) = ScAddress::from(&Address::generate(env)) else {
+        unreachable!()
+    };
+    let account = ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(bytes))));
+    Address::try_from_val(env, &account).unwrap()
+}
+
 /// Helper to create a wallet creation request.
-fn create_wallet_request(_env: &Env, owner: Address) -> WalletCreateRequest {
-    WalletCreateRequest { owner }
+fn create_wallet_request(env: &Env, owner: Address) -> WalletCreateRequest {
+    WalletCreateRequest {
+        owner,
+        guardians: Vec::new(env),
+        active: true,
+    }
 }
 
 fn create_recovery_request(
@@ -59,7 +75,7 @@ fn test_initialize_contract() {
 #[test]
 #[should_panic(expected = "Contract already initialized")]
 fn test_cannot_initialize_twice() {
-    let (env, admin, client) = setup_test_env();
+    let (env, _admin, client) = setup_test_env();
 
     let new_admin = Address::generate(&env);
     client.initialize(&new_admin);
@@ -225,7 +241,7 @@ fn test_batch_create_wallets_empty_batch() {
 #[test]
 #[should_panic]
 fn test_batch_create_wallets_unauthorized() {
-    let (env, admin, client) = setup_test_env();
+    let (env, _admin, client) = setup_test_env();
 
     let unauthorized = Address::generate(&env);
     let owner = Address::generate(&env);
@@ -316,7 +332,7 @@ fn test_batch_recover_wallets_single_success() {
     let (env, admin, client) = setup_test_env();
 
     let original_owner = Address::generate(&env);
-    let new_owner = Address::generate(&env);
+    let new_owner = generate_account(&env);
 
     let mut create_requests: Vec<WalletCreateRequest> = Vec::new(&env);
     create_requests.push_back(create_wallet_request(&env, original_owner.clone()));
@@ -331,7 +347,7 @@ fn test_batch_recover_wallets_single_success() {
     ));
 
     let recover_result: BatchRecoveryResult =
-        client.batch_recover_wallets(&admin, &recovery_requests);
+        client.batch_recover_wallets(&admin, &recovery_requests, &None);
 
     assert_eq!(recover_result.total_requests, 1);
     assert_eq!(recover_result.successful, 1);
@@ -361,8 +377,8 @@ fn test_batch_recover_wallets_partial_failures() {
     let existing_owner = Address::generate(&env);
     let other_existing_owner = Address::generate(&env);
     let non_existing_owner = Address::generate(&env);
-    let recovery_target_1 = Address::generate(&env);
-    let recovery_target_2 = Address::generate(&env);
+    let recovery_target_1 = generate_account(&env);
+    let recovery_target_2 = generate_account(&env);
 
     let mut create_requests: Vec<WalletCreateRequest> = Vec::new(&env);
     create_requests.push_back(create_wallet_request(&env, existing_owner.clone()));
@@ -389,7 +405,7 @@ fn test_batch_recover_wallets_partial_failures() {
         recovery_target_2.clone(),
     ));
 
-    let recover_result = client.batch_recover_wallets(&admin, &recovery_requests);
+    let recover_result = client.batch_recover_wallets(&admin, &recovery_requests, &None);
 
     assert_eq!(recover_result.total_requests, 3);
     assert_eq!(recover_result.successful, 1);
@@ -433,7 +449,7 @@ fn test_batch_recover_wallets_events_emitted() {
     let (env, admin, client) = setup_test_env();
 
     let original_owner = Address::generate(&env);
-    let new_owner = Address::generate(&env);
+    let new_owner = generate_account(&env);
 
     let mut create_requests: Vec<WalletCreateRequest> = Vec::new(&env);
     create_requests.push_back(create_wallet_request(&env, original_owner.clone()));
@@ -446,7 +462,7 @@ fn test_batch_recover_wallets_events_emitted() {
         new_owner.clone(),
     ));
 
-    client.batch_recover_wallets(&admin, &recovery_requests);
+    client.batch_recover_wallets(&admin, &recovery_requests, &None);
 
     let events = env.events().all();
     assert!(events.len() >= 3);
@@ -458,7 +474,7 @@ fn test_batch_recover_wallets_empty_batch() {
     let (env, admin, client) = setup_test_env();
 
     let recovery_requests: Vec<WalletRecoveryRequest> = Vec::new(&env);
-    client.batch_recover_wallets(&admin, &recovery_requests);
+    client.batch_recover_wallets(&admin, &recovery_requests, &None);
 }
 
 #[test]
@@ -467,7 +483,7 @@ fn test_batch_recover_wallets_unauthorized() {
     let (env, _admin, client) = setup_test_env();
 
     let original_owner = Address::generate(&env);
-    let new_owner = Address::generate(&env);
+    let new_owner = generate_account(&env);
 
     let mut recovery_requests: Vec<WalletRecoveryRequest> = Vec::new(&env);
     recovery_requests.push_back(create_recovery_request(
@@ -477,5 +493,1188 @@ fn test_batch_recover_wallets_unauthorized() {
     ));
 
     let unauthorized = Address::generate(&env);
-    client.batch_recover_wallets(&unauthorized, &recovery_requests);
+    client.batch_recover_wallets(&unauthorized, &recovery_requests, &None);
+}
+
+// Freeze Tests
+
+#[test]
+fn test_batch_freeze_and_unfreeze_wallets() {
+    let (env, admin, client) = setup_test_env();
+
+    let owner1 = Address::generate(&env);
+    let owner2 = Address::generate(&env);
+    let missing = Address::generate(&env);
+
+    let mut create_requests: Vec<WalletCreateRequest> = Vec::new(&env);
+    create_requests.push_back(create_wallet_request(&env, owner1.clone()));
+    create_requests.push_back(create_wallet_request(&env, owner2.clone()));
+    client.batch_create_wallets(&admin, &create_requests);
+
+    let mut owners: Vec<Address> = Vec::new(&env);
+    owners.push_back(owner1.clone());
+    owners.push_back(missing.clone());
+    owners.push_back(owner2.clone());
+
+    let result = client.batch_freeze_wallets(&admin, &owners);
+
+    assert_eq!(result.total_requests, 3);
+    assert_eq!(result.successful, 2);
+    assert_eq!(result.failed, 1);
+    match result.results.get(1).unwrap() {
+        WalletFreezeResult::Failure(addr, code) => {
+            assert_eq!(addr, missing);
+            assert_eq!(code, 1); // Wallet not found
+        }
+        _ => panic!("expected failure for missing wallet"),
+    }
+    assert!(client.is_frozen(&owner1));
+    assert!(client.is_frozen(&owner2));
+    assert!(!client.is_frozen(&missing));
+
+    let result = client.batch_unfreeze_wallets(&admin, &owners);
+
+    assert_eq!(result.successful, 2);
+    assert_eq!(result.failed, 1);
+    assert!(!client.is_frozen(&owner1));
+    assert!(!client.is_frozen(&owner2));
+}
+
+#[test]
+fn test_frozen_wallet_cannot_be_recovered() {
+    let (env, admin, client) = setup_test_env();
+
+    let owner = Address::generate(&env);
+    let new_owner = generate_account(&env);
+
+    let mut create_requests: Vec<WalletCreateRequest> = Vec::new(&env);
+    create_requests.push_back(create_wallet_request(&env, owner.clone()));
+    client.batch_create_wallets(&admin, &create_requests);
+
+    let mut owners: Vec<Address> = Vec::new(&env);
+    owners.push_back(owner.clone());
+    client.batch_freeze_wallets(&admin, &owners);
+
+    let mut recovery_requests: Vec<WalletRecoveryRequest> = Vec::new(&env);
+    recovery_requests.push_back(create_recovery_request(&env, owner.clone(), new_owner.clone()));
+
+    let result = client.batch_recover_wallets(&admin, &recovery_requests, &None);
+
+    assert_eq!(result.failed, 1);
+    match result.results.get(0).unwrap() {
+        WalletRecoveryResult::Failure(_, _, code) => assert_eq!(code, 3),
+        _ => panic!("expected failure for frozen wallet"),
+    }
+    assert!(client.get_wallet(&owner).is_some());
+}
+
+#[test]
+fn test_recovery_to_contract_destination_blocked_by_default() {
+    let (env, admin, client) = setup_test_env();
+
+    let owner = Address::generate(&env);
+    let contract_destination = Address::generate(&env);
+
+    let mut create_requests: Vec<WalletCreateRequest> = Vec::new(&env);
+    create_requests.push_back(create_wallet_request(&env, owner.clone()));
+    client.batch_create_wallets(&admin, &create_requests);
+
+    let mut recovery_requests: Vec<WalletRecoveryRequest> = Vec::new(&env);
+    recovery_requests.push_back(create_recovery_request(
+        &env,
+        owner.clone(),
+        contract_destination.clone(),
+    ));
+
+    assert!(!client.get_allow_contract_destinations());
+    let result = client.batch_recover_wallets(&admin, &recovery_requests, &None);
+    assert_eq!(result.failed, 1);
+    match result.results.get(0).unwrap() {
+        WalletRecoveryResult::Failure(_, _, code) => assert_eq!(code, 4),
+        _ => panic!("expected failure for contract destination"),
+    }
+
+    client.set_allow_contract_destinations(&admin, &true);
+    let result = client.batch_recover_wallets(&admin, &recovery_requests, &None);
+    assert_eq!(result.successful, 1);
+    assert_eq!(client.get_wallet(&contract_destination).unwrap().id, 1);
+}
+
+#[test]
+fn test_keyed_recovery_runs_only_once() {
+    let (env, admin, client) = setup_test_env();
+
+    let owner = Address::generate(&env);
+    let new_owner = generate_account(&env);
+    let later_owner = generate_account(&env);
+
+    let mut create_requests: Vec<WalletCreateRequest> = Vec::new(&env);
+    create_requests.push_back(create_wallet_request(&env, owner.clone()));
+    client.batch_create_wallets(&admin, &create_requests);
+
+    let mut recovery_requests: Vec<WalletRecoveryRequest> = Vec::new(&env);
+    recovery_requests.push_back(create_recovery_request(&env, owner.clone(), new_owner.clone()));
+    let key = Some(BytesN::from_array(&env, &[7u8; 32]));
+
+    let first = client.batch_recover_wallets(&admin, &recovery_requests, &key);
+    assert_eq!(first.successful, 1);
+    assert_eq!(client.get_total_batches(), 2);
+
+    // A wallet created for the old owner after recovery must not be re-owned by a replay
+    client.batch_create_wallets(&admin, &create_requests);
+    let replay = client.batch_recover_wallets(&admin, &recovery_requests, &key);
+
+    assert_eq!(replay.successful, first.successful);
+    assert_eq!(replay.failed, first.failed);
+    assert_eq!(client.get_total_batches(), 3);
+    assert_eq!(client.get_wallet(&owner).unwrap().id, 2);
+    assert_eq!(client.get_wallet(&new_owner).unwrap().id, 1);
+
+    // A different key executes normally
+    let mut other_requests: Vec<WalletRecoveryRequest> = Vec::new(&env);
+    other_requests.push_back(create_recovery_request(&env, owner.clone(), later_owner.clone()));
+    let other_key = Some(BytesN::from_array(&env, &[8u8; 32]));
+    let other = client.batch_recover_wallets(&admin, &other_requests, &other_key);
+    assert_eq!(other.successful, 1);
+    assert_eq!(client.get_wallet(&later_owner).unwrap().id, 2);
+}
+
+// External Id Tests
+
+#[test]
+fn test_batch_link_external_ids() {
+    let (env, admin, client) = setup_test_env();
+
+    let owner1 = Address::generate(&env);
+    let owner2 = Address::generate(&env);
+    let owner3 = Address::generate(&env);
+    let missing = Address::generate(&env);
+
+    let mut create_requests: Vec<WalletCreateRequest> = Vec::new(&env);
+    create_requests.push_back(create_wallet_request(&env, owner1.clone()));
+    create_requests.push_back(create_wallet_request(&env, owner2.clone()));
+    create_requests.push_back(create_wallet_request(&env, owner3.clone()));
+    client.batch_create_wallets(&admin, &create_requests);
+
+    let id1 = BytesN::from_array(&env, &[1u8; 32]);
+    let id2 = BytesN::from_array(&env, &[2u8; 32]);
+    let id3 = BytesN::from_array(&env, &[3u8; 32]);
+
+    let mut links: Vec<(Address, BytesN<32>)> = Vec::new(&env);
+    links.push_back((owner1.clone(), id1.clone()));
+    links.push_back((owner2.clone(), id2.clone()));
+    links.push_back((owner3.clone(), id1.clone())); // Collision with owner1
+    links.push_back((missing.clone(), id3.clone()));
+
+    let result = client.batch_link_external_ids(&admin, &links);
+
+    assert_eq!(result.total_requests, 4);
+    assert_eq!(result.successful, 2);
+    assert_eq!(result.failed, 2);
+    match result.results.get(2).unwrap() {
+        ExternalIdLinkResult::Failure(addr, _, code) => {
+            assert_eq!(addr, owner3);
+            assert_eq!(code, 2); // External id taken
+        }
+        _ => panic!("expected failure for taken external id"),
+    }
+    match result.results.get(3).unwrap() {
+        ExternalIdLinkResult::Failure(addr, _, code) => {
+            assert_eq!(addr, missing);
+            assert_eq!(code, 1); // Wallet not found
+        }
+        _ => panic!("expected failure for missing wallet"),
+    }
+
+    assert_eq!(client.get_external_id(&owner1), Some(id1.clone()));
+    assert_eq!(client.get_external_id(&owner2), Some(id2.clone()));
+    assert_eq!(client.get_external_id(&owner3), None);
+    assert_eq!(client.get_owner_by_external_id(&id1), Some(owner1));
+    assert_eq!(client.get_owner_by_external_id(&id3), None);
+}
+
+#[test]
+fn test_external_id_follows_recovered_wallet() {
+    let (env, admin, client) = setup_test_env();
+
+    let owner = Address::generate(&env);
+    let new_owner = generate_account(&env);
+    let external_id = BytesN::from_array(&env, &[9u8; 32]);
+
+    let mut create_requests: Vec<WalletCreateRequest> = Vec::new(&env);
+    create_requests.push_back(create_wallet_request(&env, owner.clone()));
+    client.batch_create_wallets(&admin, &create_requests);
+    client.link_external_id(&admin, &owner, &external_id);
+
+    let mut recovery_requests: Vec<WalletRecoveryRequest> = Vec::new(&env);
+    recovery_requests.push_back(create_recovery_request(&env, owner.clone(), new_owner.clone()));
+    client.batch_recover_wallets(&admin, &recovery_requests, &None);
+
+    assert_eq!(client.get_external_id(&owner), None);
+    assert_eq!(client.get_external_id(&new_owner), Some(external_id.clone()));
+    assert_eq!(client.get_owner_by_external_id(&external_id), Some(new_owner));
+}
+
+#[test]
+fn test_count_by_external_prefix() {
+    let (env, admin, client) = setup_test_env();
+    let owners = create_wallets(&env, &admin, &client, 4);
+
+    let external_id = |tenant: u8, account: u8| {
+        let mut bytes = [account; 32];
+        bytes[..4].copy_from_slice(&[tenant; 4]);
+        BytesN::from_array(&env, &bytes)
+    };
+    let mut links: Vec<(Address, BytesN<32>)> = Vec::new(&env);
+    links.push_back((owners.get(0).unwrap(), external_id(0xaa, 1)));
+    links.push_back((owners.get(1).unwrap(), external_id(0xaa, 2)));
+    links.push_back((owners.get(2).unwrap(), external_id(0xaa, 3)));
+    links.push_back((owners.get(3).unwrap(), external_id(0xbb, 1)));
+    client.batch_link_external_ids(&admin, &links);
+
+    let tenant_a = Bytes::from_array(&env, &[0xaa; 4]);
+    let tenant_b = Bytes::from_array(&env, &[0xbb; 4]);
+    assert_eq!(client.count_by_external_prefix(&tenant_a), 3);
+    assert_eq!(client.count_by_external_prefix(&tenant_b), 1);
+
+    // Moving a wallet to another tenant and unlinking update the counters
+    client.link_external_id(&admin, &owners.get(0).unwrap(), &external_id(0xbb, 9));
+    client.unlink_external_id(&admin, &owners.get(1).unwrap());
+    assert_eq!(client.count_by_external_prefix(&tenant_a), 1);
+    assert_eq!(client.count_by_external_prefix(&tenant_b), 2);
+    assert_eq!(client.get_external_id(&owners.get(1).unwrap()), None);
+    assert_eq!(client.get_owner_by_external_id(&external_id(0xaa, 2)), None);
+}
+
+// Configuration Tests
+
+#[test]
+fn test_wallet_config_defaults() {
+    let (_env, _admin, client) = setup_test_env();
+
+    assert_eq!(
+        client.get_wallet_config(),
+        WalletConfig {
+            max_batch_size: 100,
+            max_recovery_batch_size: 100,
+            recovery_delay_ledgers: 0,
+            max_wallets: 0,
+            batch_cooldown_ledgers: 0,
+            allow_contract_destinations: false,
+        }
+    );
+}
+
+#[test]
+fn test_wallet_config_reflects_setters() {
+    let (_env, admin, client) = setup_test_env();
+
+    client.set_max_batch_size(&admin, &25);
+    client.set_max_recovery_batch_size(&admin, &10);
+    client.set_max_wallets(&admin, &1_000);
+    client.set_allow_contract_destinations(&admin, &true);
+
+    let config = client.get_wallet_config();
+    assert_eq!(config.max_batch_size, 25);
+    assert_eq!(config.max_recovery_batch_size, 10);
+    assert_eq!(config.max_wallets, 1_000);
+    assert!(config.allow_contract_destinations);
+}
+
+#[test]
+#[should_panic]
+fn test_batch_exceeding_configured_size_rejected() {
+    let (env, admin, client) = setup_test_env();
+    client.set_max_batch_size(&admin, &2);
+
+    let mut requests: Vec<WalletCreateRequest> = Vec::new(&env);
+    for _ in 0..3 {
+        requests.push_back(create_wallet_request(&env, Address::generate(&env)));
+    }
+
+    client.batch_create_wallets(&admin, &requests);
+}
+
+#[test]
+fn test_max_wallets_caps_creation() {
+    let (env, admin, client) = setup_test_env();
+    client.set_max_wallets(&admin, &2);
+
+    let mut requests: Vec<WalletCreateRequest> = Vec::new(&env);
+    for _ in 0..3 {
+        requests.push_back(create_wallet_request(&env, Address::generate(&env)));
+    }
+
+    let result = client.batch_create_wallets(&admin, &requests);
+
+    assert_eq!(result.successful, 2);
+    assert_eq!(result.failed, 1);
+    match result.results.get(2).unwrap() {
+        WalletCreateResult::Failure(_, error_code) => assert_eq!(error_code, 2),
+        _ => panic!("Expected failure once the wallet cap is reached"),
+    }
+    assert_eq!(client.get_total_wallets_created(), 2);
+}
+
+// Migration Tests
+
+fn create_wallets(
+    env: &Env,
+    admin: &Address,
+    client: &BatchWalletContractClient,
+    count: u32,
+) -> Vec<Address> {
+    let mut owners: Vec<Address> = Vec::new(env);
+    let mut requests: Vec<WalletCreateRequest> = Vec::new(env);
+    for _ in 0..count {
+        let owner = Address::generate(env);
+        owners.push_back(owner.clone());
+        requests.push_back(create_wallet_request(env, owner));
+    }
+    client.batch_create_wallets(admin, &requests);
+    owners
+}
+
+#[test]
+fn test_remap_wallet_id() {
+    let (env, admin, client) = setup_test_env();
+    let owners = create_wallets(&env, &admin, &client, 2);
+    let owner = owners.get(0).unwrap();
+
+    client.set_migration_mode(&admin, &true);
+    client.remap_wallet_id(&admin, &owner, &500);
+
+    assert_eq!(client.get_wallet(&owner).unwrap().id, 500);
+    assert_eq!(client.get_wallet_by_id(&500).unwrap().owner, owner);
+    assert!(client.get_wallet_by_id(&1).is_none());
+}
+
+#[test]
+fn test_id_bounds_track_remaining_extremes() {
+    let (env, admin, client) = setup_test_env();
+    assert_eq!(client.get_id_bounds(), (0, 0));
+
+    let owners = create_wallets(&env, &admin, &client, 3);
+    assert_eq!(client.get_id_bounds(), (1, 3));
+
+    // Vacate the lowest id by moving its wallet above the current maximum
+    client.set_migration_mode(&admin, &true);
+    client.remap_wallet_id(&admin, &owners.get(0).unwrap(), &40);
+    assert_eq!(client.get_id_bounds(), (2, 40));
+
+    // Vacate the highest id again, leaving a gap at the top
+    client.remap_wallet_id(&admin, &owners.get(0).unwrap(), &20);
+    assert_eq!(client.get_id_bounds(), (2, 20));
+
+    // Tombstones at either end no longer count
+    let extra = create_wallets(&env, &admin, &client, 1);
+    assert_eq!(client.get_id_bounds(), (2, 41));
+    let mut deleted: Vec<Address> = Vec::new(&env);
+    deleted.push_back(owners.get(1).unwrap());
+    deleted.push_back(extra.get(0).unwrap());
+    client.batch_delete_wallets(&admin, &deleted);
+    assert_eq!(client.get_id_bounds(), (3, 20));
+}
+
+#[test]
+fn test_bump_wallets_ttl_extends_only_the_range() {
+    let (env, admin, client) = setup_test_env();
+    let owners = create_wallets(&env, &admin, &client, 3);
+    let ttl = |owner: Address| {
+        env.as_contract(&client.address, || {
+            env.storage().persistent().get_ttl(&DataKey::Wallets(owner))
+        })
+    };
+
+    // Move close to the default expiry of freshly created entries
+    let initial_ttl = ttl(owners.get(0).unwrap());
+    env.ledger().with_mut(|li| li.sequence_number += initial_ttl - 10);
+
+    assert_eq!(client.bump_wallets_ttl(&admin, &1, &2), 2);
+    assert_eq!(ttl(owners.get(0).unwrap()), WALLET_TTL_EXTEND);
+    assert_eq!(ttl(owners.get(1).unwrap()), WALLET_TTL_EXTEND);
+    assert!(ttl(owners.get(2).unwrap()) <= 10);
+    assert!(client.get_wallet(&owners.get(1).unwrap()).is_some());
+
+    // Unassigned ids are skipped
+    assert_eq!(client.bump_wallets_ttl(&admin, &3, &50), 1);
+}
+
+#[test]
+fn test_remap_wallet_id_rejects_taken_id() {
+    let (env, admin, client) = setup_test_env();
+    let owners = create_wallets(&env, &admin, &client, 2);
+
+    client.set_migration_mode(&admin, &true);
+    let result = client.try_remap_wallet_id(&admin, &owners.get(0).unwrap(), &2);
+
+    assert_eq!(result, Err(Ok(BatchWalletError::IdTaken.into())));
+    assert_eq!(client.get_wallet(&owners.get(0).unwrap()).unwrap().id, 1);
+}
+
+#[test]
+fn test_remap_wallet_id_requires_migration_mode() {
+    let (env, admin, client) = setup_test_env();
+    let owners = create_wallets(&env, &admin, &client, 1);
+
+    let result = client.try_remap_wallet_id(&admin, &owners.get(0).unwrap(), &500);
+    assert_eq!(result, Err(Ok(BatchWalletError::MigrationInactive.into())));
+
+    client.set_migration_mode(&admin, &true);
+    client.set_migration_mode(&admin, &false);
+    let result = client.try_remap_wallet_id(&admin, &owners.get(0).unwrap(), &500);
+    assert_eq!(result, Err(Ok(BatchWalletError::MigrationInactive.into())));
+}
+
+// Address Derivation Tests
+
+#[test]
+fn test_verify_wallet_address() {
+    let (env, _admin, client) = setup_test_env();
+
+    let owner = Address::generate(&env);
+    let salt = BytesN::from_array(&env, &[5u8; 32]);
+
+    let mut preimage = owner.clone().to_xdr(&env);
+    preimage.append(&salt.clone().into());
+    let owner_salt: BytesN<32> = env.crypto().sha256(&preimage).into();
+    let derived = env.as_contract(&client.address, || {
+        env.deployer()
+            .with_current_contract(owner_salt)
+            .deployed_address()
+    });
+
+    assert!(client.verify_wallet_address(&owner, &salt, &derived));
+
+    let other_salt = BytesN::from_array(&env, &[6u8; 32]);
+    assert!(!client.verify_wallet_address(&owner, &other_salt, &derived));
+    assert!(!client.verify_wallet_address(&Address::generate(&env), &salt, &derived));
+    assert!(!client.verify_wallet_address(&owner, &salt, &Address::generate(&env)));
+}
+
+// Pause Tests
+
+#[test]
+fn test_pause_recovery_keeps_creation_open() {
+    let (env, admin, client) = setup_test_env();
+    let owners = create_wallets(&env, &admin, &client, 1);
+
+    client.pause_recovery(&admin);
+    assert!(client.is_recovery_paused());
+    assert!(!client.is_creation_paused());
+
+    let mut recovery_requests: Vec<WalletRecoveryRequest> = Vec::new(&env);
+    recovery_requests.push_back(create_recovery_request(
+        &env,
+        owners.get(0).unwrap(),
+        generate_account(&env),
+    ));
+    let result = client.try_batch_recover_wallets(&admin, &recovery_requests, &None);
+    assert_eq!(result.err(), Some(Ok(BatchWalletError::RecoveryPaused.into())));
+
+    let mut create_requests: Vec<WalletCreateRequest> = Vec::new(&env);
+    create_requests.push_back(create_wallet_request(&env, Address::generate(&env)));
+    assert_eq!(client.batch_create_wallets(&admin, &create_requests).successful, 1);
+
+    client.unpause_recovery(&admin);
+    assert_eq!(
+        client
+            .batch_recover_wallets(&admin, &recovery_requests, &None)
+            .successful,
+        1
+    );
+}
+
+#[test]
+fn test_pause_creation_keeps_recovery_open() {
+    let (env, admin, client) = setup_test_env();
+    let owners = create_wallets(&env, &admin, &client, 1);
+
+    client.pause_creation(&admin);
+    assert!(client.is_creation_paused());
+    assert!(!client.is_recovery_paused());
+
+    let mut create_requests: Vec<WalletCreateRequest> = Vec::new(&env);
+    create_requests.push_back(create_wallet_request(&env, Address::generate(&env)));
+    let result = client.try_batch_create_wallets(&admin, &create_requests);
+    assert_eq!(result.err(), Some(Ok(BatchWalletError::CreationPaused.into())));
+
+    let mut recovery_requests: Vec<WalletRecoveryRequest> = Vec::new(&env);
+    recovery_requests.push_back(create_recovery_request(
+        &env,
+        owners.get(0).unwrap(),
+        generate_account(&env),
+    ));
+    assert_eq!(
+        client
+            .batch_recover_wallets(&admin, &recovery_requests, &None)
+            .successful,
+        1
+    );
+
+    client.unpause_creation(&admin);
+    assert_eq!(client.batch_create_wallets(&admin, &create_requests).successful, 1);
+}
+
+// Guardian Policy Tests
+
+#[test]
+fn test_guardian_policy_requires_minimum_guardians() {
+    let (env, admin, client) = setup_test_env();
+    client.set_guardian_policy(&admin, &2);
+    assert_eq!(client.get_guardian_policy(), 2);
+
+    let unguarded = Address::generate(&env);
+    let guarded = Address::generate(&env);
+    let mut guardians: Vec<Address> = Vec::new(&env);
+    guardians.push_back(Address::generate(&env));
+    guardians.push_back(Address::generate(&env));
+
+    let mut requests: Vec<WalletCreateRequest> = Vec::new(&env);
+    requests.push_back(create_wallet_request(&env, unguarded.clone()));
+    requests.push_back(WalletCreateRequest {
+        owner: guarded.clone(),
+        guardians: guardians.clone(),
+        active: true,
+    });
+
+    let result = client.batch_create_wallets(&admin, &requests);
+
+    assert_eq!(result.successful, 1);
+    assert_eq!(result.failed, 1);
+    match result.results.get(0).unwrap() {
+        WalletCreateResult::Failure(owner, error_code) => {
+            assert_eq!(owner, unguarded);
+            assert_eq!(error_code, 3); // Guardians required
+        }
+        _ => panic!("Expected failure for wallet without guardians"),
+    }
+    assert!(client.get_wallet(&unguarded).is_none());
+    assert!(client.get_wallet(&guarded).is_some());
+    assert_eq!(client.get_guardians(&guarded), guardians);
+}
+
+// Id Reservation Tests
+
+#[test]
+fn test_reserve_ids_and_create_out_of_order() {
+    let (env, admin, client) = setup_test_env();
+
+    let ids = client.reserve_ids(&admin, &3);
+    assert_eq!(ids, Vec::from_array(&env, [1u64, 2, 3]));
+
+    let owner3 = Address::generate(&env);
+    let owner1 = Address::generate(&env);
+    client.create_wallet_with_id(&admin, &owner3, &3);
+    client.create_wallet_with_id(&admin, &owner1, &1);
+
+    assert_eq!(client.get_wallet(&owner3).unwrap().id, 3);
+    assert_eq!(client.get_wallet(&owner1).unwrap().id, 1);
+    assert_eq!(client.get_total_wallets_created(), 2);
+
+    // Reserved ids are never handed out by batch creation
+    let owners = create_wallets(&env, &admin, &client, 1);
+    assert_eq!(client.get_wallet(&owners.get(0).unwrap()).unwrap().id, 4);
+
+    // Used and unreserved ids are rejected
+    let result = client.try_create_wallet_with_id(&admin, &Address::generate(&env), &3);
+    assert_eq!(result, Err(Ok(BatchWalletError::IdNotReserved.into())));
+    let result = client.try_create_wallet_with_id(&admin, &Address::generate(&env), &50);
+    assert_eq!(result, Err(Ok(BatchWalletError::IdNotReserved.into())));
+}
+
+#[test]
+fn test_remap_cannot_take_reserved_id() {
+    let (env, admin, client) = setup_test_env();
+    client.reserve_ids(&admin, &1);
+    let owner = create_wallets(&env, &admin, &client, 1).get(0).unwrap();
+    client.set_migration_mode(&admin, &true);
+
+    let result = client.try_remap_wallet_id(&admin, &owner, &1);
+    assert_eq!(result, Err(Ok(BatchWalletError::IdTaken.into())));
+
+    // The reservation stays usable and the existing wallet keeps its id
+    let reserved_owner = Address::generate(&env);
+    client.create_wallet_with_id(&admin, &reserved_owner, &1);
+    assert_eq!(client.get_wallet_by_id(&1).unwrap().owner, reserved_owner);
+    assert_eq!(client.get_wallet(&owner).unwrap().id, 2);
+
+    // Creating onto an id a wallet already holds fails even in migration mode
+    let result = client.try_create_wallet_with_id(&admin, &Address::generate(&env), &2);
+    assert_eq!(result, Err(Ok(BatchWalletError::IdTaken.into())));
+}
+
+#[test]
+fn test_manual_id_advances_auto_counter() {
+    let (env, admin, client) = setup_test_env();
+    client.set_migration_mode(&admin, &true);
+
+    let manual_owner = Address::generate(&env);
+    client.create_wallet_with_id(&admin, &manual_owner, &100);
+    assert_eq!(client.get_wallet(&manual_owner).unwrap().id, 100);
+
+    let owners = create_wallets(&env, &admin, &client, 1);
+    assert_eq!(client.get_wallet(&owners.get(0).unwrap()).unwrap().id, 101);
+    assert_eq!(client.get_wallet_by_id(&100).unwrap().owner, manual_owner);
+
+    let result = client.try_create_wallet_with_id(&admin, &Address::generate(&env), &100);
+    assert_eq!(result, Err(Ok(BatchWalletError::IdTaken.into())));
+    let result = client.try_create_wallet_with_id(&admin, &Address::generate(&env), &u64::MAX);
+    assert_eq!(result, Err(Ok(BatchWalletError::InvalidWalletId.into())));
+}
+
+#[test]
+fn test_first_wallet_after_initialize_gets_id_one() {
+    let (env, admin, client) = setup_test_env();
+
+    let owners = create_wallets(&env, &admin, &client, 1);
+
+    assert_eq!(client.get_wallet(&owners.get(0).unwrap()).unwrap().id, 1);
+    assert_eq!(client.get_wallet_by_id(&1).unwrap().owner, owners.get(0).unwrap());
+    assert!(client.get_wallet_by_id(&0).is_none());
+}
+
+#[test]
+fn test_id_offset_seeds_counter() {
+    let (env, admin, client) = setup_test_env();
+    client.set_id_offset(&admin, &1_000_000);
+
+    let owners = create_wallets(&env, &admin, &client, 2);
+
+    assert_eq!(client.get_wallet(&owners.get(0).unwrap()).unwrap().id, 1_000_000);
+    assert_eq!(client.get_wallet(&owners.get(1).unwrap()).unwrap().id, 1_000_001);
+    let result = client.try_set_id_offset(&admin, &5);
+    assert_eq!(result, Err(Ok(BatchWalletError::IdOffsetLocked.into())));
+}
+
+#[test]
+fn test_id_bounds_scan_is_capped_across_wide_gaps() {
+    let (env, admin, client) = setup_test_env();
+    let owners = create_wallets(&env, &admin, &client, 3);
+    client.set_migration_mode(&admin, &true);
+    client.remap_wallet_id(&admin, &owners.get(0).unwrap(), &1_000_000);
+    client.batch_delete_wallets(&admin, &Vec::from_array(&env, [owners.get(0).unwrap()]));
+
+    // The top edge stops after MAX_BATCH_SIZE reads inside the gap
+    assert_eq!(client.get_id_bounds(), (2, 999_900));
+
+    client.batch_delete_wallets(&admin, &Vec::from_array(&env, [owners.get(1).unwrap()]));
+    assert_eq!(client.get_id_bounds(), (3, 999_900));
+}
+
+#[test]
+fn test_id_bounds_start_at_id_offset() {
+    let (env, admin, client) = setup_test_env();
+    client.set_id_offset(&admin, &1_000_000);
+    let owners = create_wallets(&env, &admin, &client, 3);
+    assert_eq!(client.get_id_bounds(), (1_000_000, 1_000_002));
+
+    // A remap below the offset lowers where the scan starts
+    client.set_migration_mode(&admin, &true);
+    client.remap_wallet_id(&admin, &owners.get(1).unwrap(), &500);
+    assert_eq!(client.get_id_bounds(), (500, 1_000_002));
+}
+
+// Recovery Simulation Tests
+
+#[test]
+fn test_simulated_recovery_matches_real_run() {
+    let (env, admin, client) = setup_test_env();
+    let owners = create_wallets(&env, &admin, &client, 3);
+    let frozen_owners = Vec::from_array(&env, [owners.get(1).unwrap()]);
+    client.batch_freeze_wallets(&admin, &frozen_owners);
+
+    let destination = generate_account(&env);
+    let unknown_owner = Address::generate(&env);
+    let mut requests: Vec<WalletRecoveryRequest> = Vec::new(&env);
+    for (old_owner, new_owner) in [
+        (owners.get(0).unwrap(), destination.clone()),
+        (owners.get(1).unwrap(), generate_account(&env)),
+        // Destination taken by the first item of the same batch
+        (owners.get(2).unwrap(), destination.clone()),
+        (unknown_owner, generate_account(&env)),
+    ] {
+        requests.push_back(create_recovery_request(&env, old_owner, new_owner));
+    }
+
+    let simulated = client.simulate_batch_recover(&admin, &requests);
+    assert!(client.get_wallet(&destination).is_none());
+    assert!(client.get_wallet(&owners.get(0).unwrap()).is_some());
+
+    let real = client.batch_recover_wallets(&admin, &requests, &None);
+
+    assert_eq!(simulated.successful, 1);
+    assert_eq!(simulated.successful, real.successful);
+    assert_eq!(simulated.failed, real.failed);
+    for i in 0..requests.len() {
+        match (simulated.results.get(i).unwrap(), real.results.get(i).unwrap()) {
+            (WalletRecoveryResult::Success(_, _), WalletRecoveryResult::Success(_, _)) => {}
+            (
+                WalletRecoveryResult::Failure(_, _, simulated_code),
+                WalletRecoveryResult::Failure(_, _, real_code),
+            ) => assert_eq!(simulated_code, real_code),
+            _ => panic!("Simulated outcome differs from real outcome at {}", i),
+        }
+    }
+}
+
+#[test]
+fn test_identity_recovery_noop_flag() {
+    let (env, admin, client) = setup_test_env();
+    let owners = create_wallets(&env, &admin, &client, 1);
+    let owner = owners.get(0).unwrap();
+    let mut requests: Vec<WalletRecoveryRequest> = Vec::new(&env);
+    requests.push_back(create_recovery_request(&env, owner.clone(), owner.clone()));
+
+    let result = client.batch_recover_wallets(&admin, &requests, &None);
+    assert_eq!(result.failed, 1);
+    match result.results.get(0).unwrap() {
+        WalletRecoveryResult::Failure(_, _, code) => assert_eq!(code, 2),
+        _ => panic!("expected identity recovery to fail by default"),
+    }
+
+    client.set_identity_recovery_ok(&admin, &true);
+    let result = client.batch_recover_wallets(&admin, &requests, &None);
+    assert_eq!(result.successful, 1);
+    match result.results.get(0).unwrap() {
+        WalletRecoveryResult::NoOp(addr) => assert_eq!(addr, owner),
+        _ => panic!("expected identity recovery to be a no-op"),
+    }
+    let wallet = client.get_wallet(&owner).unwrap();
+    assert_eq!(wallet.owner, owner);
+    assert_eq!(client.get_wallet_by_id(&wallet.id).unwrap().owner, owner);
+}
+
+#[test]
+fn test_recovery_admin_separates_recovery_from_creation() {
+    let (env, admin, client) = setup_test_env();
+    let owners = create_wallets(&env, &admin, &client, 2);
+    let recovery_admin = Address::generate(&env);
+    assert_eq!(client.get_recovery_admin(), admin);
+    client.set_recovery_admin(&admin, &recovery_admin);
+
+    let mut requests: Vec<WalletRecoveryRequest> = Vec::new(&env);
+    requests.push_back(create_recovery_request(
+        &env,
+        owners.get(0).unwrap(),
+        generate_account(&env),
+    ));
+
+    let result = client.try_batch_recover_wallets(&admin, &requests, &None);
+    assert_eq!(result.err(), Some(Ok(BatchWalletError::Unauthorized.into())));
+    let result = client.batch_recover_wallets(&recovery_admin, &requests, &None);
+    assert_eq!(result.successful, 1);
+
+    let mut create: Vec<WalletCreateRequest> = Vec::new(&env);
+    create.push_back(create_wallet_request(&env, Address::generate(&env)));
+    let result = client.try_batch_create_wallets(&recovery_admin, &create);
+    assert_eq!(result.err(), Some(Ok(BatchWalletError::Unauthorized.into())));
+    assert_eq!(client.batch_create_wallets(&admin, &create).successful, 1);
+}
+
+#[test]
+fn test_wallet_profile_bundles_all_attributes() {
+    let (env, admin, client) = setup_test_env();
+    let old_owner = Address::generate(&env);
+    let mut guardians: Vec<Address> = Vec::new(&env);
+    guardians.push_back(Address::generate(&env));
+    let mut create: Vec<WalletCreateRequest> = Vec::new(&env);
+    create.push_back(WalletCreateRequest {
+        owner: old_owner.clone(),
+        guardians: guardians.clone(),
+        active: false,
+    });
+    client.batch_create_wallets(&admin, &create);
+    assert!(client.get_wallet_profile(&Address::generate(&env)).is_none());
+
+    let external_id = BytesN::from_array(&env, &[7u8; 32]);
+    client.link_external_id(&admin, &old_owner, &external_id);
+    client.set_wallet_label(&admin, &old_owner, &String::from_str(&env, "treasury"));
+
+    // Recovery carries every attribute over and bumps the nonce
+    let owner = generate_account(&env);
+    let mut requests: Vec<RecoveryWithGuardians> = Vec::new(&env);
+    requests.push_back(RecoveryWithGuardians {
+        old_owner: old_owner.clone(),
+        new_owner: owner.clone(),
+        guardians: guardians.clone(),
+        threshold: 1,
+    });
+    client.batch_recover_reset_guardians(&admin, &requests);
+    let mut freeze: Vec<Address> = Vec::new(&env);
+    freeze.push_back(owner.clone());
+    client.batch_freeze_wallets(&admin, &freeze);
+
+    let profile = client.get_wallet_profile(&owner).unwrap();
+    assert_eq!(profile.wallet.id, 1);
+    assert_eq!(profile.wallet.owner, owner);
+    assert!(profile.frozen);
+    assert!(!profile.active);
+    assert_eq!(profile.guardians, guardians);
+    assert_eq!(profile.threshold, 1);
+    assert_eq!(profile.external_id, Some(external_id));
+    assert_eq!(profile.label, Some(String::from_str(&env, "treasury")));
+    assert_eq!(profile.created_ledger, 12345);
+    assert_eq!(profile.recovery_nonce, 1);
+    assert!(client.get_wallet_profile(&old_owner).is_none());
+}
+
+#[test]
+fn test_recover_with_guardians_reset_replaces_guardian_set() {
+    let (env, admin, client) = setup_test_env();
+    let old_owner = Address::generate(&env);
+    let mut old_guardians: Vec<Address> = Vec::new(&env);
+    old_guardians.push_back(Address::generate(&env));
+    let mut create: Vec<WalletCreateRequest> = Vec::new(&env);
+    create.push_back(WalletCreateRequest {
+        owner: old_owner.clone(),
+        guardians: old_guardians,
+        active: true,
+    });
+    client.batch_create_wallets(&admin, &create);
+
+    let new_owner = generate_account(&env);
+    let mut new_guardians: Vec<Address> = Vec::new(&env);
+    new_guardians.push_back(Address::generate(&env));
+    new_guardians.push_back(Address::generate(&env));
+    let mut requests: Vec<RecoveryWithGuardians> = Vec::new(&env);
+    requests.push_back(RecoveryWithGuardians {
+        old_owner: old_owner.clone(),
+        new_owner: new_owner.clone(),
+        guardians: new_guardians.clone(),
+        threshold: 3,
+    });
+
+    // A threshold above the guardian count is rejected and nothing moves
+    let result = client.batch_recover_reset_guardians(&admin, &requests);
+    match result.results.get(0).unwrap() {
+        WalletRecoveryResult::Failure(_, _, code) => assert_eq!(code, 5),
+        _ => panic!("expected invalid guardian config to fail"),
+    }
+    assert!(client.get_wallet(&old_owner).is_some());
+
+    let mut request = requests.get(0).unwrap();
+    request.threshold = 2;
+    requests.set(0, request);
+    let result = client.batch_recover_reset_guardians(&admin, &requests);
+
+    assert_eq!(result.successful, 1);
+    assert!(client.get_wallet(&old_owner).is_none());
+    assert_eq!(client.get_wallet(&new_owner).unwrap().owner, new_owner);
+    assert_eq!(client.get_guardians(&new_owner), new_guardians);
+    assert_eq!(client.get_guardian_threshold(&new_owner), 2);
+    assert!(client.get_guardians(&old_owner).is_empty());
+    assert_eq!(client.get_guardian_threshold(&old_owner), 0);
+}
+
+// Merkle Root Tests
+
+fn hash_pair(env: &Env, left: &BytesN<32>, right: &BytesN<32>) -> BytesN<32> {
+    let mut preimage = Bytes::from(left.clone());
+    preimage.append(&Bytes::from(right.clone()));
+    env.crypto().sha256(&preimage).into()
+}
+
+#[test]
+fn test_wallet_root_verifies_leaf_proof() {
+    let (env, admin, client) = setup_test_env();
+    let owners = create_wallets(&env, &admin, &client, 3);
+
+    let root = client.compute_wallet_root(&1, &10);
+
+    // Leaf for wallet 2, proven with sibling leaf 1 and the carried-up leaf 3
+    let leaf = |id: u64, owner: Address| -> BytesN<32> {
+        let mut preimage = Bytes::from_array(&env, &id.to_be_bytes());
+        preimage.append(&owner.to_xdr(&env));
+        env.crypto().sha256(&preimage).into()
+    };
+    let leaf1 = leaf(1, owners.get(0).unwrap());
+    let leaf2 = leaf(2, owners.get(1).unwrap());
+    let leaf3 = leaf(3, owners.get(2).unwrap());
+    assert_eq!(hash_pair(&env, &hash_pair(&env, &leaf1, &leaf2), &leaf3), root);
+
+    assert_eq!(client.compute_wallet_root(&3, &1), leaf3);
+
+    // Deleted wallets drop out of the tree and are no longer bumped
+    let deleted = Vec::from_array(&env, [owners.get(0).unwrap()]);
+    client.batch_delete_wallets(&admin, &deleted);
+    assert_eq!(client.compute_wallet_root(&1, &10), hash_pair(&env, &leaf2, &leaf3));
+    assert_eq!(client.bump_wallets_ttl(&admin, &1, &10), 2);
+    assert_eq!(
+        client.compute_wallet_root(&50, &10),
+        BytesN::from_array(&env, &[0u8; 32])
+    );
+}
+
+// Activation Tests
+
+#[test]
+fn test_create_wallets_with_active_flags() {
+    let (env, admin, client) = setup_test_env();
+
+    let active_owner = Address::generate(&env);
+    let dormant_owner = Address::generate(&env);
+    let mut requests: Vec<WalletCreateRequest> = Vec::new(&env);
+    requests.push_back(create_wallet_request(&env, active_owner.clone()));
+    requests.push_back(WalletCreateRequest {
+        owner: dormant_owner.clone(),
+        guardians: Vec::new(&env),
+        active: false,
+    });
+
+    let result = client.batch_create_wallets(&admin, &requests);
+
+    assert_eq!(result.successful, 2);
+    assert!(client.is_active(&active_owner));
+    assert!(!client.is_active(&dormant_owner));
+
+    client.set_wallet_active(&admin, &dormant_owner, &true);
+    assert!(client.is_active(&dormant_owner));
+}
+
+#[test]
+fn test_delegate_can_be_set_and_cleared() {
+    let (env, admin, client) = setup_test_env();
+    let owners = create_wallets(&env, &admin, &client, 1);
+    let owner = owners.get(0).unwrap();
+    let delegate = Address::generate(&env);
+    assert_eq!(client.get_delegate(&owner), None);
+
+    client.set_delegate(&owner, &delegate);
+    assert_eq!(client.get_delegate(&owner), Some(delegate));
+    assert_eq!(client.get_wallet(&owner).unwrap().owner, owner);
+
+    client.clear_delegate(&owner);
+    assert_eq!(client.get_delegate(&owner), None);
+
+    let result = client.try_set_delegate(&Address::generate(&env), &Address::generate(&env));
+    assert_eq!(result.err(), Some(Ok(BatchWalletError::WalletNotFound.into())));
+}
+
+#[test]
+fn test_batch_create_returns_owner_id_pairs() {
+    let (env, admin, client) = setup_test_env();
+    let existing = create_wallets(&env, &admin, &client, 1).get(0).unwrap();
+
+    let mut requests: Vec<WalletCreateRequest> = Vec::new(&env);
+    for _ in 0..3 {
+        requests.push_back(create_wallet_request(&env, Address::generate(&env)));
+    }
+    // Failures contribute no pair
+    requests.insert(1, create_wallet_request(&env, existing));
+    let result = client.batch_create_wallets(&admin, &requests);
+
+    assert_eq!(result.successful, 3);
+    assert_eq!(result.owner_ids.len(), 3);
+    let expected = [(0u32, 2u64), (2, 3), (3, 4)];
+    for (i, (index, id)) in expected.iter().enumerate() {
+        let (owner, assigned) = result.owner_ids.get(i as u32).unwrap();
+        assert_eq!(owner, requests.get(*index).unwrap().owner);
+        assert_eq!(assigned, *id);
+        assert_eq!(client.get_wallet(&owner).unwrap().id, *id);
+    }
+}
+
+#[test]
+fn test_soft_deleted_wallet_stays_queryable_until_purged() {
+    let (env, admin, client) = setup_test_env();
+    let owners = create_wallets(&env, &admin, &client, 2);
+    let owner = owners.get(0).unwrap();
+    let id = client.get_wallet(&owner).unwrap().id;
+
+    let mut delete: Vec<Address> = Vec::new(&env);
+    delete.push_back(owner.clone());
+    delete.push_back(Address::generate(&env));
+    let result = client.batch_delete_wallets(&admin, &delete);
+    assert_eq!(result.successful, 1);
+    assert_eq!(result.failed, 1);
+    match result.results.get(1).unwrap() {
+        WalletDeleteResult::Failure(_, code) => assert_eq!(code, 1),
+        _ => panic!("expected deleting an unknown wallet to fail"),
+    }
+
+    // The tombstone is still visible but unusable
+    assert!(!client.is_active(&owner));
+    assert_eq!(client.get_wallet(&owner).unwrap().id, id);
+    assert!(client.get_wallet_profile(&owner).unwrap().deleted);
+    assert_eq!(client.get_live_wallet_count(), 1);
+    let result = client.try_set_wallet_active(&admin, &owner, &true);
+    assert_eq!(result.err(), Some(Ok(BatchWalletError::WalletNotFound.into())));
+    let mut requests: Vec<WalletRecoveryRequest> = Vec::new(&env);
+    requests.push_back(create_recovery_request(&env, owner.clone(), generate_account(&env)));
+    assert_eq!(client.batch_recover_wallets(&admin, &requests, &None).failed, 1);
+
+    // Live wallets are not purged
+    let mut ids: Vec<u64> = Vec::new(&env);
+    ids.push_back(id);
+    ids.push_back(client.get_wallet(&owners.get(1).unwrap()).unwrap().id);
+    assert_eq!(client.purge_deleted(&admin, &ids), 1);
+    assert!(client.get_wallet(&owner).is_none());
+    assert!(client.get_wallet_by_id(&id).is_none());
+    assert!(client.get_wallet(&owners.get(1).unwrap()).is_some());
+}
+
+#[test]
+fn test_guardian_lists_above_limit_rejected() {
+    let (env, admin, client) = setup_test_env();
+    assert_eq!(client.get_max_guardians(), 10);
+    client.set_max_guardians(&admin, &3);
+
+    let mut guardians: Vec<Address> = Vec::new(&env);
+    for _ in 0..3 {
+        guardians.push_back(Address::generate(&env));
+    }
+    let mut too_many = guardians.clone();
+    too_many.push_back(Address::generate(&env));
+
+    let at_limit = Address::generate(&env);
+    let mut create: Vec<WalletCreateRequest> = Vec::new(&env);
+    create.push_back(WalletCreateRequest {
+        owner: at_limit.clone(),
+        guardians: guardians.clone(),
+        active: true,
+    });
+    create.push_back(WalletCreateRequest {
+        owner: Address::generate(&env),
+        guardians: too_many.clone(),
+        active: true,
+    });
+    let result = client.batch_create_wallets(&admin, &create);
+    assert_eq!(result.successful, 1);
+    match result.results.get(1).unwrap() {
+        WalletCreateResult::Failure(_, code) => assert_eq!(code, 4),
+        _ => panic!("expected too many guardians to fail"),
+    }
+
+    let result = client.try_set_guardians(&admin, &at_limit, &too_many);
+    assert_eq!(result.err(), Some(Ok(BatchWalletError::TooManyGuardians.into())));
+    guardians.set(0, Address::generate(&env));
+    client.set_guardians(&admin, &at_limit, &guardians);
+    assert_eq!(client.get_guardians(&at_limit), guardians);
+}
+
+#[test]
+fn test_replace_guardian_rotates_shared_guardian() {
+    let (env, admin, client) = setup_test_env();
+    let shared = Address::generate(&env);
+    let rotated = Address::generate(&env);
+    let other = Address::generate(&env);
+
+    let mut create: Vec<WalletCreateRequest> = Vec::new(&env);
+    for i in 0..4 {
+        let mut guardians: Vec<Address> = Vec::new(&env);
+        guardians.push_back(other.clone());
+        if i != 3 {
+            guardians.push_back(shared.clone());
+        }
+        create.push_back(WalletCreateRequest {
+            owner: Address::generate(&env),
+            guardians,
+            active: true,
+        });
+    }
+    client.batch_create_wallets(&admin, &create);
+
+    // Paged: only ids 1 and 2 are in the first page
+    assert_eq!(client.replace_guardian_range(&admin, &shared, &rotated, &1, &2), 2);
+    assert_eq!(client.replace_guardian_range(&admin, &shared, &rotated, &3, &2), 1);
+    assert_eq!(client.replace_guardian_range(&admin, &shared, &rotated, &1, &4), 0);
+
+    for (i, request) in create.iter().enumerate() {
+        let guardians = client.get_guardians(&request.owner);
+        assert!(!guardians.contains(&shared));
+        assert_eq!(guardians.contains(&rotated), i != 3);
+        assert_eq!(guardians.get(0), Some(other.clone()));
+    }
+
+    // A wallet already holding both guardians loses the rotated-out one
+    let holds_both = create.get(0).unwrap().owner;
+    assert_eq!(client.replace_guardian_range(&admin, &rotated, &other, &1, &1), 1);
+    assert_eq!(client.get_guardians(&holds_both), Vec::from_array(&env, [other.clone()]));
+}
+
+#[test]
+fn test_batch_recovery_respects_recovery_delay() {
+    let (env, admin, client) = setup_test_env();
+    let owners = create_wallets(&env, &admin, &client, 2);
+    let (pending_owner, direct_owner) = (owners.get(0).unwrap(), owners.get(1).unwrap());
+    let target = generate_account(&env);
+    client.set_recovery_delay(&admin, &100);
+    client.initiate_recovery(&admin, &pending_owner, &target);
+
+    let mut requests: Vec<WalletRecoveryRequest> = Vec::new(&env);
+    requests.push_back(create_recovery_request(&env, pending_owner.clone(), target.clone()));
+    requests.push_back(create_recovery_request(
+        &env,
+        direct_owner.clone(),
+        generate_account(&env),
+    ));
+
+    // Neither request may bypass the timelock yet
+    let result = client.batch_recover_wallets(&admin, &requests, &None);
+    assert_eq!(result.successful, 0);
+    for item in result.results.iter() {
+        match item {
+            WalletRecoveryResult::Failure(_, _, code) => assert_eq!(code, 6),
+            _ => panic!("expected timelocked recovery to fail"),
+        }
+    }
+
+    // Once unlocked, only the initiated recovery goes through
+    env.ledger().with_mut(|li| li.sequence_number += 100);
+    let result = client.batch_recover_wallets(&admin, &requests, &None);
+    assert_eq!(result.successful, 1);
+    assert!(client.get_wallet(&target).is_some());
+    assert_eq!(client.get_pending_recovery(&pending_owner), None);
+    assert!(client.get_wallet(&direct_owner).is_some());
+}
+
+#[test]
+fn test_deleting_wallet_drops_pending_recovery() {
+    let (env, admin, client) = setup_test_env();
+    let owner = create_wallets(&env, &admin, &client, 1).get(0).unwrap();
+    client.set_recovery_delay(&admin, &100);
+    client.initiate_recovery(&admin, &owner, &generate_account(&env));
+
+    let owners = Vec::from_array(&env, [owner.clone()]);
+    client.batch_delete_wallets(&admin, &owners);
+    assert_eq!(client.get_pending_recovery(&owner), None);
+    client.purge_deleted(&admin, &Vec::from_array(&env, [1u64]));
+
+    // A fresh wallet for the same owner must not inherit the old recovery
+    let mut create: Vec<WalletCreateRequest> = Vec::new(&env);
+    create.push_back(create_wallet_request(&env, owner.clone()));
+    client.batch_create_wallets(&admin, &create);
+    env.ledger().with_mut(|li| li.sequence_number += 100);
+    let result = client.try_execute_recovery(&admin, &owner);
+    assert_eq!(result.err(), Some(Ok(BatchWalletError::NoPendingRecovery.into())));
+    assert!(client.get_wallet(&owner).is_some());
+}
+
+#[test]
+fn test_pending_recovery_reports_target_and_unlock_ledger() {
+    let (env, admin, client) = setup_test_env();
+    let owner = create_wallets(&env, &admin, &client, 1).get(0).unwrap();
+    let new_owner = generate_account(&env);
+    client.set_recovery_delay(&admin, &100);
+    assert_eq!(client.get_pending_recovery(&owner), None);
+
+    client.initiate_recovery(&admin, &owner, &new_owner);
+    assert_eq!(
+        client.get_pending_recovery(&owner),
+        Some((new_owner.clone(), 12_445))
+    );
+    let result = client.try_execute_recovery(&admin, &owner);
+    assert_eq!(result.err(), Some(Ok(BatchWalletError::RecoveryLocked.into())));
+
+    // The owner notices and cancels
+    client.cancel_recovery(&owner);
+    assert_eq!(client.get_pending_recovery(&owner), None);
+
+    client.initiate_recovery(&admin, &owner, &new_owner);
+    env.ledger().with_mut(|li| li.sequence_number += 100);
+    client.execute_recovery(&admin, &owner);
+    assert_eq!(client.get_pending_recovery(&owner), None);
+    assert!(client.get_wallet(&owner).is_none());
+    assert_eq!(client.get_wallet(&new_owner).unwrap().id, 1);
 }
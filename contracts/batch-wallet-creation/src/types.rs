@@ -1,11 +1,33 @@
-use soroban_sdk::{contracttype, symbol_short, Address, Env, Vec};
+use soroban_sdk::{contracttype, symbol_short, Address, Bytes, BytesN, Env, String, Vec};
 
 pub const MAX_BATCH_SIZE: u32 = 100;
 
+/// TTL threshold (in ledgers) below which stored batch results are extended (~1 day).
+pub const RESULT_TTL_THRESHOLD: u32 = 17_280;
+
+/// TTL (in ledgers) stored batch results are extended to (~30 days).
+pub const RESULT_TTL_EXTEND: u32 = 518_400;
+
+/// TTL threshold (in ledgers) below which `bump_wallets_ttl` extends a wallet (~1 day).
+pub const WALLET_TTL_THRESHOLD: u32 = 17_280;
+
+/// TTL (in ledgers) `bump_wallets_ttl` extends wallets to (~30 days).
+pub const WALLET_TTL_EXTEND: u32 = 518_400;
+
+/// Guardians a wallet may list unless the admin configures another limit.
+pub const DEFAULT_MAX_GUARDIANS: u32 = 10;
+
+/// Leading bytes of an external id that identify its tenant.
+pub const EXTERNAL_PREFIX_LEN: u32 = 4;
+
 #[derive(Clone, Debug)]
 #[contracttype]
 pub struct WalletCreateRequest {
     pub owner: Address,
+    /// Addresses allowed to assist in recovering the wallet
+    pub guardians: Vec<Address>,
+    /// Whether the wallet starts active; dormant imports pass `false`
+    pub active: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -15,6 +37,25 @@ pub struct WalletRecoveryRequest {
     pub new_owner: Address,
 }
 
+/// A recovery that also replaces the wallet's guardian set and threshold.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct RecoveryWithGuardians {
+    pub old_owner: Address,
+    pub new_owner: Address,
+    pub guardians: Vec<Address>,
+    pub threshold: u32,
+}
+
+/// A timelocked recovery started by `initiate_recovery`.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct PendingRecovery {
+    pub new_owner: Address,
+    /// First ledger at which `execute_recovery` may run
+    pub unlock_ledger: u32,
+}
+
 #[derive(Clone, Debug)]
 #[contracttype]
 pub enum WalletCreateResult {
@@ -27,6 +68,29 @@ pub enum WalletCreateResult {
 pub enum WalletRecoveryResult {
     Success(Address, Address),
     Failure(Address, Address, u32),
+    /// Identity recovery (`old_owner == new_owner`) accepted without changes
+    NoOp(Address),
+}
+
+#[derive(Clone, Debug)]
+#[contracttype]
+pub enum WalletFreezeResult {
+    Success(Address),
+    Failure(Address, u32),
+}
+
+#[derive(Clone, Debug)]
+#[contracttype]
+pub enum WalletDeleteResult {
+    Success(Address),
+    Failure(Address, u32),
+}
+
+#[derive(Clone, Debug)]
+#[contracttype]
+pub enum ExternalIdLinkResult {
+    Success(Address, BytesN<32>),
+    Failure(Address, BytesN<32>, u32),
 }
 
 #[derive(Clone, Debug)]
@@ -36,6 +100,8 @@ pub struct BatchCreateResult {
     pub successful: u32,
     pub failed: u32,
     pub results: Vec<WalletCreateResult>,
+    /// `(owner, id)` for every wallet created, in request order
+    pub owner_ids: Vec<(Address, u64)>,
 }
 
 #[derive(Clone, Debug)]
@@ -47,6 +113,48 @@ pub struct BatchRecoveryResult {
     pub results: Vec<WalletRecoveryResult>,
 }
 
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct BatchFreezeResult {
+    pub total_requests: u32,
+    pub successful: u32,
+    pub failed: u32,
+    pub results: Vec<WalletFreezeResult>,
+}
+
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct BatchDeleteResult {
+    pub total_requests: u32,
+    pub successful: u32,
+    pub failed: u32,
+    pub results: Vec<WalletDeleteResult>,
+}
+
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct BatchLinkResult {
+    pub total_requests: u32,
+    pub successful: u32,
+    pub failed: u32,
+    pub results: Vec<ExternalIdLinkResult>,
+}
+
+/// Admin-configurable limits, returned as one bundle by `get_wallet_config`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct WalletConfig {
+    pub max_batch_size: u32,
+    pub max_recovery_batch_size: u32,
+    /// Ledgers a recovery must wait before executing (0 = immediate)
+    pub recovery_delay_ledgers: u32,
+    /// Maximum number of wallets that may be created (0 = unlimited)
+    pub max_wallets: u64,
+    /// Ledgers required between batches (0 = no cooldown)
+    pub batch_cooldown_ledgers: u32,
+    pub allow_contract_destinations: bool,
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
@@ -54,6 +162,37 @@ pub enum DataKey {
     TotalBatches,
     TotalWalletsCreated,
     Wallets(Address), // Map of address to wallet id or something
+    Frozen(Address),
+    AllowContractDestinations,
+    IdentityRecoveryOk,
+    RecoveryResult(BytesN<32>),
+    ExternalId(Address),
+    ExternalIdOwner(BytesN<32>),
+    MaxBatchSize,
+    MaxRecoveryBatchSize,
+    MaxWallets,
+    WalletById(u64),
+    MigrationMode,
+    CreationPaused,
+    RecoveryPaused,
+    Guardians(Address),
+    MinGuardians,
+    NextWalletId,
+    ReservedId(u64),
+    Inactive(Address),
+    ExternalPrefixCount(Bytes),
+    GuardianThreshold(Address),
+    RecoveryAdmin,
+    Label(Address),
+    CreatedLedger(Address),
+    RecoveryNonce(Address),
+    Delegate(Address),
+    Deleted(Address),
+    DeletedWallets,
+    MaxGuardians,
+    RecoveryDelay,
+    PendingRecovery(Address),
+    MinWalletId,
 }
 
 #[derive(Clone, Debug)]
@@ -64,6 +203,25 @@ pub struct Wallet {
     pub created_at: u64,
 }
 
+/// Everything known about a wallet, see `get_wallet_profile`.
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct WalletProfile {
+    pub wallet: Wallet,
+    pub frozen: bool,
+    pub active: bool,
+    pub guardians: Vec<Address>,
+    pub threshold: u32,
+    pub external_id: Option<BytesN<32>>,
+    pub label: Option<String>,
+    /// Ledger the wallet was created in; 0 for wallets predating tracking
+    pub created_ledger: u32,
+    /// Number of times the wallet has been recovered to a new owner
+    pub recovery_nonce: u32,
+    /// Whether the wallet is a tombstone left by `batch_delete_wallets`
+    pub deleted: bool,
+}
+
 pub struct WalletEvents;
 
 impl WalletEvents {
@@ -97,6 +255,43 @@ impl WalletEvents {
         env.events().publish(topics, (successful, failed));
     }
 
+    pub fn wallet_frozen(env: &Env, owner: &Address) {
+        let topics = (symbol_short!("wallet"), symbol_short!("frozen"));
+        env.events().publish(topics, owner.clone());
+    }
+
+    pub fn wallet_deleted(env: &Env, owner: &Address) {
+        let topics = (symbol_short!("wallet"), symbol_short!("deleted"));
+        env.events().publish(topics, owner.clone());
+    }
+
+    pub fn wallet_purged(env: &Env, owner: &Address, wallet_id: u64) {
+        let topics = (symbol_short!("wallet"), symbol_short!("purged"));
+        env.events().publish(topics, (owner.clone(), wallet_id));
+    }
+
+    pub fn wallet_unfrozen(env: &Env, owner: &Address) {
+        let topics = (symbol_short!("wallet"), symbol_short!("unfrozen"));
+        env.events().publish(topics, owner.clone());
+    }
+
+    pub fn external_id_linked(env: &Env, owner: &Address, external_id: &BytesN<32>) {
+        let topics = (symbol_short!("wallet"), symbol_short!("linked"));
+        env.events()
+            .publish(topics, (owner.clone(), external_id.clone()));
+    }
+
+    pub fn external_id_unlinked(env: &Env, owner: &Address, external_id: &BytesN<32>) {
+        let topics = (symbol_short!("wallet"), symbol_short!("unlinked"));
+        env.events()
+            .publish(topics, (owner.clone(), external_id.clone()));
+    }
+
+    pub fn wallet_id_remapped(env: &Env, owner: &Address, old_id: u64, new_id: u64) {
+        let topics = (symbol_short!("wallet"), symbol_short!("remapped"));
+        env.events().publish(topics, (owner.clone(), old_id, new_id));
+    }
+
     pub fn recovery_started(env: &Env, batch_id: u64, request_count: u32) {
         let topics = (symbol_short!("recovery"), symbol_short!("started"));
         env.events().publish(topics, (batch_id, request_count));
@@ -128,6 +323,22 @@ impl WalletEvents {
         );
     }
 
+    pub fn recovery_initiated(
+        env: &Env,
+        old_owner: &Address,
+        new_owner: &Address,
+        unlock_ledger: u32,
+    ) {
+        let topics = (symbol_short!("recovery"), symbol_short!("initiated"));
+        env.events()
+            .publish(topics, (old_owner.clone(), new_owner.clone(), unlock_ledger));
+    }
+
+    pub fn recovery_cancelled(env: &Env, old_owner: &Address, new_owner: &Address) {
+        let topics = (symbol_short!("recovery"), symbol_short!("cancelled"));
+        env.events().publish(topics, (old_owner.clone(), new_owner.clone()));
+    }
+
     pub fn recovery_completed(
         env: &Env,
         batch_id: u64,
@@ -8,8 +8,42 @@ pub fn validate_address(_address: &Address) -> Result<(), ()> {
     Ok(())
 }
 
+/// Returns true if the address is a contract (`C...` strkey) rather than an account.
+pub fn is_contract_address(address: &Address) -> bool {
+    let strkey = address.to_string();
+    let mut buf = [0u8; 56];
+    if strkey.len() as usize != buf.len() {
+        return false;
+    }
+    strkey.copy_into_slice(&mut buf);
+    buf[0] == b'C'
+}
+
 /// Checks if a wallet already exists for the given address.
 pub fn wallet_exists(env: &Env, address: &Address) -> bool {
     use crate::types::DataKey;
     env.storage().persistent().has(&DataKey::Wallets(address.clone()))
-}
\ No newline at end of file
+}
+
+/// Checks if the wallet of the given address has been soft-deleted.
+pub fn is_deleted(env: &Env, address: &Address) -> bool {
+    use crate::types::DataKey;
+    env.storage()
+        .persistent()
+        .get(&DataKey::Deleted(address.clone()))
+        .unwrap_or(false)
+}
+
+/// Checks if a usable wallet exists, i.e. one that is not a tombstone.
+pub fn wallet_live(env: &Env, address: &Address) -> bool {
+    wallet_exists(env, address) && !is_deleted(env, address)
+}
+
+/// Checks if the wallet of the given address is frozen.
+pub fn is_frozen(env: &Env, address: &Address) -> bool {
+    use crate::types::DataKey;
+    env.storage()
+        .persistent()
+        .get(&DataKey::Frozen(address.clone()))
+        .unwrap_or(false)
+}